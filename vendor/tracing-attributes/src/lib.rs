@@ -43,7 +43,7 @@ use proc_macro::TokenStream;
 use quote::{quote, quote_spanned, ToTokens};
 use syn::{
     spanned::Spanned, AttributeArgs, FnArg, Ident, ItemFn, Lit, LitInt, Meta, MetaNameValue,
-    NestedMeta, Pat, PatIdent, PatType, Signature,
+    NestedMeta, Pat, PatIdent, PatType, ReturnType, Signature, Type,
 };
 
 /// Instruments a function to create and enter a `tracing` [span] every time
@@ -74,6 +74,24 @@ use syn::{
 /// }
 /// # fn main() {}
 /// ```
+///
+/// `level` must be a literal (a string or an integer 1-5): like any other
+/// built-in attribute argument, it's parsed with [`syn::Lit`], so it can't be
+/// a `const` or the result of `cfg!(...)`. To select a level at compile time
+/// based on a feature flag, stack two `cfg_attr`s instead of trying to
+/// parameterize a single `level = ...`:
+/// ```
+/// # use tracing_attributes::instrument;
+/// #[cfg_attr(feature = "verbose-tracing", instrument(level = "trace"))]
+/// #[cfg_attr(not(feature = "verbose-tracing"), instrument(level = "info"))]
+/// pub fn my_function() {
+///     // ...
+/// }
+/// # fn main() {}
+/// ```
+///
+/// [`syn::Lit`]: https://docs.rs/syn/1/syn/enum.Lit.html
+///
 /// Overriding the generated span's target:
 /// ```
 /// # use tracing_attributes::instrument;
@@ -84,6 +102,104 @@ use syn::{
 /// # fn main() {}
 /// ```
 ///
+/// Like `level`, `target` must be a string literal — `target =
+/// plugin.name()` is rejected, and for a deeper reason than `level`'s: it
+/// isn't just that [`syn::Lit`] can't parse an arbitrary expression, it's
+/// that the generated span's `Metadata` is a single `static` shared by
+/// every call to the function (this is how `tracing-core` 0.1 avoids
+/// allocating metadata per-call), so there is nowhere for a *per-call*
+/// target computed at runtime to live even if it could be parsed. If the
+/// target only needs to vary at compile time, stack `cfg_attr`s the same
+/// way as for `level`:
+/// ```
+/// # use tracing_attributes::instrument;
+/// #[cfg_attr(feature = "verbose-tracing", instrument(target = "my_crate::verbose"))]
+/// #[cfg_attr(not(feature = "verbose-tracing"), instrument(target = "my_crate"))]
+/// pub fn my_function() {
+///     // ...
+/// }
+/// # fn main() {}
+/// ```
+/// A genuinely per-call target — one that depends on a runtime value like
+/// `plugin.name()` — can't come from `#[instrument]` at all in this
+/// version of `tracing`; it would need a hand-written `tracing::span!`
+/// call inside the function body using a dynamically-chosen `Metadata`,
+/// which `tracing-core` 0.1's macros don't support either (`span!`'s
+/// `target:` argument has exactly the same static-`Metadata` constraint).
+///
+/// By default, every argument is recorded using its `fmt::Debug`
+/// implementation. Arguments named in a `display(...)` grouping are recorded
+/// with `fmt::Display` instead:
+/// ```
+/// # use tracing_attributes::instrument;
+/// #[instrument(display(name))]
+/// pub fn greet(name: &str, id: u64) {
+///     // `name` is recorded with Display, `id` with Debug.
+/// }
+/// # fn main() {}
+/// ```
+///
+/// The return value of an instrumented function can be recorded as an event
+/// by adding `ret`. By default, the value is recorded with `fmt::Debug`; a
+/// `ret(Display)` grouping records it with `fmt::Display` instead, which is
+/// useful for types whose `Debug` output embeds data that shouldn't be
+/// logged. `err` behaves the same way for the `Err` variant of a function
+/// returning a `Result`, except its event is always recorded at `ERROR`,
+/// regardless of the span's own `level`:
+/// ```
+/// # use tracing_attributes::instrument;
+/// #[instrument(ret(Display))]
+/// pub fn greeting(name: &str) -> String {
+///     format!("hello, {}", name)
+/// }
+/// # fn main() {}
+/// ```
+///
+/// `ret` and `err` can be combined on a function returning a `Result` to log
+/// the two variants distinctly: `err`'s `ERROR` event fires (with the error
+/// value) only on `Err`, and `ret`'s event fires (with the unwrapped `Ok`
+/// value, not the whole `Result`) only on `Ok`, so a given call produces at
+/// most one of the two events, never both:
+/// ```
+/// # use tracing_attributes::instrument;
+/// #[instrument(ret, err)]
+/// pub fn divide(a: u32, b: u32) -> Result<u32, &'static str> {
+///     if b == 0 {
+///         Err("division by zero")
+///     } else {
+///         Ok(a / b)
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// There is no `structured(...)` grouping for recording an argument via a
+/// `Valuable`-style structured trait instead of `fmt::Debug`: that would
+/// require both the `valuable` crate and `tracing-core` support for
+/// non-primitive field values, neither of which this version of
+/// `tracing-core` has. Naming an argument in `structured(...)` is a compile
+/// error rather than a silent fallback to `Debug`:
+/// ```compile_fail
+/// # use tracing_attributes::instrument;
+/// #[instrument(structured(config))]
+/// pub fn configure(config: std::collections::HashMap<String, String>) {
+///     // ...
+/// }
+/// # fn main() {}
+/// ```
+///
+/// The same is true of capturing an argument via `serde::Serialize` instead
+/// of `fmt::Debug` (e.g. a hypothetical `fields(config = serde(config))`):
+/// the blocker isn't which crate provides the structured trait, it's that
+/// `tracing_core::field::Value` in this version of `tracing-core` only has
+/// primitive impls (`i64`, `u64`, `f64`, `bool`, `str`) plus a catch-all
+/// `fmt::Debug`/`fmt::Display` fallback — there's no hook for a value that
+/// knows how to serialize itself into a structured tree of fields rather
+/// than a single opaque string. Use `structured(...)`'s rejection above as
+/// the canonical error for this whole category of request; recording
+/// `config` with `fmt::Debug` (the default) is the closest available
+/// approximation.
+///
 /// When the `async-await` feature flag is enabled, `async fn`s may also be
 /// instrumented:
 ///
@@ -100,10 +216,61 @@ use syn::{
 /// # fn main() {}
 /// ```
 ///
+/// Instrumenting a trait's default method works the same way. Since every
+/// implementor calls through the same default body, a `self`/`&self`/`&mut
+/// self` receiver causes the macro to record an extra `self_type` field
+/// holding `std::any::type_name::<Self>()`, so spans from different
+/// implementors of the trait can be told apart:
+/// ```
+/// # use tracing_attributes::instrument;
+/// trait Greeter {
+///     #[instrument]
+///     fn greet(&self) {
+///         // the generated span includes a `self_type` field naming the
+///         // concrete implementor.
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// The generated span can be bound to a name in the function body with
+/// `bind(...)`, so the body can record fields on it directly:
+/// ```
+/// # use tracing_attributes::instrument;
+/// #[instrument(bind(my_span))]
+/// pub fn my_function(my_arg: usize) {
+///     if my_arg == 0 {
+///         my_span.record("my_arg", &"none");
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// An `enabled = "feature_name"` argument compiles instrumentation out
+/// entirely when `feature_name` is off, rather than merely skipping it at
+/// runtime: the function expands to the plain, unmodified body under
+/// `#[cfg(not(feature = "feature_name"))]`, with no span, no field capture,
+/// and no `tracing` dependency on the hot path at all when the feature is
+/// disabled. This is for call sites too hot to pay even the cost of a
+/// disabled span's `enabled()` check:
+/// ```
+/// # use tracing_attributes::instrument;
+/// #[instrument(enabled = "detailed-tracing")]
+/// pub fn hot_loop_body(i: usize) {
+///     // ...
+/// }
+/// # fn main() {}
+/// ```
+///
 /// # Notes
 /// - All argument types must implement `fmt::Debug`
 /// - When using `#[instrument]` on an `async fn`, the `tracing_futures` must
 ///   also be specified as a dependency in `Cargo.toml`.
+/// - A sync `fn` that returns `Pin<Box<dyn Future<..> + ..>>` (the shape
+///   `#[async_trait]` expands an `async fn` trait method into) is detected
+///   and instrumented the same way as an `async fn`: the returned future is
+///   wrapped with `tracing_futures::Instrument` and re-boxed, so the span
+///   covers polling rather than just the synchronous call that builds it.
 ///
 /// [span]: https://docs.rs/tracing/0.1.5/tracing/span/index.html
 /// [`tracing`]: https://github.com/tokio-rs/tracing
@@ -156,9 +323,78 @@ pub fn instrument(args: TokenStream, item: TokenStream) -> TokenStream {
         .collect();
     let param_names_clone = param_names.clone();
 
+    if let Some(name) = structured_fields(&args).first() {
+        let message = format!(
+            "`structured({name})` is not supported: recording an argument \
+             structurally (whether via the `valuable` crate's `Valuable` \
+             trait, `serde::Serialize`, or any other structured-capture \
+             trait) requires `tracing-core` support for non-primitive field \
+             values, which is not available in this version of \
+             `tracing-core`. Remove `structured(...)` and let `{name}` be \
+             recorded with `fmt::Debug`, or use `display({name})` if it \
+             implements `fmt::Display`.",
+            name = name,
+        );
+        return quote_spanned!(name.span()=> compile_error!(#message);).into();
+    }
+
+    let level = level(&args);
+    let target = target(&args);
+    let span_name = name(&args, ident_str);
+    let display_fields = display_fields(&args);
+    let ret_fmt = fmt_flag(&args, "ret");
+    let err_fmt = fmt_flag(&args, "err");
+    let bind_name = bind_name(&args);
+
+    // If `ret` and/or `err` were given, capture the block's value in a
+    // local so it can be logged before being returned, rather than simply
+    // inlining the block as the tail expression.
+    let instrumented_block = if ret_fmt.is_none() && err_fmt.is_none() {
+        quote_spanned!(block.span()=> #block)
+    } else {
+        let ret_event = ret_fmt.map(|fmt| {
+            if err_fmt.is_some() {
+                // With `err` also present, the function returns a `Result`;
+                // only log `ret` on the `Ok` branch, and log the unwrapped
+                // value rather than the whole `Result`, so a given call
+                // produces at most one of `ret`/`err`'s events, never both.
+                let value = format_value(fmt, quote!(__tracing_attr_ret_ok));
+                quote_spanned!(block.span()=>
+                    if let Ok(ref __tracing_attr_ret_ok) = __tracing_attr_ret {
+                        tracing::event!(target: #target, #level, ret = #value);
+                    }
+                )
+            } else {
+                let value = format_value(fmt, quote!(__tracing_attr_ret));
+                quote_spanned!(block.span()=>
+                    tracing::event!(target: #target, #level, ret = #value);
+                )
+            }
+        });
+        let err_event = err_fmt.map(|fmt| {
+            let value = format_value(fmt, quote!(e));
+            quote_spanned!(block.span()=>
+                if let Err(ref e) = __tracing_attr_ret {
+                    tracing::event!(target: #target, tracing::Level::ERROR, error = #value);
+                }
+            )
+        });
+        quote_spanned!(block.span()=> {
+            let __tracing_attr_ret = #block;
+            #err_event
+            #ret_event
+            __tracing_attr_ret
+        })
+    };
+
     // Generate the instrumented function body.
     // If the function is an `async fn`, this will wrap it in an async block,
-    // which is `instrument`ed using `tracing-futures`. Otherwise, this will
+    // which is `instrument`ed using `tracing-futures`. If it's a sync `fn`
+    // that returns `Pin<Box<dyn Future<..> + ..>>` -- the shape `async_trait`
+    // desugars `async fn` trait methods into -- the returned boxed future is
+    // instrumented and re-boxed instead, since there's no `async` block here
+    // to wrap and nothing to await: the function itself is still sync, it
+    // just hands back a future for the caller to poll. Otherwise, this will
     // enter the span and then perform the rest of the body.
     let body = if asyncness.is_some() {
         // We can't quote these keywords in the `quote!` macro, since their
@@ -170,24 +406,66 @@ pub fn instrument(args: TokenStream, item: TokenStream) -> TokenStream {
         let await_kwd = syn::Ident::new("await", block.span());
         quote_spanned! {block.span()=>
             tracing_futures::Instrument::instrument(
-                #async_kwd move { #block },
+                #async_kwd move { #instrumented_block },
                 __tracing_attr_span
             )
                 .#await_kwd
         }
+    } else if returns_boxed_future(&return_type) {
+        quote_spanned! {block.span()=>
+            std::boxed::Box::pin(tracing_futures::Instrument::instrument(
+                #instrumented_block,
+                __tracing_attr_span
+            ))
+        }
     } else {
         quote_spanned!(block.span()=>
             let __tracing_attr_guard = __tracing_attr_span.enter();
-            #block
+            #instrumented_block
         )
     };
 
-    let level = level(&args);
-    let target = target(&args);
-    let span_name = name(&args, ident_str);
+    // A method with a `self`/`&self`/`&mut self` receiver is instrumented
+    // the same way whether it's an inherent impl or a trait's default
+    // implementation. In the latter case, every implementor's call produces
+    // a span with the same name, so traces can't tell them apart. Recording
+    // the concrete `Self` type as a field disambiguates them without
+    // requiring the caller to opt in.
+    let has_self_receiver = params.iter().any(|p| matches!(p, FnArg::Receiver(_)));
+
+    let field_values = param_names
+        .iter()
+        .zip(param_names_clone.iter())
+        .map(|(name, name_clone)| {
+            if display_fields.iter().any(|d| d == name) {
+                quote!(#name = tracing::field::display(&#name_clone))
+            } else {
+                quote!(#name = tracing::field::debug(&#name_clone))
+            }
+        })
+        .chain(if has_self_receiver {
+            Some(quote!(self_type = tracing::field::display(std::any::type_name::<Self>())))
+        } else {
+            None
+        });
+
+    let bind_let = bind_name.map(|name| quote!(let #name = &__tracing_attr_span;));
 
-    quote!(
+    // A function that diverges (`-> !`) never reaches the end of its body,
+    // so the span guard's implicit drop there is unreachable by
+    // construction. That's expected, not a bug, so silence the lint rather
+    // than let `#![deny(warnings)]`-style crates fail to build.
+    let is_never_return =
+        matches!(return_type, ReturnType::Type(_, ref ty) if matches!(**ty, Type::Never(_)));
+    let allow_unreachable = if is_never_return {
+        quote!(#[allow(unreachable_code)])
+    } else {
+        quote!()
+    };
+
+    let instrumented_fn = quote!(
         #(#attrs) *
+        #allow_unreachable
         #vis #constness #unsafety #asyncness #abi fn #ident<#gen_params>(#params) #return_type
         #where_clause
         {
@@ -195,12 +473,216 @@ pub fn instrument(args: TokenStream, item: TokenStream) -> TokenStream {
                 target: #target,
                 #level,
                 #span_name,
-                #(#param_names = tracing::field::debug(&#param_names_clone)),*
+                #(#field_values),*
             );
+            #bind_let
             #body
         }
-    )
-    .into()
+    );
+
+    match enabled_feature(&args) {
+        None => instrumented_fn.into(),
+        Some(feature) => quote!(
+            #[cfg(feature = #feature)]
+            #instrumented_fn
+
+            #[cfg(not(feature = #feature))]
+            #(#attrs) *
+            #vis #constness #unsafety #asyncness #abi fn #ident<#gen_params>(#params) #return_type
+            #where_clause
+            #block
+        )
+        .into(),
+    }
+}
+
+/// Returns `true` if `return_type` is (syntactically) `Pin<Box<dyn Future<..>
+/// + ..>>`, the shape `#[async_trait]` desugars an `async fn` trait method
+/// into once it's stripped of its `async` keyword. Only the last path
+/// segment of `Pin` and `Box` is checked, so `std::pin::Pin<std::boxed::Box<..>>`
+/// and other module-qualified spellings match too.
+fn returns_boxed_future(return_type: &ReturnType) -> bool {
+    let ty = match return_type {
+        ReturnType::Type(_, ty) => &**ty,
+        ReturnType::Default => return false,
+    };
+    let pin_args = match last_path_segment(ty).filter(|seg| seg.ident == "Pin") {
+        Some(seg) => &seg.arguments,
+        None => return false,
+    };
+    let boxed_ty = match angle_bracketed_type(pin_args) {
+        Some(ty) => ty,
+        None => return false,
+    };
+    let box_args = match last_path_segment(boxed_ty).filter(|seg| seg.ident == "Box") {
+        Some(seg) => &seg.arguments,
+        None => return false,
+    };
+    let trait_object = match angle_bracketed_type(box_args) {
+        Some(Type::TraitObject(trait_object)) => trait_object,
+        _ => return false,
+    };
+    trait_object.bounds.iter().any(|bound| match bound {
+        syn::TypeParamBound::Trait(bound) => {
+            last_path_segment(&Type::Path(syn::TypePath {
+                qself: None,
+                path: bound.path.clone(),
+            }))
+            .map_or(false, |seg| seg.ident == "Future")
+        }
+        _ => false,
+    })
+}
+
+/// Returns the last segment of `ty`'s path, if `ty` is a (possibly
+/// module-qualified) path type.
+fn last_path_segment(ty: &Type) -> Option<&syn::PathSegment> {
+    match ty {
+        Type::Path(syn::TypePath { path, .. }) => path.segments.last(),
+        _ => None,
+    }
+}
+
+/// Returns the first type argument of a single angle-bracketed generic
+/// argument list, e.g. the `T` in `Foo<T>`.
+fn angle_bracketed_type(args: &syn::PathArguments) -> Option<&Type> {
+    match args {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+/// Returns the identifier to bind the generated span to, from a
+/// `bind(name)` argument, e.g. `#[instrument(bind(request_span))]`. This
+/// lets the function body refer to its own span (for example, to call
+/// [`Span::record`]) without having to look it up through
+/// [`tracing::Span::current`].
+///
+/// Note the argument is `bind(name)`, a parenthesized identifier, not
+/// `bind = name`: `syn`'s `NameValue` grammar requires the right-hand side
+/// of a `name = value` argument to be a literal, so a bare identifier can't
+/// be written that way. `bind(name)` mirrors the existing `display(...)`
+/// and `ret(...)` grouping syntax instead.
+///
+/// [`Span::record`]: https://docs.rs/tracing/0.1/tracing/span/struct.Span.html#method.record
+fn bind_name(args: &AttributeArgs) -> Option<Ident> {
+    args.iter().find_map(|arg| match arg {
+        NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("bind") => {
+            list.nested.iter().find_map(|nested| match nested {
+                NestedMeta::Meta(Meta::Path(path)) => path.get_ident().cloned(),
+                _ => None,
+            })
+        }
+        _ => None,
+    })
+}
+
+/// Returns the feature name named by an `enabled = "feature_name"` argument,
+/// e.g. `#[instrument(enabled = "detailed-tracing")]`, if one was given.
+fn enabled_feature(args: &AttributeArgs) -> Option<String> {
+    args.iter().find_map(|arg| match arg {
+        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+            path,
+            lit: Lit::Str(s),
+            ..
+        })) if path.is_ident("enabled") => Some(s.value()),
+        _ => None,
+    })
+}
+
+/// Returns the names of the parameters listed in a `display(...)` grouping,
+/// e.g. `#[instrument(display(a, b))]`. Fields named here are recorded using
+/// `fmt::Display` rather than the default `fmt::Debug`.
+fn display_fields(args: &AttributeArgs) -> Vec<Ident> {
+    args.iter()
+        .filter_map(|arg| match arg {
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("display") => {
+                Some(list.nested.iter().filter_map(|nested| match nested {
+                    NestedMeta::Meta(Meta::Path(path)) => path.get_ident().cloned(),
+                    _ => None,
+                }))
+            }
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// Returns the names of the parameters listed in a `structured(...)`
+/// grouping, e.g. `#[instrument(structured(config))]`.
+///
+/// These names are never actually recorded structurally: doing so would
+/// require the `valuable` crate's `Valuable` trait and `tracing-core`
+/// support for non-primitive field values, neither of which exists in this
+/// version of `tracing-core`. This function exists only so the macro can
+/// detect the grouping and reject it with a compile error, rather than
+/// silently falling back to `fmt::Debug` and leaving the caller to wonder
+/// why their JSON output isn't nested.
+fn structured_fields(args: &AttributeArgs) -> Vec<Ident> {
+    args.iter()
+        .filter_map(|arg| match arg {
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("structured") => {
+                Some(list.nested.iter().filter_map(|nested| match nested {
+                    NestedMeta::Meta(Meta::Path(path)) => path.get_ident().cloned(),
+                    _ => None,
+                }))
+            }
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// The formatting trait to use when recording a `ret` or `err` value.
+#[derive(Clone, Copy)]
+enum FmtKind {
+    Debug,
+    Display,
+}
+
+impl FmtKind {
+    fn field_fn(self) -> impl ToTokens {
+        match self {
+            FmtKind::Debug => quote!(tracing::field::debug),
+            FmtKind::Display => quote!(tracing::field::display),
+        }
+    }
+}
+
+/// Formats `value` (a bare expression, such as a local variable) using the
+/// given `FmtKind`, for use as a field value in a generated `tracing::event!`
+/// invocation.
+fn format_value(kind: FmtKind, value: impl ToTokens) -> impl ToTokens {
+    let field_fn = kind.field_fn();
+    quote!(#field_fn(&#value))
+}
+
+/// Returns the `FmtKind` requested for the argument named `name`, e.g.
+/// `ret` or `ret(Display)`. A bare flag (`ret`) or `ret(Debug)` both select
+/// `FmtKind::Debug`; `ret(Display)` selects `FmtKind::Display`. Returns
+/// `None` if `name` was not given at all.
+fn fmt_flag(args: &AttributeArgs, name: &str) -> Option<FmtKind> {
+    args.iter().find_map(|arg| match arg {
+        NestedMeta::Meta(Meta::Path(path)) if path.is_ident(name) => Some(FmtKind::Debug),
+        NestedMeta::Meta(Meta::List(list)) if list.path.is_ident(name) => {
+            match list.nested.iter().next() {
+                Some(NestedMeta::Meta(Meta::Path(path))) if path.is_ident("Display") => {
+                    Some(FmtKind::Display)
+                }
+                Some(NestedMeta::Meta(Meta::Path(path))) if path.is_ident("Debug") => {
+                    Some(FmtKind::Debug)
+                }
+                // Anything else (including an empty list) falls back to the
+                // default `Debug` formatting, mirroring `display_fields`'s
+                // leniency about unrecognized nested syntax.
+                _ => Some(FmtKind::Debug),
+            }
+        }
+        _ => None,
+    })
 }
 
 fn level(args: &AttributeArgs) -> impl ToTokens {
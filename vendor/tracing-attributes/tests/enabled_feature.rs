@@ -0,0 +1,36 @@
+// `#[instrument(enabled = "some-feature")]` compiles the instrumented version
+// of the function in when `some-feature` is enabled, and a plain,
+// span-free copy of the function when it isn't — unlike `level`/`target`,
+// `enabled` takes a plain string literal, so it needs no `cfg_attr`
+// stacking to express; the macro itself emits both `#[cfg(...)]`-gated
+// copies.
+mod support;
+use support::*;
+
+use tracing::subscriber::with_default;
+use tracing_attributes::instrument;
+
+#[test]
+fn enabled_gates_instrumentation_on_the_named_feature() {
+    #[instrument(enabled = "verbose-tracing")]
+    fn my_function() -> u32 {
+        42
+    }
+
+    #[cfg(feature = "verbose-tracing")]
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(span::mock().named("my_function"))
+        .enter(span::mock().named("my_function"))
+        .exit(span::mock().named("my_function"))
+        .done()
+        .run_with_handle();
+
+    #[cfg(not(feature = "verbose-tracing"))]
+    let (subscriber, handle) = subscriber::mock().done().run_with_handle();
+
+    with_default(subscriber, || {
+        assert_eq!(my_function(), 42);
+    });
+
+    handle.assert_finished();
+}
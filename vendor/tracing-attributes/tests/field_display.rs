@@ -0,0 +1,120 @@
+// Regression coverage for the shape of the code `#[instrument]` expands to
+// when recording fields: arguments should be recorded with `fmt::Debug` by
+// default, and with `fmt::Display` when named in a `display(...)` grouping.
+// If the macro's expansion changes in a way that alters this behavior, these
+// tests will fail, even though they don't inspect the expanded tokens
+// directly.
+mod support;
+use support::*;
+
+use tracing::subscriber::with_default;
+use tracing_attributes::instrument;
+
+#[test]
+fn debug_is_the_default() {
+    #[instrument]
+    fn my_fn(arg: Pretty) {}
+
+    struct Pretty;
+    impl std::fmt::Debug for Pretty {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("debug")
+        }
+    }
+    impl std::fmt::Display for Pretty {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("display")
+        }
+    }
+
+    let span = span::mock().named("my_fn");
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(
+            span.clone()
+                .with_field(field::mock("arg").with_value(&format_args!("debug"))),
+        )
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        my_fn(Pretty);
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn display_grouping_uses_display() {
+    #[instrument(display(arg))]
+    fn my_fn(arg: Pretty) {}
+
+    struct Pretty;
+    impl std::fmt::Debug for Pretty {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("debug")
+        }
+    }
+    impl std::fmt::Display for Pretty {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("display")
+        }
+    }
+
+    let span = span::mock().named("my_fn");
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(
+            span.clone()
+                .with_field(field::mock("arg").with_value(&format_args!("display"))),
+        )
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        my_fn(Pretty);
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn display_grouping_only_affects_named_fields() {
+    #[instrument(display(a))]
+    fn my_fn(a: Pretty, b: Pretty) {}
+
+    struct Pretty;
+    impl std::fmt::Debug for Pretty {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("debug")
+        }
+    }
+    impl std::fmt::Display for Pretty {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("display")
+        }
+    }
+
+    let span = span::mock().named("my_fn");
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(span.clone().with_field(
+            field::mock("a")
+                .with_value(&format_args!("display"))
+                .and(field::mock("b").with_value(&format_args!("debug"))),
+        ))
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        my_fn(Pretty, Pretty);
+    });
+
+    handle.assert_finished();
+}
@@ -121,3 +121,130 @@ fn generics() {
 
     handle.assert_finished();
 }
+
+#[test]
+fn trait_default_method_records_self_type() {
+    trait Greeter {
+        #[instrument]
+        fn greet(&self) {}
+    }
+
+    struct Formal;
+    impl Greeter for Formal {}
+
+    struct Casual;
+    impl Greeter for Casual {}
+
+    let span_formal = span::mock().named("greet");
+    let span_casual = span::mock().named("greet");
+
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(span_formal.clone().with_field(
+            field::mock("self_type")
+                .with_value(&format_args!("{}", std::any::type_name::<Formal>())),
+        ))
+        .enter(span_formal.clone())
+        .exit(span_formal.clone())
+        .drop_span(span_formal)
+        .new_span(span_casual.clone().with_field(
+            field::mock("self_type")
+                .with_value(&format_args!("{}", std::any::type_name::<Casual>())),
+        ))
+        .enter(span_casual.clone())
+        .exit(span_casual.clone())
+        .drop_span(span_casual)
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        Formal.greet();
+        Casual.greet();
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn bind_name_allows_recording_a_field_from_the_body() {
+    #[instrument(bind(my_span))]
+    fn my_fn(count: usize) {
+        if count == 0 {
+            my_span.record("count", &"none");
+        }
+    }
+
+    let span = span::mock().named("my_fn");
+
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(
+            span.clone()
+                .with_field(field::mock("count").with_value(&format_args!("0"))),
+        )
+        .enter(span.clone())
+        .record(
+            span.clone(),
+            field::mock("count").with_value(&"none"),
+        )
+        .exit(span.clone())
+        .drop_span(span)
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        my_fn(0);
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn const_generics() {
+    #[instrument]
+    fn my_fn<const N: usize>(arg: [u8; N]) -> usize {
+        arg.len()
+    }
+
+    let span = span::mock().named("my_fn");
+
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(
+            span.clone()
+                .with_field(field::mock("arg").with_value(&format_args!("[1, 2, 3]"))),
+        )
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        assert_eq!(my_fn([1u8, 2, 3]), 3);
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn instruments_a_function_that_diverges() {
+    #[instrument]
+    fn always_panics() -> ! {
+        panic!("boom")
+    }
+
+    let span = span::mock().named("always_panics");
+
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(span.clone())
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(always_panics));
+        assert!(result.is_err());
+    });
+
+    handle.assert_finished();
+}
@@ -96,3 +96,33 @@ fn numeric_levels() {
 
     handle.assert_finished();
 }
+
+// `level` must be a literal, so it can't be computed from a `cfg!(...)`
+// expression directly (see the `instrument` docs). The supported pattern is
+// stacking two `cfg_attr`s, one per feature state; this test compiles and
+// runs under both `cargo test` and `cargo test --features verbose-tracing`,
+// exercising whichever branch is active.
+#[test]
+fn level_selected_via_cfg_attr_feature_flag() {
+    #[cfg_attr(feature = "verbose-tracing", instrument(level = "trace"))]
+    #[cfg_attr(not(feature = "verbose-tracing"), instrument(level = "info"))]
+    fn my_function() {}
+
+    #[cfg(feature = "verbose-tracing")]
+    let expected_level = Level::TRACE;
+    #[cfg(not(feature = "verbose-tracing"))]
+    let expected_level = Level::INFO;
+
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(span::mock().named("my_function").at_level(expected_level.clone()))
+        .enter(span::mock().named("my_function").at_level(expected_level.clone()))
+        .exit(span::mock().named("my_function").at_level(expected_level))
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        my_function();
+    });
+
+    handle.assert_finished();
+}
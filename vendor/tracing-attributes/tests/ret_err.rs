@@ -0,0 +1,169 @@
+// Regression coverage for the `ret`/`err` arguments: the return value (or
+// error) of an instrumented function is recorded as an event using
+// `fmt::Debug` by default, and `fmt::Display` when a `ret(Display)` /
+// `err(Display)` grouping is given.
+mod support;
+use support::*;
+
+use tracing::subscriber::with_default;
+use tracing_attributes::instrument;
+
+#[test]
+fn ret_records_the_return_value() {
+    #[instrument(ret)]
+    fn my_fn() -> u32 {
+        42
+    }
+
+    let span = span::mock().named("my_fn");
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(span.clone())
+        .enter(span.clone())
+        .event(event::mock().with_fields(field::mock("ret").with_value(&format_args!("42"))))
+        .exit(span.clone())
+        .drop_span(span)
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        my_fn();
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn ret_display_uses_display() {
+    #[instrument(ret(Display))]
+    fn greeting() -> Pretty {
+        Pretty
+    }
+
+    struct Pretty;
+    impl std::fmt::Debug for Pretty {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("debug")
+        }
+    }
+    impl std::fmt::Display for Pretty {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("display")
+        }
+    }
+
+    let span = span::mock().named("greeting");
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(span.clone())
+        .enter(span.clone())
+        .event(
+            event::mock().with_fields(field::mock("ret").with_value(&format_args!("display"))),
+        )
+        .exit(span.clone())
+        .drop_span(span)
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        greeting();
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn err_records_the_error_variant() {
+    #[instrument(err)]
+    fn fallible(fail: bool) -> Result<(), &'static str> {
+        if fail {
+            Err("oh no")
+        } else {
+            Ok(())
+        }
+    }
+
+    let span = span::mock().named("fallible");
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(span.clone())
+        .enter(span.clone())
+        .event(
+            event::mock()
+                .with_fields(field::mock("error").with_value(&format_args!("{:?}", "oh no"))),
+        )
+        .exit(span.clone())
+        .drop_span(span)
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        let _ = fallible(true);
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn ret_and_err_together_log_the_ok_value_distinctly_from_the_error() {
+    #[instrument(ret, err)]
+    fn divide(a: u32, b: u32) -> Result<u32, &'static str> {
+        if b == 0 {
+            Err("division by zero")
+        } else {
+            Ok(a / b)
+        }
+    }
+
+    let span = span::mock().named("divide");
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(span.clone())
+        .enter(span.clone())
+        .event(event::mock().with_fields(field::mock("ret").with_value(&format_args!("5"))))
+        .exit(span.clone())
+        .drop_span(span.clone())
+        .new_span(span.clone())
+        .enter(span.clone())
+        .event(
+            event::mock()
+                .at_level(tracing::Level::ERROR)
+                .with_fields(
+                    field::mock("error").with_value(&format_args!("{:?}", "division by zero")),
+                ),
+        )
+        .exit(span.clone())
+        .drop_span(span)
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        assert_eq!(divide(10, 2), Ok(5));
+        assert_eq!(divide(10, 0), Err("division by zero"));
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn err_is_silent_on_ok() {
+    #[instrument(err)]
+    fn fallible(fail: bool) -> Result<(), &'static str> {
+        if fail {
+            Err("oh no")
+        } else {
+            Ok(())
+        }
+    }
+
+    let span = span::mock().named("fallible");
+    let (subscriber, handle) = subscriber::mock()
+        .new_span(span.clone())
+        .enter(span.clone())
+        .exit(span.clone())
+        .drop_span(span)
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        let _ = fallible(false);
+    });
+
+    handle.assert_finished();
+}
@@ -63,6 +63,27 @@ pub trait Callsite: Sync {
     ///
     /// [metadata]: ../metadata/struct.Metadata.html
     fn metadata(&self) -> &Metadata<'_>;
+
+    /// Returns the [`Interest`] most recently set for this callsite by
+    /// [`set_interest`], or [`Interest::sometimes()`] if it hasn't cached
+    /// one.
+    ///
+    /// Callsites that cache the interest passed to [`set_interest`] (as the
+    /// callsites generated by `tracing`'s macros do) should override this to
+    /// report that cached value. The default implementation conservatively
+    /// returns [`Interest::sometimes()`], which is always a correct answer
+    /// for callsites with no cache to report from.
+    ///
+    /// This exists for introspection, e.g. to let a diagnostics tool list
+    /// every known callsite along with whatever is currently known about
+    /// whether it's enabled; it is not used by the registry itself, which
+    /// always recomputes interest from scratch via [`set_interest`].
+    ///
+    /// [`set_interest`]: Callsite::set_interest
+    /// [`Interest::sometimes()`]: ../subscriber/struct.Interest.html#method.sometimes
+    fn interest(&self) -> Interest {
+        Interest::sometimes()
+    }
 }
 
 /// Uniquely identifies a [`Callsite`]
@@ -111,6 +132,22 @@ pub fn register(callsite: &'static dyn Callsite) {
     registry.callsites.push(callsite);
 }
 
+/// Returns every [`Callsite`] currently registered with the global registry.
+///
+/// This is an introspection helper, intended for diagnostics tools that want
+/// to answer "what callsites does this process know about, and is each one
+/// enabled?" — for each callsite, its [metadata] and [`Callsite::interest`]
+/// can be inspected to determine that.
+///
+/// The order of the returned callsites is unspecified, and a callsite is
+/// only included once it has actually been hit at least once (callsites are
+/// lazily registered the first time their `span!`/`event!` site runs).
+///
+/// [metadata]: ../metadata/struct.Metadata.html
+pub fn all() -> Vec<&'static dyn Callsite> {
+    REGISTRY.lock().unwrap().callsites.clone()
+}
+
 pub(crate) fn register_dispatch(dispatch: &Dispatch) {
     let mut registry = REGISTRY.lock().unwrap();
     registry.dispatchers.push(dispatch.registrar());
@@ -200,8 +200,11 @@ struct State {
 
 /// A guard that resets the current default dispatcher to the prior
 /// default dispatcher when dropped.
+///
+/// Returned by [`set_default`].
 #[cfg(feature = "std")]
-struct ResetGuard(Option<Dispatch>);
+#[derive(Debug)]
+pub struct DefaultGuard(Option<Dispatch>);
 
 /// Sets this dispatch as the default for the duration of a closure.
 ///
@@ -221,10 +224,30 @@ pub fn with_default<T>(dispatcher: &Dispatch, f: impl FnOnce() -> T) -> T {
     // prior default. Using this (rather than simply resetting after calling
     // `f`) ensures that we always reset to the prior dispatcher even if `f`
     // panics.
-    let _guard = State::set_default(dispatcher.clone());
+    let _guard = set_default(dispatcher);
     f()
 }
 
+/// Sets this dispatch as the default for the current thread, returning a
+/// guard that resets the default dispatcher to the prior default when it is
+/// dropped.
+///
+/// Unlike [`with_default`], which only sets the default for the duration of
+/// a closure, this allows the default dispatcher to be set for an
+/// arbitrarily-scoped section of code, as determined by the returned
+/// guard's lifetime. This is especially useful in tests, where `with_default`
+/// would otherwise force every assertion into a single closure.
+///
+/// **Note**: This function requires the Rust standard library. `no_std` users
+/// should use [`set_global_default`] instead.
+///
+/// [`with_default`]: fn.with_default.html
+/// [`set_global_default`]: fn.set_global_default.html
+#[cfg(feature = "std")]
+pub fn set_default(dispatcher: &Dispatch) -> DefaultGuard {
+    State::set_default(dispatcher.clone())
+}
+
 /// Sets this dispatch as the global default for the duration of the entire program.
 /// Will be used as a fallback if no thread-local dispatch has been set in a thread
 /// (using `with_default`.)
@@ -630,10 +653,10 @@ impl State {
     /// Replaces the current default dispatcher on this thread with the provided
     /// dispatcher.Any
     ///
-    /// Dropping the returned `ResetGuard` will reset the default dispatcher to
+    /// Dropping the returned `DefaultGuard` will reset the default dispatcher to
     /// the previous value.
     #[inline]
-    fn set_default(new_dispatch: Dispatch) -> ResetGuard {
+    fn set_default(new_dispatch: Dispatch) -> DefaultGuard {
         let prior = CURRENT_STATE
             .try_with(|state| {
                 state.can_enter.set(true);
@@ -641,14 +664,14 @@ impl State {
             })
             .ok();
         EXISTS.store(true, Ordering::Release);
-        ResetGuard(prior)
+        DefaultGuard(prior)
     }
 }
 
-// ===== impl ResetGuard =====
+// ===== impl DefaultGuard =====
 
 #[cfg(feature = "std")]
-impl Drop for ResetGuard {
+impl Drop for DefaultGuard {
     #[inline]
     fn drop(&mut self) {
         if let Some(dispatch) = self.0.take() {
@@ -121,8 +121,9 @@ pub struct Iter {
 /// to be printed or stored in some other data structure.
 ///
 /// The `Visit` trait provides default implementations for `record_i64`,
-/// `record_u64`, `record_bool`, `record_str`, and `record_error`, which simply
-/// forward the recorded value to `record_debug`. Thus, `record_debug` is the
+/// `record_u64`, `record_f64`, `record_bool`, `record_str`, and
+/// `record_error`, which simply forward the recorded value to
+/// `record_debug`. Thus, `record_debug` is the
 /// only method which a `Visit` implementation *must* implement. However,
 /// visitors may override the default implementations of these functions in
 /// order to implement type-specific behavior.
@@ -183,6 +184,11 @@ pub trait Visit {
         self.record_debug(field, &value)
     }
 
+    /// Visit a double-precision floating point value.
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record_debug(field, &value)
+    }
+
     /// Visit a boolean value.
     fn record_bool(&mut self, field: &Field, value: bool) {
         self.record_debug(field, &value)
@@ -247,6 +253,99 @@ where
     DebugValue(t)
 }
 
+/// A byte slice which, when displayed, renders as a lowercase hex string.
+#[derive(Clone, Debug)]
+pub struct HexBytes<'a>(&'a [u8]);
+
+impl<'a> fmt::Display for HexBytes<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// A byte slice which, when displayed, renders as a base64 string.
+#[derive(Clone, Debug)]
+pub struct Base64Bytes<'a>(&'a [u8]);
+
+impl<'a> fmt::Display for Base64Bytes<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        for chunk in self.0.chunks(3) {
+            let b0 = chunk[0];
+            let b1 = *chunk.get(1).unwrap_or(&0);
+            let b2 = *chunk.get(2).unwrap_or(&0);
+
+            write!(f, "{}", ALPHABET[(b0 >> 2) as usize] as char)?;
+            write!(f, "{}", ALPHABET[(((b0 & 0x03) << 4) | (b1 >> 4)) as usize] as char)?;
+            write!(
+                f,
+                "{}",
+                if chunk.len() > 1 {
+                    ALPHABET[(((b1 & 0x0f) << 2) | (b2 >> 6)) as usize] as char
+                } else {
+                    '='
+                }
+            )?;
+            write!(
+                f,
+                "{}",
+                if chunk.len() > 2 {
+                    ALPHABET[(b2 & 0x3f) as usize] as char
+                } else {
+                    '='
+                }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Wraps a byte slice as a `Value` that, when recorded, renders as a
+/// lowercase hex string (e.g. `&[0xde, 0xad]` renders as `"dead"`).
+///
+/// This is useful for recording binary fields (such as protocol frames or
+/// hashes) in a form that's actually readable, rather than relying on the
+/// `[104, 105, ...]` rendering `&[u8]`'s `Debug` implementation produces.
+pub fn hex(bytes: &[u8]) -> DisplayValue<HexBytes<'_>> {
+    display(HexBytes(bytes))
+}
+
+/// Wraps a byte slice as a `Value` that, when recorded, renders as a
+/// base64 string.
+///
+/// This is useful for recording binary fields in a more compact form than
+/// [`hex`] produces.
+pub fn base64(bytes: &[u8]) -> DisplayValue<Base64Bytes<'_>> {
+    display(Base64Bytes(bytes))
+}
+
+/// A `Duration` which, when displayed, always renders as a fractional
+/// number of seconds (e.g. `1.500s`), regardless of magnitude.
+#[derive(Clone, Debug)]
+pub struct Seconds(crate::stdlib::time::Duration);
+
+impl fmt::Display for Seconds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.3}s", self.0.as_secs_f64())
+    }
+}
+
+/// Wraps a `Duration` as a `Value` that, when recorded, renders in a single
+/// consistent unit (fractional seconds, e.g. `1.500s`).
+///
+/// `Duration` has no native `Value` impl, and its own `Debug` output
+/// switches units depending on magnitude (`1.5s`, `1500ms`, `200µs`, ...),
+/// which is awkward to parse downstream. Recording `field::duration(d)`
+/// instead of `d` directly gives structured and text formatters alike a
+/// single, predictable rendering.
+pub fn duration(d: crate::stdlib::time::Duration) -> DisplayValue<Seconds> {
+    display(Seconds(d))
+}
+
 // ===== impl Visit =====
 
 impl<'a, 'b> Visit for fmt::DebugStruct<'a, 'b> {
@@ -317,6 +416,8 @@ impl_values! {
     record_u64(usize, u32, u16 as u64),
     record_i64(i64),
     record_i64(isize, i32, i16, i8 as i64),
+    record_f64(f64),
+    record_f64(f32 as f64),
     record_bool(bool)
 }
 
@@ -820,4 +921,25 @@ mod test {
         });
         assert_eq!(result, "123".to_owned());
     }
+
+    #[test]
+    fn hex_renders_lowercase_hex() {
+        assert_eq!(format!("{}", HexBytes(&[0xde, 0xad])), "dead");
+        assert_eq!(format!("{}", HexBytes(&[])), "");
+    }
+
+    #[test]
+    fn base64_renders_padded_base64() {
+        assert_eq!(format!("{}", Base64Bytes(b"foobar")), "Zm9vYmFy");
+        assert_eq!(format!("{}", Base64Bytes(b"foo")), "Zm9v");
+        assert_eq!(format!("{}", Base64Bytes(b"f")), "Zg==");
+    }
+
+    #[test]
+    fn seconds_renders_a_fixed_unit_regardless_of_magnitude() {
+        use crate::stdlib::time::Duration;
+        assert_eq!(format!("{}", Seconds(Duration::from_millis(1500))), "1.500s");
+        assert_eq!(format!("{}", Seconds(Duration::from_micros(200))), "0.000s");
+        assert_eq!(format!("{}", Seconds(Duration::from_secs(90))), "90.000s");
+    }
 }
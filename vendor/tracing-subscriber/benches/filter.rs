@@ -275,5 +275,36 @@ fn bench_dynamic(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_static, bench_dynamic);
+fn bench_targets(c: &mut Criterion) {
+    use tracing_subscriber::filter::Targets;
+
+    let mut group = c.benchmark_group("targets");
+
+    let prefixes: Vec<String> = (0..64).map(|i| format!("crate_{:02}::module", i)).collect();
+    let targets_to_check: Vec<String> = (0..64)
+        .map(|i| format!("crate_{:02}::module::sub_module::function", i))
+        .chain(std::iter::once("unrelated_crate::module".to_string()))
+        .collect();
+
+    group.bench_function("linear_scan_64_prefixes", |b| {
+        b.iter(|| {
+            for target in &targets_to_check {
+                let _ = prefixes.iter().any(|p| target.starts_with(p.as_str()));
+            }
+        })
+    });
+
+    group.bench_function("targets_trie_64_prefixes", |b| {
+        let targets = Targets::new(&prefixes);
+        b.iter(|| {
+            for target in &targets_to_check {
+                let _ = targets.enabled(target);
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_static, bench_dynamic, bench_targets);
 criterion_main!(benches);
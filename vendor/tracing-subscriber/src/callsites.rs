@@ -0,0 +1,109 @@
+//! Introspection helpers for listing the callsites `tracing` knows about.
+//!
+//! This is meant for "why isn't this logging" debugging: [`CallsiteReport`]
+//! pairs each registered callsite's [`Metadata`] with the [`Interest`] it
+//! currently reports, so a diagnostics dashboard can show, for every span or
+//! event site the process has hit at least once, whether it's enabled under
+//! the active filter.
+//!
+//! A callsite only shows up once its `span!`/`event!` call site has actually
+//! executed at least once — `tracing`'s macros register each callsite
+//! lazily, the first time they run.
+use tracing_core::{callsite, subscriber::Interest, Metadata};
+
+/// A registered callsite's metadata, together with the [`Interest`] it most
+/// recently reported.
+///
+/// [`Interest`]: tracing_core::subscriber::Interest
+#[derive(Debug)]
+pub struct CallsiteReport {
+    metadata: &'static Metadata<'static>,
+    interest: Interest,
+}
+
+impl CallsiteReport {
+    /// Returns the callsite's metadata.
+    pub fn metadata(&self) -> &'static Metadata<'static> {
+        self.metadata
+    }
+
+    /// Returns the [`Interest`] this callsite most recently reported.
+    ///
+    /// This reflects the cached value from the last time interest was
+    /// (re)computed — see [`Callsite::interest`] — not a live re-evaluation
+    /// against the currently active subscriber.
+    ///
+    /// [`Callsite::interest`]: tracing_core::callsite::Callsite::interest
+    pub fn interest(&self) -> &Interest {
+        &self.interest
+    }
+}
+
+/// Lists every callsite currently registered with the global registry,
+/// along with the [`Interest`] each one reports.
+///
+/// See the [module-level documentation](self) for caveats.
+pub fn all() -> Vec<CallsiteReport> {
+    callsite::all()
+        .into_iter()
+        .map(|cs| CallsiteReport {
+            metadata: cs.metadata(),
+            interest: cs.interest(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::subscriber::with_default;
+    use tracing_core::{span, Event};
+
+    struct OnlyInfoAndAbove;
+
+    impl tracing_core::Subscriber for OnlyInfoAndAbove {
+        fn register_callsite(&self, meta: &Metadata<'_>) -> Interest {
+            if meta.level() <= &tracing_core::Level::INFO {
+                Interest::always()
+            } else {
+                Interest::never()
+            }
+        }
+
+        fn enabled(&self, meta: &Metadata<'_>) -> bool {
+            meta.level() <= &tracing_core::Level::INFO
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn all_reports_the_expected_interest_for_known_callsites() {
+        with_default(OnlyInfoAndAbove, || {
+            tracing::info!(callsites_test.marker = "info-marker", "an info event");
+            tracing::trace!(callsites_test.marker = "trace-marker", "a trace event");
+        });
+
+        let reports = all();
+
+        let info_report = reports
+            .iter()
+            .find(|r| r.metadata().fields().field("callsites_test.marker").is_some() && r.metadata().level() == &tracing_core::Level::INFO)
+            .expect("the info callsite should have been registered");
+        assert!(info_report.interest().is_always());
+
+        let trace_report = reports
+            .iter()
+            .find(|r| r.metadata().fields().field("callsites_test.marker").is_some() && r.metadata().level() == &tracing_core::Level::TRACE)
+            .expect("the trace callsite should have been registered");
+        assert!(trace_report.interest().is_never());
+    }
+}
@@ -0,0 +1,128 @@
+//! A `Layer` that captures events matching a predicate into a shared `Vec`,
+//! for snapshot-style test assertions.
+use crate::layer::{Context, Layer};
+use crate::sync::RwLock;
+use std::fmt;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use tracing_core::{
+    field::{Field, Visit},
+    subscriber::Subscriber,
+    Event, Level, Metadata,
+};
+
+/// A rendered snapshot of a captured [`Event`], independent of its
+/// callsite's lifetime.
+///
+/// [`Event`]: https://docs.rs/tracing-core/latest/tracing_core/event/struct.Event.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapturedEvent {
+    /// The event's level.
+    pub level: Level,
+    /// The event's target.
+    pub target: String,
+    /// The event's fields, rendered as `name=value` pairs in the order they
+    /// were recorded.
+    pub fields: String,
+}
+
+/// A `Layer` that records every event matching a predicate into a shared,
+/// lock-protected `Vec<CapturedEvent>`.
+///
+/// This is test tooling for asserting on a small, filtered slice of events
+/// (e.g. "only WARN and above") without standing up a full collecting
+/// subscriber and filtering its output after the fact.
+#[derive(Clone)]
+pub struct FilteredCaptureLayer<F> {
+    filter: Arc<F>,
+    captured: Arc<RwLock<Vec<CapturedEvent>>>,
+}
+
+impl<F> FilteredCaptureLayer<F>
+where
+    F: Fn(&Metadata<'_>) -> bool,
+{
+    /// Returns a new `FilteredCaptureLayer` that captures only events for
+    /// which `filter` returns `true`.
+    pub fn new(filter: F) -> Self {
+        Self {
+            filter: Arc::new(filter),
+            captured: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Returns a clone of the events captured so far.
+    pub fn events(&self) -> Vec<CapturedEvent> {
+        try_lock!(self.captured.read(), else return Vec::new()).clone()
+    }
+}
+
+impl<S, F> Layer<S> for FilteredCaptureLayer<F>
+where
+    S: Subscriber,
+    F: Fn(&Metadata<'_>) -> bool + 'static,
+{
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        (self.filter)(metadata)
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = String::new();
+        event.record(&mut FieldsRecorder(&mut fields));
+        try_lock!(self.captured.write()).push(CapturedEvent {
+            level: event.metadata().level().clone(),
+            target: event.metadata().target().to_string(),
+            fields,
+        });
+    }
+}
+
+struct FieldsRecorder<'a>(&'a mut String);
+
+impl<'a> FieldsRecorder<'a> {
+    fn pad(&mut self) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+    }
+}
+
+impl<'a> Visit for FieldsRecorder<'a> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.pad();
+        let _ = write!(*self.0, "{}={:?}", field.name(), value);
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.pad();
+        let _ = write!(*self.0, "{}={}", field.name(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn only_events_matching_predicate_are_captured() {
+        let layer = FilteredCaptureLayer::new(|meta: &Metadata<'_>| *meta.level() <= Level::WARN);
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer.clone()),
+        );
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            tracing::info!("ignored");
+            tracing::warn!(code = 1, "careful");
+            tracing::error!("boom");
+            tracing::debug!("also ignored");
+        });
+
+        let events = layer.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].level, Level::WARN);
+        assert!(events[0].fields.contains("code=1"));
+        assert!(events[0].fields.contains("message=careful"));
+        assert_eq!(events[1].level, Level::ERROR);
+    }
+}
@@ -0,0 +1,243 @@
+//! A `Layer` that collapses spans created in a tight loop into an occurrence
+//! count.
+use crate::drop_counters::{DropCounters, DropReason};
+use crate::layer::{Context, Layer};
+use crate::sync::RwLock;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing_core::field::FieldSet;
+use tracing_core::metadata::Kind;
+use tracing_core::{callsite, field, span, subscriber::Subscriber, Callsite, Level, Metadata};
+
+/// Identifies spans considered duplicates of one another: the same name, the
+/// same target, and the same field values.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct DedupKey {
+    name: &'static str,
+    target: &'static str,
+    fields: u64,
+}
+
+struct Entry {
+    window_started: Instant,
+    count: u64,
+}
+
+/// A `Layer` that collapses spans created with the same name, target, and
+/// field values in quick succession into a single logical occurrence,
+/// tracking how many times it happened within a sliding `window`.
+///
+/// Spans are grouped by hashing their field values at [`new_span`] alongside
+/// their name and target. The first span in a group starts its window; every
+/// later span that hashes the same within that window just increments the
+/// group's count instead of starting a new one. Once a span for a group
+/// arrives after its window has elapsed, the previous window's final count
+/// is emitted as a summary event and a new window starts.
+///
+/// ## Why duplicate spans still reach the wrapped subscriber
+///
+/// This version of `tracing-subscriber` predates [`Registry`]: a `Layer`
+/// finds out about a new span via [`new_span`], which is a *notification* —
+/// by the time it fires, the wrapped [`Subscriber`] has already assigned the
+/// span an [`Id`] and is free to record it. There is no hook a `Layer` can
+/// use to veto that. So this layer cannot make the duplicate spans
+/// themselves disappear from whatever is underneath it; what it provides
+/// instead is an accurate count of how many there were, available via
+/// [`DedupSpanLayer::with_drop_counters`]'s [`DropReason::DedupCollapsed`]
+/// counter and via the summary event emitted on window expiry. A caller that
+/// wants the duplicates themselves suppressed needs a filtering layer above
+/// this one that consults that count.
+///
+/// [`Registry`]: ../struct.Registry.html
+/// [`Subscriber`]: https://docs.rs/tracing-core/latest/tracing_core/trait.Subscriber.html
+/// [`Id`]: https://docs.rs/tracing-core/latest/tracing_core/span/struct.Id.html
+/// [`new_span`]: crate::layer::Layer::new_span
+#[derive(Clone)]
+pub struct DedupSpanLayer {
+    window: Duration,
+    entries: Arc<RwLock<HashMap<DedupKey, Entry>>>,
+    drop_counters: Option<DropCounters>,
+}
+
+impl DedupSpanLayer {
+    /// Returns a new `DedupSpanLayer` that collapses identical spans seen
+    /// within `window` of one another.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: Arc::new(RwLock::new(HashMap::new())),
+            drop_counters: None,
+        }
+    }
+
+    /// Returns this layer with `counters` recording a
+    /// [`DropReason::DedupCollapsed`] for every duplicate span it collapses
+    /// (that is, every span after the first in a group's window).
+    pub fn with_drop_counters(mut self, counters: DropCounters) -> Self {
+        self.drop_counters = Some(counters);
+        self
+    }
+
+    fn emit_summary(&self, key: &DedupKey, count: u64) {
+        struct DedupSummaryCallsite;
+        impl Callsite for DedupSummaryCallsite {
+            fn set_interest(&self, _: tracing_core::subscriber::Interest) {}
+            fn metadata(&self) -> &'static Metadata<'static> {
+                &METADATA
+            }
+        }
+        static CALLSITE: DedupSummaryCallsite = DedupSummaryCallsite;
+        static METADATA: Metadata<'static> = Metadata::new(
+            "event",
+            "tracing_subscriber::dedup",
+            Level::INFO,
+            Some(file!()),
+            Some(line!()),
+            Some(module_path!()),
+            FieldSet::new(
+                &["message", "span_name", "target", "count"],
+                callsite::Identifier(&CALLSITE),
+            ),
+            Kind::EVENT,
+        );
+
+        let message: &str = "collapsed duplicate spans";
+        use tracing_core::field::Value;
+        let fields = METADATA.fields();
+        let values: [(&field::Field, Option<&dyn Value>); 4] = [
+            (&fields.field("message").unwrap(), Some(&message as &dyn Value)),
+            (&fields.field("span_name").unwrap(), Some(&key.name as &dyn Value)),
+            (&fields.field("target").unwrap(), Some(&key.target as &dyn Value)),
+            (&fields.field("count").unwrap(), Some(&count as &dyn Value)),
+        ];
+        tracing_core::Event::dispatch(&METADATA, &fields.value_set(&values));
+    }
+}
+
+impl<S> Layer<S> for DedupSpanLayer
+where
+    S: Subscriber,
+{
+    fn new_span(&self, attrs: &span::Attributes<'_>, _id: &span::Id, _ctx: Context<'_, S>) {
+        let mut hasher = FieldHasher::new();
+        attrs.record(&mut hasher);
+        let key = DedupKey {
+            name: attrs.metadata().name(),
+            target: attrs.metadata().target(),
+            fields: hasher.finish(),
+        };
+
+        let expired = {
+            let entries = try_lock!(self.entries.read());
+            entries
+                .get(&key)
+                .map_or(false, |entry| entry.window_started.elapsed() >= self.window)
+        };
+
+        if expired {
+            let previous = try_lock!(self.entries.write()).remove(&key);
+            if let Some(entry) = previous {
+                self.emit_summary(&key, entry.count);
+            }
+        }
+
+        let mut entries = try_lock!(self.entries.write());
+        let entry = entries.entry(key).or_insert_with(|| Entry {
+            window_started: Instant::now(),
+            count: 0,
+        });
+        entry.count += 1;
+        if entry.count > 1 {
+            if let Some(counters) = &self.drop_counters {
+                counters.record(DropReason::DedupCollapsed);
+            }
+        }
+    }
+}
+
+/// Hashes a span's field values (by name and `Debug` representation) into a
+/// single `u64`, for grouping spans that carry identical data.
+struct FieldHasher {
+    hasher: DefaultHasher,
+}
+
+impl FieldHasher {
+    fn new() -> Self {
+        Self {
+            hasher: DefaultHasher::new(),
+        }
+    }
+
+    fn finish(self) -> u64 {
+        self.hasher.finish()
+    }
+}
+
+impl field::Visit for FieldHasher {
+    fn record_debug(&mut self, field: &field::Field, value: &dyn fmt::Debug) {
+        field.name().hash(&mut self.hasher);
+        format!("{:?}", value).hash(&mut self.hasher);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn ten_identical_spans_collapse_to_one_entry_with_a_count_of_ten() {
+        let layer = DedupSpanLayer::new(Duration::from_secs(60));
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer.clone()),
+        );
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            for _ in 0..10 {
+                tracing::info_span!("loop_iteration", iteration = 1);
+            }
+        });
+
+        let entries = try_lock!(layer.entries.read());
+        assert_eq!(entries.len(), 1, "all ten spans should hash to a single group");
+        assert_eq!(entries.values().next().unwrap().count, 10);
+    }
+
+    #[test]
+    fn drop_counters_record_every_collapsed_duplicate() {
+        use crate::drop_counters::{DropCounters, DropReason};
+
+        let counters = DropCounters::new();
+        let layer = DedupSpanLayer::new(Duration::from_secs(60)).with_drop_counters(counters.clone());
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer),
+        );
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            for _ in 0..10 {
+                tracing::info_span!("loop_iteration", iteration = 1);
+            }
+        });
+
+        assert_eq!(counters.count(DropReason::DedupCollapsed), 9);
+    }
+
+    #[test]
+    fn differing_field_values_start_separate_groups() {
+        let layer = DedupSpanLayer::new(Duration::from_secs(60));
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer.clone()),
+        );
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            tracing::info_span!("loop_iteration", iteration = 1);
+            tracing::info_span!("loop_iteration", iteration = 2);
+        });
+
+        assert_eq!(try_lock!(layer.entries.read()).len(), 2);
+    }
+}
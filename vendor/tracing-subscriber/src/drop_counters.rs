@@ -0,0 +1,232 @@
+//! A shared registry of counters for spans and events that filtering
+//! features discard, plus a way to emit a one-line summary of them.
+//!
+//! Rate limiting, sampling, and a writer's drop-on-buffer-full behavior can
+//! each silently discard a meaningful fraction of a process's telemetry, and
+//! an operator staring at a gap in their dashboards has no way to tell
+//! whether anything was lost, let alone why. [`DropCounters`] gives these
+//! independent features a common place to record *why* something was
+//! dropped, and [`DropCounters::shutdown`] emits a single summary event
+//! tallying each reason, meant to be called once, deterministically, as a
+//! process (or a subsystem) winds down.
+//!
+//! ## What's wired up today
+//!
+//! [`DropReason::Sampled`] is incremented by [`SamplingFilter`] (via
+//! [`SamplingFilter::with_drop_counters`]) whenever it filters out an event
+//! belonging to a trace it decided not to sample. [`DropReason::DedupCollapsed`]
+//! is incremented by [`DedupSpanLayer`] (via
+//! [`DedupSpanLayer::with_drop_counters`]) for every duplicate span it
+//! collapses into an existing group.
+//!
+//! The other two reasons exist as counters callers can drive themselves, but
+//! nothing in this crate increments them automatically yet:
+//!
+//! - [`DropReason::RateLimited`]: no rate-limiting `Layer` exists in this
+//!   crate yet.
+//! - [`DropReason::BufferFull`]: [`non_blocking`], [`buffered_writer`], and
+//!   [`socket_writer`] already track drop-on-buffer-full counts themselves,
+//!   each as its own private `Arc<AtomicUsize>`
+//!   ([`NonBlocking::shutdown`]'s buffered count, [`SocketWriter::dropped`],
+//!   and similarly for [`BufferedWriter`]). Routing those through a shared
+//!   registry would mean breaking their existing constructor signatures, so
+//!   for now, a caller that wants one unified summary should read a
+//!   writer's own counter at its own shutdown point and feed it in with
+//!   [`DropCounters::observe_buffer_full`].
+//!
+//! [`SamplingFilter`]: crate::sampling::SamplingFilter
+//! [`SamplingFilter::with_drop_counters`]: crate::sampling::SamplingFilter::with_drop_counters
+//! [`DedupSpanLayer`]: crate::dedup::DedupSpanLayer
+//! [`DedupSpanLayer::with_drop_counters`]: crate::dedup::DedupSpanLayer::with_drop_counters
+//! [`non_blocking`]: crate::fmt::writer::non_blocking
+//! [`buffered_writer`]: crate::fmt::writer::buffered_writer
+//! [`socket_writer`]: crate::fmt::writer::socket_writer
+//! [`NonBlocking::shutdown`]: crate::fmt::writer::NonBlocking::shutdown
+//! [`SocketWriter::dropped`]: crate::fmt::writer::SocketWriter::dropped
+//! [`BufferedWriter`]: crate::fmt::writer::BufferedWriter
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing_core::{callsite, field::FieldSet, metadata::Kind, Callsite, Level, Metadata};
+
+/// Why a span or event was discarded instead of being recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropReason {
+    /// Filtered out by [`SamplingFilter`](crate::sampling::SamplingFilter)
+    /// because it belonged to a trace that wasn't sampled.
+    Sampled,
+    /// Filtered out by a rate limiter.
+    RateLimited,
+    /// Dropped by a writer because its buffer was full.
+    BufferFull,
+    /// Collapsed into an earlier, identical span by a deduplication layer.
+    DedupCollapsed,
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    sampled: AtomicU64,
+    rate_limited: AtomicU64,
+    buffer_full: AtomicU64,
+    dedup_collapsed: AtomicU64,
+}
+
+/// A shared, cheaply-cloneable registry of per-reason drop counters.
+///
+/// Clones all share the same underlying counters, so a single
+/// `DropCounters` can be handed to several independent filtering features
+/// (a [`SamplingFilter`](crate::sampling::SamplingFilter), a rate limiter,
+/// ...) and queried or summarized from anywhere else that holds a clone.
+#[derive(Debug, Clone, Default)]
+pub struct DropCounters {
+    counts: Arc<Counts>,
+}
+
+impl DropCounters {
+    /// Returns a new, zeroed `DropCounters`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records one dropped span or event for `reason`.
+    pub fn record(&self, reason: DropReason) {
+        let counter = match reason {
+            DropReason::Sampled => &self.counts.sampled,
+            DropReason::RateLimited => &self.counts.rate_limited,
+            DropReason::BufferFull => &self.counts.buffer_full,
+            DropReason::DedupCollapsed => &self.counts.dedup_collapsed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the number of drops recorded so far for `reason`.
+    pub fn count(&self, reason: DropReason) -> u64 {
+        let counter = match reason {
+            DropReason::Sampled => &self.counts.sampled,
+            DropReason::RateLimited => &self.counts.rate_limited,
+            DropReason::BufferFull => &self.counts.buffer_full,
+            DropReason::DedupCollapsed => &self.counts.dedup_collapsed,
+        };
+        counter.load(Ordering::Relaxed)
+    }
+
+    /// Adds `count` to the [`BufferFull`](DropReason::BufferFull) counter.
+    ///
+    /// For use by callers bridging in a writer's own drop-on-full count
+    /// (see the module docs) rather than by code that can call [`record`]
+    /// directly as each drop happens.
+    ///
+    /// [`record`]: DropCounters::record
+    pub fn observe_buffer_full(&self, count: u64) {
+        self.counts.buffer_full.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Renders the current counts as `dropped{sampled=.. rate_limited=..
+    /// buffer_full=.. dedup_collapsed=..}`.
+    pub fn summary(&self) -> String {
+        format!(
+            "dropped{{sampled={} rate_limited={} buffer_full={} dedup_collapsed={}}}",
+            self.count(DropReason::Sampled),
+            self.count(DropReason::RateLimited),
+            self.count(DropReason::BufferFull),
+            self.count(DropReason::DedupCollapsed),
+        )
+    }
+
+    /// Emits [`summary`](DropCounters::summary) as a single `INFO` event on
+    /// the current dispatcher.
+    ///
+    /// Meant to be called once, deterministically, at shutdown -- after the
+    /// last span or event that could still be dropped, and before whatever
+    /// subscriber is recording it is itself torn down -- so the summary
+    /// reaches the same destination as everything else that was logged.
+    ///
+    /// This crate depends on `tracing-core`, not the `tracing` macros, so
+    /// the event is built and dispatched by hand here instead of via
+    /// `tracing::info!`.
+    pub fn shutdown(&self) {
+        struct ShutdownSummaryCallsite;
+        impl Callsite for ShutdownSummaryCallsite {
+            fn set_interest(&self, _: tracing_core::subscriber::Interest) {}
+            fn metadata(&self) -> &'static Metadata<'static> {
+                &METADATA
+            }
+        }
+        static CALLSITE: ShutdownSummaryCallsite = ShutdownSummaryCallsite;
+        static METADATA: Metadata<'static> = Metadata::new(
+            "event",
+            "tracing_subscriber::drop_counters",
+            Level::INFO,
+            Some(file!()),
+            Some(line!()),
+            Some(module_path!()),
+            FieldSet::new(
+                &["message", "sampled", "rate_limited", "buffer_full", "dedup_collapsed"],
+                callsite::Identifier(&CALLSITE),
+            ),
+            Kind::EVENT,
+        );
+
+        let message: &str = "dropped";
+        let sampled = self.count(DropReason::Sampled);
+        let rate_limited = self.count(DropReason::RateLimited);
+        let buffer_full = self.count(DropReason::BufferFull);
+        let dedup_collapsed = self.count(DropReason::DedupCollapsed);
+
+        use tracing_core::field::Value;
+        let fields = METADATA.fields();
+        let values: [(&tracing_core::field::Field, Option<&dyn Value>); 5] = [
+            (&fields.field("message").unwrap(), Some(&message as &dyn Value)),
+            (&fields.field("sampled").unwrap(), Some(&sampled as &dyn Value)),
+            (&fields.field("rate_limited").unwrap(), Some(&rate_limited as &dyn Value)),
+            (&fields.field("buffer_full").unwrap(), Some(&buffer_full as &dyn Value)),
+            (
+                &fields.field("dedup_collapsed").unwrap(),
+                Some(&dedup_collapsed as &dyn Value),
+            ),
+        ];
+        tracing_core::Event::dispatch(&METADATA, &fields.value_set(&values));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_start_at_zero_and_accumulate_independently() {
+        let counters = DropCounters::new();
+        assert_eq!(counters.count(DropReason::Sampled), 0);
+        assert_eq!(counters.count(DropReason::BufferFull), 0);
+
+        counters.record(DropReason::Sampled);
+        counters.record(DropReason::Sampled);
+        counters.record(DropReason::BufferFull);
+
+        assert_eq!(counters.count(DropReason::Sampled), 2);
+        assert_eq!(counters.count(DropReason::BufferFull), 1);
+        assert_eq!(counters.count(DropReason::RateLimited), 0);
+        assert_eq!(counters.count(DropReason::DedupCollapsed), 0);
+    }
+
+    #[test]
+    fn clones_share_the_same_underlying_counters() {
+        let counters = DropCounters::new();
+        let clone = counters.clone();
+
+        clone.record(DropReason::RateLimited);
+
+        assert_eq!(counters.count(DropReason::RateLimited), 1);
+    }
+
+    #[test]
+    fn summary_reports_every_reason() {
+        let counters = DropCounters::new();
+        counters.record(DropReason::Sampled);
+        counters.observe_buffer_full(3);
+
+        assert_eq!(
+            counters.summary(),
+            "dropped{sampled=1 rate_limited=0 buffer_full=3 dedup_collapsed=0}"
+        );
+    }
+}
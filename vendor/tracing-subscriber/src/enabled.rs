@@ -0,0 +1,102 @@
+//! A cheap, `Metadata`-free check for whether a level/target pair would be
+//! enabled by the current dispatcher.
+//!
+//! Code that wants to skip expensive instrumentation prep (gathering extra
+//! context fields, rendering a large payload) when nothing would actually
+//! log it faces a chicken-and-egg problem: the usual way to ask "would this
+//! be enabled" is [`Subscriber::enabled`], which wants a `&Metadata`, and
+//! `Metadata` is normally only ever constructed once, statically, by the
+//! `tracing` macros at the exact callsite doing the logging. [`would_log`]
+//! synthesizes a throwaway `Metadata` on the caller's behalf so this check
+//! can be made from arbitrary code, not just from inside a macro expansion.
+//!
+//! This mirrors [`log::log_enabled!`].
+//!
+//! [`log::log_enabled!`]: https://docs.rs/log/latest/log/macro.log_enabled.html
+use tracing_core::{
+    callsite::{self, Callsite},
+    dispatcher,
+    field::FieldSet,
+    metadata::Kind,
+    subscriber::Interest,
+    Level, Metadata,
+};
+
+struct WouldLogCallsite;
+
+impl Callsite for WouldLogCallsite {
+    fn set_interest(&self, _: Interest) {
+        // Nothing caches interest for this callsite; every `would_log` call
+        // goes straight to the dispatcher's `enabled`, so there's nothing to
+        // update here.
+    }
+
+    fn metadata(&self) -> &'static Metadata<'static> {
+        // Never called: nothing registers this callsite with the global
+        // registry, since `would_log` only needs a callsite `Identifier` to
+        // build a `FieldSet`, not a registered callsite.
+        unreachable!("WouldLogCallsite is never registered")
+    }
+}
+
+static WOULD_LOG_CALLSITE: WouldLogCallsite = WouldLogCallsite;
+
+/// Returns `true` if an event at `level` with the given `target` would be
+/// enabled by the currently active [`Dispatch`], without requiring a
+/// `&Metadata` for a real callsite.
+///
+/// This consults the current dispatcher's [`Subscriber::enabled`] with a
+/// synthesized, fieldless event `Metadata` carrying `level` and `target`
+/// (and no file, line, or module path). Since most filters (including
+/// [`EnvFilter`] and [`Targets`]) only ever look at level, target, and name,
+/// this gives the same answer a real event at that level/target would get in
+/// the common case; a filter that specifically keys off of fields, file, or
+/// line will not see those and may answer differently than it would for a
+/// real callsite.
+///
+/// This is a dynamic, per-call check, unlike the static, per-callsite
+/// caching the `tracing` macros use (via [`Callsite::set_interest`]) to make
+/// `if_log_enabled!`-style checks nearly free; prefer `tracing::enabled!` (if
+/// available) or a real `tracing::event!`/`span!` callsite when one is
+/// available, and reach for this only when no static callsite exists for the
+/// check you want to make.
+///
+/// [`Dispatch`]: tracing_core::Dispatch
+/// [`Subscriber::enabled`]: tracing_core::Subscriber::enabled
+/// [`EnvFilter`]: crate::filter::EnvFilter
+/// [`Targets`]: crate::filter::Targets
+pub fn would_log(level: Level, target: &str) -> bool {
+    let fields = FieldSet::new(&[], callsite::Identifier(&WOULD_LOG_CALLSITE));
+    let metadata = Metadata::new(
+        "would_log",
+        target,
+        level,
+        None,
+        None,
+        None,
+        fields,
+        Kind::EVENT,
+    );
+    dispatcher::get_default(|dispatch| dispatch.enabled(&metadata))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::LevelFilter;
+    use crate::layer::Layer;
+    use tracing_core::Dispatch;
+
+    #[test]
+    fn would_log_matches_the_installed_level_filter() {
+        let subscriber = LevelFilter::INFO.with_subscriber(crate::fmt::Subscriber::new());
+        let dispatch = Dispatch::new(subscriber);
+
+        dispatcher::with_default(&dispatch, || {
+            assert!(would_log(Level::INFO, "my::target"));
+            assert!(would_log(Level::WARN, "my::target"));
+            assert!(!would_log(Level::DEBUG, "my::target"));
+            assert!(!would_log(Level::TRACE, "my::target"));
+        });
+    }
+}
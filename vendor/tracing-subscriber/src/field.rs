@@ -0,0 +1,205 @@
+//! Helpers for recording field values in canonical, parseable formats.
+
+use tracing_core::field::{self, DisplayValue};
+
+/// Renders `time` as an RFC 3339 timestamp, suitable for recording as a
+/// structured field:
+///
+/// ```
+/// # use tracing_subscriber::field;
+/// # use std::time::SystemTime;
+/// tracing::info!(at = field::time(&SystemTime::now()));
+/// ```
+///
+/// Implemented for [`std::time::SystemTime`] and any [`chrono::DateTime`],
+/// so the same call site works whether a timestamp came from the standard
+/// library or from `chrono`.
+///
+/// Requires the `chrono` feature, enabled by default.
+#[cfg(feature = "chrono")]
+pub fn time<T: Rfc3339>(time: &T) -> DisplayValue<String> {
+    field::display(time.to_rfc3339())
+}
+
+/// Types that [`time`] can render as an RFC 3339 timestamp.
+#[cfg(feature = "chrono")]
+pub trait Rfc3339 {
+    /// Renders `self` as an RFC 3339 timestamp string.
+    fn to_rfc3339(&self) -> String;
+}
+
+#[cfg(feature = "chrono")]
+impl Rfc3339 for std::time::SystemTime {
+    fn to_rfc3339(&self) -> String {
+        chrono::DateTime::<chrono::Utc>::from(*self).to_rfc3339()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz> Rfc3339 for chrono::DateTime<Tz>
+where
+    Tz: chrono::TimeZone,
+    Tz::Offset: std::fmt::Display,
+{
+    fn to_rfc3339(&self) -> String {
+        chrono::DateTime::to_rfc3339(self)
+    }
+}
+
+/// Records only an enum value's variant name as a field value, omitting
+/// whatever data the variant carries.
+///
+/// This version of `tracing-core` has no derivable trait for variant names
+/// (deriving is out of scope), so the name is produced by a caller-supplied
+/// closure instead, e.g. a hand-written `fn name(&self) -> &str` method:
+///
+/// ```
+/// # use tracing_subscriber::field;
+/// enum Connection {
+///     Connected,
+///     Disconnected { reason: String },
+/// }
+///
+/// impl Connection {
+///     fn name(&self) -> &'static str {
+///         match self {
+///             Connection::Connected => "Connected",
+///             Connection::Disconnected { .. } => "Disconnected",
+///         }
+///     }
+/// }
+///
+/// let state = Connection::Disconnected { reason: "timeout".into() };
+/// tracing::info!(state = field::variant_with(&state, |s| s.name()));
+/// ```
+pub fn variant_with<'a, T, F>(value: &'a T, name_fn: F) -> DisplayValue<&'a str>
+where
+    F: FnOnce(&'a T) -> &'a str,
+{
+    field::display(name_fn(value))
+}
+
+#[cfg(all(test, feature = "fmt"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::dispatcher::Dispatch;
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn a_system_time_field_renders_as_rfc_3339() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || IoWriter(writer_buf.clone()))
+            .finish();
+
+        struct IoWriter(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for IoWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let at = chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let system_time: std::time::SystemTime = at.into();
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!(at = time(&system_time), "tick");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("at=2020-01-01T00:00:00+00:00"), "{}", out);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn a_chrono_datetime_field_renders_as_rfc_3339() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || IoWriter(writer_buf.clone()))
+            .finish();
+
+        struct IoWriter(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for IoWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let at = chrono::DateTime::parse_from_rfc3339("2020-06-15T12:30:00Z").unwrap();
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!(at = time(&at), "tick");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("at=2020-06-15T12:30:00+00:00"), "{}", out);
+    }
+
+    enum Connection {
+        Connected,
+        Disconnected { reason: String },
+    }
+
+    impl Connection {
+        fn name(&self) -> &'static str {
+            match self {
+                Connection::Connected => "Connected",
+                Connection::Disconnected { .. } => "Disconnected",
+            }
+        }
+    }
+
+    #[test]
+    fn variant_with_records_only_the_variant_name() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || IoWriter(writer_buf.clone()))
+            .finish();
+
+        struct IoWriter(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for IoWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let state = Connection::Disconnected {
+            reason: "timeout".into(),
+        };
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!(state = variant_with(&state, |s| s.name()));
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("state=Disconnected"), "{}", out);
+        assert!(!out.contains("timeout"), "{}", out);
+    }
+}
@@ -0,0 +1,256 @@
+//! A `Layer` that elevates events within a span subtree once an ancestor
+//! span has recorded a configured field as `true`, for turning on verbose
+//! logging scoped to a single request or task.
+use crate::layer::{Context, Layer};
+use crate::sync::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing_core::field::{Field, Visit};
+use tracing_core::{span, subscriber::Interest, subscriber::Subscriber, Level, Metadata};
+
+/// A `Layer` that raises the effective level threshold for a span subtree
+/// once that subtree's root (or any ancestor) has recorded a configured
+/// field as `true`.
+///
+/// Events are enabled at `base_level` and above as usual; an event more
+/// verbose than `base_level` (for example, a `TRACE` event under a `base_level`
+/// of `INFO`) is additionally enabled if the current span, or any of its
+/// ancestors, has recorded the configured field as `true` — regardless of
+/// the event's target. This is useful for turning on verbose logging for one
+/// request at a time: tag the request's root span with `debug_mode = true`
+/// and every event nested under it, at any level, is emitted.
+///
+/// ## How elevation is tracked
+///
+/// This version of `tracing-subscriber` predates [`Registry`] and
+/// [`LookupSpan`]: there is no per-span extensions map, and a `Layer` cannot
+/// walk a span's full ancestor chain — only its immediate parent is visible,
+/// via [`Attributes::parent`] or the contextually current span. This layer
+/// gets the same effect without that machinery, the same way
+/// [`sampling::SamplingFilter`] does: because [`new_span`] fires exactly once
+/// for every span, resolving one hop of parent and propagating the stored
+/// decision at creation time is transitively equivalent to walking all the
+/// way to the root. The decision itself is kept in this layer's own map,
+/// keyed by span ID, in place of a per-span extensions entry.
+///
+/// A span that records the field as `true` after some of its descendants
+/// were already created (via [`on_record`] rather than at span creation)
+/// elevates itself and any *future* descendants, but does not retroactively
+/// elevate descendants created before the field was recorded.
+///
+/// ## Composing with other layers
+///
+/// A `Layered` stack's `enabled` is the logical AND of every layer in the
+/// stack — a `Layer` can only veto another layer's decision, never override
+/// it. This layer's elevation therefore only has its intended effect when
+/// it is the layer making the level-based accept/reject decision that would
+/// otherwise apply (which is why it takes its own `base_level` rather than
+/// deferring to a separate level filter below it); stacking this on top of
+/// an independent, stricter filter still leaves that filter free to reject
+/// an elevated event.
+///
+/// [`new_span`]: #method.new_span
+/// [`on_record`]: #method.on_record
+/// [`Registry`]: ../struct.Registry.html
+/// [`LookupSpan`]: ../registry/trait.LookupSpan.html
+/// [`Attributes::parent`]: https://docs.rs/tracing-core/latest/tracing_core/span/struct.Attributes.html#method.parent
+/// [`sampling::SamplingFilter`]: ../sampling/struct.SamplingFilter.html
+#[derive(Clone, Debug)]
+pub struct FieldElevationFilter {
+    field_name: &'static str,
+    base_level: Level,
+    elevated: Arc<RwLock<HashMap<span::Id, bool>>>,
+}
+
+impl FieldElevationFilter {
+    /// Returns a new `FieldElevationFilter` that enables events at
+    /// `base_level` and above unconditionally, and enables events more
+    /// verbose than `base_level` within any span subtree whose root (or an
+    /// ancestor) has recorded `field_name` as `true`.
+    pub fn new(field_name: &'static str, base_level: Level) -> Self {
+        Self {
+            field_name,
+            base_level,
+            elevated: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Returns whether the span with the given `id` is currently elevated
+    /// (itself or an ancestor recorded the configured field as `true`), if
+    /// this layer has a decision recorded for it.
+    pub fn is_elevated(&self, id: &span::Id) -> Option<bool> {
+        try_lock!(self.elevated.read(), else return None)
+            .get(id)
+            .copied()
+    }
+
+    fn parent_of<S: Subscriber>(
+        &self,
+        attrs: &span::Attributes<'_>,
+        ctx: &Context<'_, S>,
+    ) -> Option<span::Id> {
+        if let Some(parent) = attrs.parent() {
+            return Some(parent.clone());
+        }
+        if attrs.is_contextual() {
+            return ctx.current_span().id().cloned();
+        }
+        None
+    }
+}
+
+struct BoolFieldVisitor<'a> {
+    field_name: &'a str,
+    value: bool,
+}
+
+impl<'a> Visit for BoolFieldVisitor<'a> {
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if field.name() == self.field_name {
+            self.value = value;
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+impl<S: Subscriber> Layer<S> for FieldElevationFilter {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if metadata.is_event() && *metadata.level() > self.base_level {
+            // Whether this callsite is enabled depends on the span it's
+            // invoked from, so its interest can't be cached as a single
+            // global answer.
+            Interest::sometimes()
+        } else {
+            Interest::always()
+        }
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let inherited = self
+            .parent_of(attrs, &ctx)
+            .and_then(|parent| self.is_elevated(&parent))
+            .unwrap_or(false);
+
+        let mut visitor = BoolFieldVisitor {
+            field_name: self.field_name,
+            value: inherited,
+        };
+        attrs.record(&mut visitor);
+
+        try_lock!(self.elevated.write()).insert(id.clone(), visitor.value);
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, _ctx: Context<'_, S>) {
+        let current = self.is_elevated(id).unwrap_or(false);
+        let mut visitor = BoolFieldVisitor {
+            field_name: self.field_name,
+            value: current,
+        };
+        values.record(&mut visitor);
+        if visitor.value != current {
+            try_lock!(self.elevated.write()).insert(id.clone(), visitor.value);
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
+        if !metadata.is_event() || *metadata.level() <= self.base_level {
+            return true;
+        }
+        match ctx.current_span().id() {
+            Some(id) => self.is_elevated(id).unwrap_or(false),
+            None => false,
+        }
+    }
+
+    fn on_close(&self, id: span::Id, _ctx: Context<'_, S>) {
+        try_lock!(self.elevated.write()).remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct CountingSubscriber {
+        events: Arc<Mutex<usize>>,
+        spans: Arc<crate::layer::tests::TrackingSubscriber>,
+    }
+
+    impl Subscriber for CountingSubscriber {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+            self.spans.new_span(attrs)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, _: &tracing_core::Event<'_>) {
+            *self.events.lock().unwrap() += 1;
+        }
+
+        fn enter(&self, id: &span::Id) {
+            self.spans.enter(id)
+        }
+
+        fn exit(&self, id: &span::Id) {
+            self.spans.exit(id)
+        }
+
+        fn try_close(&self, id: span::Id) -> bool {
+            self.spans.try_close(id)
+        }
+
+        fn current_span(&self) -> span::Current {
+            self.spans.current_span()
+        }
+    }
+
+    #[test]
+    fn trace_events_are_dropped_outside_an_elevated_subtree() {
+        let counter = CountingSubscriber::default();
+        let filter = FieldElevationFilter::new("debug_mode", Level::INFO);
+        let subscriber =
+            tracing_core::dispatcher::Dispatch::new(counter.clone().with(filter));
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let span = tracing::info_span!("normal_request");
+            let _enter = span.enter();
+            tracing::info!("always shown");
+            tracing::trace!("too verbose, dropped");
+        });
+
+        assert_eq!(*counter.events.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn trace_events_pass_inside_a_subtree_tagged_debug_mode() {
+        let counter = CountingSubscriber::default();
+        let filter = FieldElevationFilter::new("debug_mode", Level::INFO);
+        let subscriber =
+            tracing_core::dispatcher::Dispatch::new(counter.clone().with(filter));
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let root = tracing::info_span!("noisy_request", debug_mode = true);
+            let _root_enter = root.enter();
+
+            let child = tracing::info_span!("handler");
+            let _child_enter = child.enter();
+
+            tracing::trace!("now visible because an ancestor set debug_mode");
+        });
+
+        assert_eq!(*counter.events.lock().unwrap(), 1);
+    }
+}
@@ -0,0 +1,98 @@
+use tracing_core::{
+    subscriber::{Interest, Subscriber},
+    Metadata,
+};
+
+/// A filter that enables spans and events based on externally-owned context,
+/// such as a thread-local or task-local "baggage" value.
+///
+/// `BaggageFilter` doesn't know anything about *how* that context is stored;
+/// instead, it's constructed with two closures: one that reads the current
+/// value of the context, and one that decides whether a given value should
+/// enable filtering. This lets it generalize over whatever storage (a
+/// `thread_local!`, a task-local in an async runtime, ...) the caller already
+/// uses.
+///
+/// # Examples
+///
+/// ```
+/// # use tracing_subscriber::filter::BaggageFilter;
+/// # struct Baggage;
+/// # impl Baggage { fn contains(&self, _key: &str) -> bool { false } }
+/// # fn current_baggage() -> Baggage { Baggage }
+/// let filter = BaggageFilter::new(current_baggage, |baggage: &Baggage| {
+///     baggage.contains("trace_me")
+/// });
+/// ```
+pub struct BaggageFilter<G, F> {
+    get: G,
+    matches: F,
+}
+
+impl<G, B, F> BaggageFilter<G, F>
+where
+    G: Fn() -> B,
+    F: Fn(&B) -> bool,
+{
+    /// Returns a new `BaggageFilter`.
+    ///
+    /// `get` is called once per callsite check to read the current baggage
+    /// value; `matches` is then called with that value to decide whether the
+    /// callsite should be enabled.
+    pub fn new(get: G, matches: F) -> Self {
+        Self { get, matches }
+    }
+
+    fn is_enabled(&self) -> bool {
+        (self.matches)(&(self.get)())
+    }
+}
+
+impl<S, G, B, F> crate::Layer<S> for BaggageFilter<G, F>
+where
+    S: Subscriber,
+    G: Fn() -> B + 'static,
+    F: Fn(&B) -> bool + 'static,
+{
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        // The baggage value may change between calls, so we can't cache the
+        // result of a single check the way a purely static filter could;
+        // every callsite must be rechecked each time it's hit.
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, _metadata: &Metadata<'_>, _: crate::layer::Context<'_, S>) -> bool {
+        self.is_enabled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::tests::NopSubscriber;
+    use crate::Layer;
+    use std::cell::Cell;
+
+    thread_local! {
+        static BAGGAGE: Cell<bool> = Cell::new(false);
+    }
+
+    #[test]
+    fn reads_accessor_at_check_time() {
+        let filter = BaggageFilter::new(|| BAGGAGE.with(|b| b.get()), |enabled: &bool| *enabled);
+
+        assert!(!filter.is_enabled());
+
+        BAGGAGE.with(|b| b.set(true));
+        assert!(filter.is_enabled());
+
+        BAGGAGE.with(|b| b.set(false));
+        assert!(!filter.is_enabled());
+    }
+
+    #[test]
+    fn is_a_layer() {
+        fn assert_layer<S: tracing_core::Subscriber>(_l: impl Layer<S>) {}
+        assert_layer::<NopSubscriber>(BaggageFilter::new(|| true, |enabled: &bool| *enabled));
+    }
+}
@@ -13,6 +13,20 @@ use std::{
 use tracing_core::{span, Metadata};
 
 /// A single filtering directive.
+///
+/// ## The `<=` level operator
+///
+/// A directive's level (`target=LEVEL`) already behaves as a *ceiling*:
+/// it enables `LEVEL` and anything less verbose (e.g. `app=debug` enables
+/// `DEBUG`, `INFO`, `WARN`, and `ERROR`, but not `TRACE`), never anything
+/// more verbose. There is no separate syntax for a *floor* (a directive that
+/// enables `LEVEL` and anything *more* verbose) in this grammar.
+///
+/// `target<=LEVEL` is accepted as an explicit, more readable alias for
+/// `target=LEVEL` for exactly this reason -- it parses to the same
+/// [`Directive`] and behaves identically. It exists purely so that a filter
+/// spec can spell out the ceiling it already has, for readers who'd
+/// otherwise assume `=` means "this level only".
 // TODO(eliza): add a builder for programmatically constructing directives?
 #[derive(Debug, Eq, PartialEq)]
 pub struct Directive {
@@ -191,7 +205,12 @@ impl FromStr for Directive {
                     (?P<target>[\w:]+)|(?P<span>\[[^\]]*\])
                 ){1,2}
                 (?: # level or nothing
-                    =(?P<level>trace|TRACE|debug|DEBUG|info|INFO|warn|WARN|error|ERROR|off|OFF|[0-5])?
+                    # `<=level` is accepted as an explicit alias for `=level`: a
+                    # directive's level already acts as a ceiling (it enables that
+                    # level and anything less verbose, never anything more verbose),
+                    # so the two spellings are equivalent. See `<=`'s doc comment on
+                    # `Directive` for why there's no separate floor operator.
+                    (?:<=|=)(?P<level>trace|TRACE|debug|DEBUG|info|INFO|warn|WARN|error|ERROR|off|OFF|[0-5])?
                 )?
                 $
                 "
@@ -383,6 +402,16 @@ impl From<LevelFilter> for Directive {
     }
 }
 
+impl From<(LevelFilter, Option<&str>)> for Directive {
+    fn from((level, target): (LevelFilter, Option<&str>)) -> Self {
+        Self {
+            level,
+            target: target.map(String::from),
+            ..Self::default()
+        }
+    }
+}
+
 // === impl DirectiveSet ===
 
 impl<T> DirectiveSet<T> {
@@ -1008,4 +1037,21 @@ mod test {
         assert_eq!(dirs[2].level, LevelFilter::DEBUG);
         assert_eq!(dirs[2].in_span, Some("baz".to_string()));
     }
+
+    #[test]
+    fn level_ceiling_operator_parses_the_same_as_plain_equals() {
+        let with_ceiling: Directive = "noisy<=debug".parse().unwrap();
+        let with_equals: Directive = "noisy=debug".parse().unwrap();
+        assert_eq!(with_ceiling, with_equals);
+        assert_eq!(with_ceiling.level, LevelFilter::DEBUG);
+        assert_eq!(with_ceiling.target, Some("noisy".to_string()));
+    }
+
+    #[test]
+    fn level_ceiling_operator_also_works_with_a_span() {
+        let dir: Directive = "crate1::mod1[foo]<=error".parse().unwrap();
+        assert_eq!(dir.target, Some("crate1::mod1".to_string()));
+        assert_eq!(dir.in_span, Some("foo".to_string()));
+        assert_eq!(dir.level, LevelFilter::ERROR);
+    }
 }
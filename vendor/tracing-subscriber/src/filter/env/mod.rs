@@ -10,6 +10,8 @@ pub use self::{
 };
 mod directive;
 mod field;
+#[cfg(all(feature = "signal", unix))]
+mod signal;
 
 use crate::{
     filter::LevelFilter,
@@ -65,9 +67,42 @@ pub struct FromEnvError {
 #[derive(Debug)]
 enum ErrorKind {
     Parse(ParseError),
+    Parses(ParseErrors),
     Env(env::VarError),
 }
 
+/// Aggregates every malformed directive found while parsing in
+/// [`Builder::strict`] mode, rather than reporting (and stopping at) just
+/// the first one.
+#[derive(Debug)]
+pub struct ParseErrors {
+    errors: Vec<(String, ParseError)>,
+}
+
+impl ParseErrors {
+    /// Returns the directive string and the reason parsing it failed, for
+    /// each malformed directive found.
+    pub fn errors(&self) -> impl Iterator<Item = (&str, &ParseError)> {
+        self.errors.iter().map(|(s, e)| (s.as_str(), e))
+    }
+}
+
+impl fmt::Display for ParseErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} invalid filter directive(s)", self.errors.len())?;
+        for (directive, err) in &self.errors {
+            write!(f, "\n  `{}`: {}", directive, err)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseErrors {
+    fn description(&self) -> &str {
+        "one or more invalid filter directives"
+    }
+}
+
 impl EnvFilter {
     /// The default environment variable used by [`EnvFilter::from_default_env`]
     /// and [`EnvFilter::try_from_default_env`].
@@ -76,6 +111,12 @@ impl EnvFilter {
     /// [`EnvFilter::try_from_default_env`]: #method.try_from_default_env
     pub const DEFAULT_ENV: &'static str = "RUST_LOG";
 
+    /// Returns a [`Builder`] for incrementally constructing an `EnvFilter`
+    /// with non-default behavior, such as [`Builder::strict`] parsing.
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
     /// Returns a new `EnvFilter` from the value of the `RUST_LOG` environment
     /// variable, ignoring any invalid filter directives.
     pub fn from_default_env() -> Self {
@@ -88,10 +129,93 @@ impl EnvFilter {
         env::var(env.as_ref()).map(Self::new).unwrap_or_default()
     }
 
+    /// Returns a new `EnvFilter` from the value of the first environment
+    /// variable in `envs` that is set, ignoring any invalid filter
+    /// directives.
+    ///
+    /// The variables are checked in the order given, so earlier entries take
+    /// precedence over later ones. This is useful for applications that want
+    /// an application-specific variable to override `RUST_LOG` when both are
+    /// set, while still falling back to `RUST_LOG` when the application
+    /// variable is unset.
+    ///
+    /// If none of the given variables are set, this returns the same filter
+    /// as [`EnvFilter::default`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use tracing_subscriber::filter::EnvFilter;
+    ///
+    /// let filter = EnvFilter::from_envs(&["MY_APP_LOG", "RUST_LOG"]);
+    /// ```
+    ///
+    /// [`EnvFilter::default`]: #method.default
+    pub fn from_envs<A: AsRef<str>>(envs: impl IntoIterator<Item = A>) -> Self {
+        for env in envs {
+            if let Ok(value) = env::var(env.as_ref()) {
+                return Self::new(value);
+            }
+        }
+        Self::default()
+    }
+
+    /// Returns a new `EnvFilter` from the value of the first environment
+    /// variable in `envs` that is set, or an error if none of them are set,
+    /// or if the one that is set contains any invalid filter directives.
+    ///
+    /// The variables are checked in the order given, so earlier entries take
+    /// precedence over later ones.
+    ///
+    /// [`EnvFilter::from_envs`]: #method.from_envs
+    pub fn try_from_envs<A: AsRef<str>>(envs: impl IntoIterator<Item = A>) -> Result<Self, FromEnvError> {
+        let mut last_err = None;
+        for env in envs {
+            match env::var(env.as_ref()) {
+                Ok(value) => return value.parse().map_err(Into::into),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or(env::VarError::NotPresent).into())
+    }
+
+    /// Returns a new `EnvFilter` built from a static table of
+    /// `(LevelFilter, Option<target>)` pairs, without parsing any strings.
+    ///
+    /// This is useful for baked-in, compile-time-known filter configurations,
+    /// since it avoids the runtime cost (and the possibility of a parse
+    /// error) of [`EnvFilter::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+    ///
+    /// let filter = EnvFilter::from_static(&[
+    ///     (LevelFilter::INFO, None),
+    ///     (LevelFilter::DEBUG, Some("app")),
+    /// ]);
+    /// ```
+    ///
+    /// [`EnvFilter::new`]: #method.new
+    pub fn from_static(directives: &[(LevelFilter, Option<&str>)]) -> Self {
+        let directives = directives
+            .iter()
+            .map(|(level, target)| Directive::from((level.clone(), *target)));
+        Self::from_directives(directives)
+    }
+
     /// Returns a new `EnvFilter` from the directives in the given string,
     /// ignoring any that are invalid.
+    ///
+    /// Directives may be separated by commas, as in `RUST_LOG`, or by
+    /// newlines, as when reading a multiline filter spec out of a config
+    /// file; the two may be mixed freely. A `#` begins a comment that runs
+    /// to the end of its line. See [`directive_specs`] for the exact
+    /// splitting rules.
+    ///
+    /// [`directive_specs`]: fn.directive_specs.html
     pub fn new<S: AsRef<str>>(dirs: S) -> Self {
-        let directives = dirs.as_ref().split(',').filter_map(|s| match s.parse() {
+        let directives = directive_specs(dirs.as_ref()).filter_map(|s| match s.parse() {
             Ok(d) => Some(d),
             Err(err) => {
                 eprintln!("ignoring `{}`: {}", s, err);
@@ -103,10 +227,10 @@ impl EnvFilter {
 
     /// Returns a new `EnvFilter` from the directives in the given string,
     /// or an error if any are invalid.
+    ///
+    /// See [`EnvFilter::new`] for the accepted directive separators.
     pub fn try_new<S: AsRef<str>>(dirs: S) -> Result<Self, ParseError> {
-        let directives = dirs
-            .as_ref()
-            .split(',')
+        let directives = directive_specs(dirs.as_ref())
             .map(|s| s.parse())
             .collect::<Result<Vec<_>, _>>()?;
         Ok(Self::from_directives(directives))
@@ -126,6 +250,37 @@ impl EnvFilter {
         env::var(env.as_ref())?.parse().map_err(Into::into)
     }
 
+    /// Installs a `SIGHUP` handler that reloads this filter from the named
+    /// environment variable, using the given [`reload::Handle`].
+    ///
+    /// This is a convenience wrapper over [`reload`] plus a Unix signal
+    /// handler, for services that conventionally reload their configuration
+    /// on `SIGHUP`. The signal handler itself only sets a flag (the one
+    /// async-signal-safe thing to do); a background thread polls that flag
+    /// and performs the actual re-read and reload off-signal. If the
+    /// environment variable is unset, or contains invalid directives, the
+    /// failure is logged to stderr (via `eprintln!`, since this crate does
+    /// not depend on `tracing` itself) and the previous filter is left in
+    /// place rather than the process crashing.
+    ///
+    /// Requires the `signal` feature flag, and is Unix-only.
+    ///
+    /// [`reload::Handle`]: ../../reload/struct.Handle.html
+    /// [`reload`]: ../../reload/index.html
+    #[cfg(all(feature = "signal", unix))]
+    pub fn reload_on_sighup<S>(handle: crate::reload::Handle<EnvFilter, S>, var_name: &'static str)
+    where
+        S: Subscriber + Send + Sync + 'static,
+    {
+        unsafe {
+            libc::signal(libc::SIGHUP, self::signal::on_sighup as libc::sighandler_t);
+        }
+        std::thread::spawn(move || loop {
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            self::signal::reload_if_pending(&handle, var_name);
+        });
+    }
+
     /// Add a filtering directive to this `EnvFilter`.
     ///
     /// The added directive will be used in addition to any previously set
@@ -200,6 +355,67 @@ impl EnvFilter {
     }
 }
 
+/// Incrementally constructs an [`EnvFilter`], with control over how
+/// malformed directives are handled.
+///
+/// Returned by [`EnvFilter::builder`].
+#[derive(Debug, Default)]
+pub struct Builder {
+    strict: bool,
+}
+
+impl Builder {
+    /// Sets whether a malformed directive causes parsing to fail.
+    ///
+    /// By default (`strict(false)`), a malformed directive is skipped and a
+    /// warning is printed to stderr -- convenient interactively, but a typo
+    /// in `RUST_LOG` can silently disable most of a deployment's logging
+    /// with no indication anything went wrong. With `strict(true)`,
+    /// [`Builder::parse`] and [`Builder::from_env`] instead fail with a
+    /// [`ParseErrors`] listing every malformed directive found, so bad
+    /// configuration is rejected up front -- e.g. in CI, or at startup in a
+    /// deployment that would rather fail to boot than run unfiltered.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Parses `dirs` into an `EnvFilter`.
+    ///
+    /// In lenient mode (the default), this behaves like [`EnvFilter::new`]:
+    /// malformed directives are skipped. In [`strict`](Builder::strict)
+    /// mode, a string with one or more malformed directives is rejected
+    /// with a [`ParseErrors`] aggregating all of them, rather than just the
+    /// first.
+    pub fn parse<S: AsRef<str>>(&self, dirs: S) -> Result<EnvFilter, ParseErrors> {
+        if !self.strict {
+            return Ok(EnvFilter::new(dirs));
+        }
+
+        let mut directives = Vec::new();
+        let mut errors = Vec::new();
+        for spec in directive_specs(dirs.as_ref()) {
+            match spec.parse() {
+                Ok(d) => directives.push(d),
+                Err(e) => errors.push((spec.to_string(), e)),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(ParseErrors { errors });
+        }
+
+        Ok(EnvFilter::from_directives(directives))
+    }
+
+    /// Returns a new `EnvFilter` from the value of the given environment
+    /// variable, honoring [`strict`](Builder::strict).
+    pub fn from_env<A: AsRef<str>>(&self, env: A) -> Result<EnvFilter, FromEnvError> {
+        let value = env::var(env.as_ref())?;
+        self.parse(value).map_err(Into::into)
+    }
+}
+
 impl<S: Subscriber> Layer<S> for EnvFilter {
     fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
         if metadata.is_span() {
@@ -281,6 +497,22 @@ impl<S: Subscriber> Layer<S> for EnvFilter {
     }
 }
 
+/// Splits a directive spec into individual directive strings, honoring
+/// `#`-prefixed comments and allowing directives to be separated by commas,
+/// newlines, or both.
+///
+/// For each line, anything from a `#` to the end of the line is dropped;
+/// what remains is split on commas, and each resulting piece is trimmed of
+/// surrounding whitespace. Empty pieces (blank lines, trailing commas,
+/// comment-only lines) are skipped.
+fn directive_specs(spec: &str) -> impl Iterator<Item = &str> {
+    spec.lines()
+        .map(|line| line.split('#').next().unwrap_or(""))
+        .flat_map(|line| line.split(','))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
 impl FromStr for EnvFilter {
     type Err = ParseError;
 
@@ -304,6 +536,20 @@ impl Default for EnvFilter {
     }
 }
 
+impl Clone for EnvFilter {
+    /// Returns an `EnvFilter` with the same directives as this one.
+    ///
+    /// This round-trips through the filter's [`Display`](fmt::Display)
+    /// representation rather than deep-cloning the directive sets directly,
+    /// since those aren't `Clone`. The clone also starts with empty
+    /// per-span and per-callsite match caches, just like
+    /// [`EnvFilter::new`] — those are rebuilt lazily as the clone sees
+    /// spans and callsites of its own.
+    fn clone(&self) -> Self {
+        Self::try_new(self.to_string()).unwrap_or_else(|_| Self::from_directives(std::iter::empty()))
+    }
+}
+
 impl fmt::Display for EnvFilter {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut statics = self.statics.iter();
@@ -349,10 +595,19 @@ impl From<env::VarError> for FromEnvError {
     }
 }
 
+impl From<ParseErrors> for FromEnvError {
+    fn from(p: ParseErrors) -> Self {
+        Self {
+            kind: ErrorKind::Parses(p),
+        }
+    }
+}
+
 impl fmt::Display for FromEnvError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.kind {
             ErrorKind::Parse(ref p) => p.fmt(f),
+            ErrorKind::Parses(ref p) => p.fmt(f),
             ErrorKind::Env(ref e) => e.fmt(f),
         }
     }
@@ -362,6 +617,7 @@ impl Error for FromEnvError {
     fn description(&self) -> &str {
         match self.kind {
             ErrorKind::Parse(ref p) => p.description(),
+            ErrorKind::Parses(ref p) => p.description(),
             ErrorKind::Env(ref e) => e.description(),
         }
     }
@@ -369,6 +625,7 @@ impl Error for FromEnvError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self.kind {
             ErrorKind::Parse(ref p) => Some(p),
+            ErrorKind::Parses(ref p) => Some(p),
             ErrorKind::Env(ref e) => Some(e),
         }
     }
@@ -501,6 +758,71 @@ mod tests {
         assert!(interest.is_never());
     }
 
+    #[test]
+    fn from_static_matches_equivalent_string() {
+        let from_static = EnvFilter::from_static(&[
+            (LevelFilter::INFO, None),
+            (LevelFilter::DEBUG, Some("app")),
+        ]);
+        let from_string: EnvFilter = "info,app=debug".parse().unwrap();
+        assert_eq!(from_static.statics, from_string.statics);
+        assert_eq!(from_static.dynamics, from_string.dynamics);
+    }
+
+    #[test]
+    fn newline_separated_directives_parse_like_comma_separated() {
+        let from_lines: EnvFilter = "info\napp=debug".parse().unwrap();
+        let from_commas: EnvFilter = "info,app=debug".parse().unwrap();
+        assert_eq!(from_lines.statics, from_commas.statics);
+        assert_eq!(from_lines.dynamics, from_commas.dynamics);
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_ignored() {
+        let with_comments: EnvFilter = "\
+            # global default\n\
+            info\n\
+            \n\
+            app=debug # only this crate gets debug logs\n\
+        "
+        .parse()
+        .unwrap();
+        let without_comments: EnvFilter = "info,app=debug".parse().unwrap();
+        assert_eq!(with_comments.statics, without_comments.statics);
+        assert_eq!(with_comments.dynamics, without_comments.dynamics);
+    }
+
+    #[test]
+    fn comma_and_newline_separators_can_be_mixed() {
+        let mixed: EnvFilter = "info, app=debug\nother_crate=warn".parse().unwrap();
+        let all_commas: EnvFilter = "info,app=debug,other_crate=warn".parse().unwrap();
+        assert_eq!(mixed.statics, all_commas.statics);
+        assert_eq!(mixed.dynamics, all_commas.dynamics);
+    }
+
+    #[test]
+    fn from_envs_prefers_earlier_set_variable() {
+        // Neither of these variables is actually set in the test process'
+        // environment, so `from_envs` should fall back to the default
+        // filter, exercising the "none are set" path without racing other
+        // tests that do set environment variables.
+        let filter = EnvFilter::from_envs(&[
+            "TRACING_SUBSCRIBER_TEST_FROM_ENVS_FIRST",
+            "TRACING_SUBSCRIBER_TEST_FROM_ENVS_SECOND",
+        ]);
+        assert_eq!(filter.statics, EnvFilter::default().statics);
+        assert_eq!(filter.dynamics, EnvFilter::default().dynamics);
+    }
+
+    #[test]
+    fn try_from_envs_errors_when_none_are_set() {
+        let result = EnvFilter::try_from_envs(&[
+            "TRACING_SUBSCRIBER_TEST_TRY_FROM_ENVS_FIRST",
+            "TRACING_SUBSCRIBER_TEST_TRY_FROM_ENVS_SECOND",
+        ]);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn roundtrip() {
         let f1: EnvFilter =
@@ -511,4 +833,69 @@ mod tests {
         assert_eq!(f1.statics, f2.statics);
         assert_eq!(f1.dynamics, f2.dynamics);
     }
+
+    #[test]
+    fn level_ceiling_directive_suppresses_more_verbose_levels() {
+        let filter = EnvFilter::new("noisy<=debug").with_subscriber(NoSubscriber);
+
+        static TRACE_META: &'static Metadata<'static> = &Metadata::new(
+            "event",
+            "noisy",
+            Level::TRACE,
+            None,
+            None,
+            None,
+            FieldSet::new(&[], identify_callsite!(&Cs)),
+            Kind::EVENT,
+        );
+        static DEBUG_META: &'static Metadata<'static> = &Metadata::new(
+            "event",
+            "noisy",
+            Level::DEBUG,
+            None,
+            None,
+            None,
+            FieldSet::new(&[], identify_callsite!(&Cs)),
+            Kind::EVENT,
+        );
+
+        assert!(!filter.enabled(TRACE_META), "TRACE should be suppressed");
+        assert!(filter.enabled(DEBUG_META), "DEBUG should pass");
+    }
+
+    #[test]
+    fn builder_is_lenient_by_default() {
+        let result = EnvFilter::builder().parse("app=debug,not a directive,other=warn");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn strict_builder_rejects_a_single_malformed_directive() {
+        let result = EnvFilter::builder()
+            .strict(true)
+            .parse("app=debug,not a directive,other=warn");
+        let err = result.expect_err("a malformed directive should be rejected");
+        let directives: Vec<&str> = err.errors().map(|(d, _)| d).collect();
+        assert_eq!(directives, vec!["not a directive"]);
+    }
+
+    #[test]
+    fn strict_builder_aggregates_every_malformed_directive() {
+        let result = EnvFilter::builder()
+            .strict(true)
+            .parse("app=debug,not a directive,other=bogus_level,still_bad[");
+        let err = result.expect_err("every malformed directive should be reported");
+        assert_eq!(err.errors().count(), 3);
+    }
+
+    #[test]
+    fn strict_builder_accepts_a_fully_valid_spec() {
+        let strict = EnvFilter::builder()
+            .strict(true)
+            .parse("app=debug,other_crate=warn")
+            .expect("a valid spec should parse in strict mode");
+        let lenient = EnvFilter::new("app=debug,other_crate=warn");
+        assert_eq!(strict.statics, lenient.statics);
+        assert_eq!(strict.dynamics, lenient.dynamics);
+    }
 }
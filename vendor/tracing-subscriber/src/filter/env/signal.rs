@@ -0,0 +1,96 @@
+//! The `SIGHUP` reload worker for [`EnvFilter::reload_on_sighup`].
+//!
+//! [`EnvFilter::reload_on_sighup`]: super::EnvFilter::reload_on_sighup
+use super::EnvFilter;
+use crate::reload;
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing_core::subscriber::Subscriber;
+
+static SIGHUP_RECEIVED: AtomicBool = AtomicBool::new(false);
+
+/// The actual signal handler. Per `signal-safety(7)`, this must do nothing
+/// beyond setting a flag; the real work happens in [`reload_if_pending`],
+/// called from a regular (non-signal) thread.
+pub(super) extern "C" fn on_sighup(_: libc::c_int) {
+    SIGHUP_RECEIVED.store(true, Ordering::SeqCst);
+}
+
+/// If a `SIGHUP` has been received since the last call, re-reads `var_name`
+/// and reloads `handle` with it. Does nothing if no signal is pending.
+///
+/// Parse or environment-lookup failures are reported via `eprintln!` and
+/// otherwise ignored, so a bad reload can never bring the process down.
+pub(super) fn reload_if_pending<S>(handle: &reload::Handle<EnvFilter, S>, var_name: &str)
+where
+    S: Subscriber + 'static,
+{
+    if !SIGHUP_RECEIVED.swap(false, Ordering::SeqCst) {
+        return;
+    }
+
+    let value = match env::var(var_name) {
+        Ok(value) => value,
+        Err(err) => {
+            eprintln!(
+                "ignoring SIGHUP reload: `{}` is not set: {}",
+                var_name, err
+            );
+            return;
+        }
+    };
+
+    let new_filter = match EnvFilter::try_new(&value) {
+        Ok(new_filter) => new_filter,
+        Err(err) => {
+            eprintln!("ignoring SIGHUP reload: invalid `{}`: {}", var_name, err);
+            return;
+        }
+    };
+
+    if let Err(err) = handle.reload(new_filter) {
+        eprintln!("failed to reload `EnvFilter` after SIGHUP: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::tests::NopSubscriber;
+
+    #[test]
+    fn reload_if_pending_is_a_noop_without_a_pending_signal() {
+        SIGHUP_RECEIVED.store(false, Ordering::SeqCst);
+        let (_layer, handle) = reload::Layer::<EnvFilter, NopSubscriber>::new(EnvFilter::new("info"));
+
+        std::env::set_var("TRACING_SIGHUP_TEST_NOOP", "debug");
+        reload_if_pending(&handle, "TRACING_SIGHUP_TEST_NOOP");
+
+        let current = handle.with_current(|f| format!("{}", f)).unwrap();
+        assert_eq!(current, "info");
+    }
+
+    #[test]
+    fn reload_if_pending_reloads_from_the_env_var() {
+        let (_layer, handle) = reload::Layer::<EnvFilter, NopSubscriber>::new(EnvFilter::new("info"));
+
+        std::env::set_var("TRACING_SIGHUP_TEST_RELOAD", "trace");
+        SIGHUP_RECEIVED.store(true, Ordering::SeqCst);
+        reload_if_pending(&handle, "TRACING_SIGHUP_TEST_RELOAD");
+
+        let reloaded = handle.clone_current().unwrap();
+        assert_eq!(format!("{}", reloaded), "trace");
+    }
+
+    #[test]
+    fn invalid_directive_leaves_prior_filter_in_place() {
+        let (_layer, handle) = reload::Layer::<EnvFilter, NopSubscriber>::new(EnvFilter::new("info"));
+
+        std::env::set_var("TRACING_SIGHUP_TEST_INVALID", "not a valid directive===");
+        SIGHUP_RECEIVED.store(true, Ordering::SeqCst);
+        reload_if_pending(&handle, "TRACING_SIGHUP_TEST_INVALID");
+
+        let current = handle.clone_current().unwrap();
+        assert_eq!(format!("{}", current), "info");
+    }
+}
@@ -0,0 +1,136 @@
+use tracing_core::{
+    subscriber::{Interest, Subscriber},
+    Metadata,
+};
+
+use crate::filter::LevelFilter;
+use crate::layer::Context;
+
+/// A `Layer` that always enables events at or above a configured floor
+/// level, even when the wrapped `ceiling` filter would otherwise suppress
+/// them — while leaving spans entirely up to `ceiling`.
+///
+/// This is useful when spans are filtered aggressively to cut down on
+/// span-open noise (e.g. `LevelFilter::INFO`), but high-severity events
+/// emitted from deeper, normally-filtered spans must always be visible.
+/// Raising the span ceiling to let those events through would also
+/// un-suppress every span at that level; `EventFloor` decouples the two.
+///
+/// Returned by [`always_emit_events_at`].
+pub struct EventFloor<L> {
+    floor: LevelFilter,
+    ceiling: L,
+}
+
+/// Returns a [`Layer`] that always enables events at or above `floor`,
+/// deferring everything else — including all spans — to `ceiling`.
+///
+/// Unlike a bare `LevelFilter`, `ceiling` is passed in explicitly rather
+/// than read from shared state, so the floor composes the same way any
+/// other `Layer` does.
+///
+/// # Examples
+///
+/// ```
+/// # use tracing_subscriber::filter::{always_emit_events_at, LevelFilter};
+/// # use tracing_core::Level;
+/// // Spans are filtered to INFO, but WARN+ events always get through.
+/// let filter = always_emit_events_at(Level::WARN, LevelFilter::INFO);
+/// ```
+///
+/// [`Layer`]: crate::layer::Layer
+pub fn always_emit_events_at<L>(floor: impl Into<LevelFilter>, ceiling: L) -> EventFloor<L> {
+    EventFloor {
+        floor: floor.into(),
+        ceiling,
+    }
+}
+
+impl<S, L> crate::Layer<S> for EventFloor<L>
+where
+    S: Subscriber,
+    L: crate::Layer<S>,
+{
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if metadata.is_event() && self.floor >= *metadata.level() {
+            return Interest::always();
+        }
+        self.ceiling.register_callsite(metadata)
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
+        if metadata.is_event() && self.floor >= *metadata.level() {
+            return true;
+        }
+        self.ceiling.enabled(metadata, ctx)
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        match self.ceiling.max_level_hint() {
+            Some(hint) => Some(std::cmp::max(hint, self.floor.clone())),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::tests::NopSubscriber;
+    use crate::Layer;
+    use tracing_core::Level;
+
+    fn meta_event(level: Level) -> &'static Metadata<'static> {
+        macro_rules! event_meta {
+            ($level:expr) => {{
+                static META: Metadata<'static> = tracing_core::metadata! {
+                    name: "event",
+                    target: "event_floor::tests",
+                    level: $level,
+                    fields: &[],
+                    callsite: &CALLSITE,
+                    kind: tracing_core::Kind::EVENT,
+                };
+                struct TestCallsite;
+                static CALLSITE: TestCallsite = TestCallsite;
+                impl tracing_core::Callsite for TestCallsite {
+                    fn set_interest(&self, _: Interest) {}
+                    fn metadata(&self) -> &'static Metadata<'static> {
+                        &META
+                    }
+                }
+                &META
+            }};
+        }
+        match level {
+            Level::ERROR => event_meta!(Level::ERROR),
+            Level::WARN => event_meta!(Level::WARN),
+            Level::INFO => event_meta!(Level::INFO),
+            Level::DEBUG => event_meta!(Level::DEBUG),
+            Level::TRACE => event_meta!(Level::TRACE),
+        }
+    }
+
+    #[test]
+    fn events_at_or_above_floor_bypass_the_ceiling() {
+        let filter = always_emit_events_at(Level::WARN, LevelFilter::ERROR);
+
+        let ctx: Context<'_, NopSubscriber> = Context::none();
+        assert!(filter.enabled(meta_event(Level::WARN), ctx.clone()));
+        assert!(filter.enabled(meta_event(Level::ERROR), ctx.clone()));
+    }
+
+    #[test]
+    fn events_below_the_floor_still_defer_to_the_ceiling() {
+        let filter = always_emit_events_at(Level::WARN, LevelFilter::ERROR);
+
+        let ctx: Context<'_, NopSubscriber> = Context::none();
+        assert!(!filter.enabled(meta_event(Level::INFO), ctx));
+    }
+
+    #[test]
+    fn is_a_layer() {
+        fn assert_layer<S: tracing_core::Subscriber>(_l: impl Layer<S>) {}
+        assert_layer::<NopSubscriber>(always_emit_events_at(Level::WARN, LevelFilter::ERROR));
+    }
+}
@@ -59,6 +59,10 @@ impl<S: Subscriber> crate::Layer<S> for LevelFilter {
     fn enabled(&self, metadata: &Metadata<'_>, _: crate::layer::Context<'_, S>) -> bool {
         self >= metadata.level()
     }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(self.clone())
+    }
 }
 
 impl PartialEq<Level> for LevelFilter {
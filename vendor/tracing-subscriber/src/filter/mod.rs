@@ -2,11 +2,21 @@
 //! subscriber.
 //!
 //! [`Layer`]: ../trait.Layer.html
+mod baggage;
 #[cfg(feature = "env-filter")]
 mod env;
+mod event_floor;
 mod level;
+mod startup_grace;
+mod targets;
+mod union;
 
+pub use self::baggage::BaggageFilter;
+pub use self::event_floor::{always_emit_events_at, EventFloor};
 pub use self::level::{LevelFilter, ParseError as LevelParseError};
+pub use self::startup_grace::StartupGraceFilter;
+pub use self::targets::Targets;
+pub use self::union::Union;
 
 #[cfg(feature = "env-filter")]
 pub use self::env::*;
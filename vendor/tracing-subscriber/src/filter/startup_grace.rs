@@ -0,0 +1,142 @@
+use crate::filter::LevelFilter;
+use crate::layer::Context;
+use std::time::{Duration, Instant};
+use tracing_core::{
+    subscriber::{Interest, Subscriber},
+    Metadata,
+};
+
+/// A `Layer` that suppresses events more verbose than a configured level for
+/// a grace period after it's constructed, then defers entirely to `base`.
+///
+/// Some subscribers (shipping to a network sink, sampling based on recent
+/// history, etc.) are noisiest — and least reliable — in the moment they're
+/// installed, before any downstream connection or warm-up state is ready.
+/// `StartupGraceFilter` papers over that window: for `grace`, only events at
+/// or below `suppressed_level` pass (regardless of what `base` would decide);
+/// once `grace` has elapsed, every decision is `base`'s alone. Spans are
+/// never suppressed by the grace period — only events are — so span context
+/// established during startup is still available once the grace period ends.
+///
+/// The grace period is measured from when this filter is constructed, not
+/// from when the `Subscriber` it's installed into starts receiving events.
+/// Construct it as close to `set_global_default`/`set_default` as possible.
+pub struct StartupGraceFilter<L> {
+    installed_at: Instant,
+    grace: Duration,
+    suppressed_level: LevelFilter,
+    base: L,
+}
+
+impl<L> StartupGraceFilter<L> {
+    /// Returns a new `StartupGraceFilter` wrapping `base`, admitting only
+    /// events at or below `suppressed_level` for the first `grace` duration.
+    pub fn new(grace: Duration, suppressed_level: LevelFilter, base: L) -> Self {
+        Self {
+            installed_at: Instant::now(),
+            grace,
+            suppressed_level,
+            base,
+        }
+    }
+
+    fn in_grace_period(&self) -> bool {
+        self.installed_at.elapsed() < self.grace
+    }
+}
+
+impl<S, L> crate::Layer<S> for StartupGraceFilter<L>
+where
+    S: Subscriber,
+    L: crate::Layer<S>,
+{
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if metadata.is_event() && self.in_grace_period() && self.suppressed_level < *metadata.level() {
+            // Whether this callsite is enabled depends on elapsed wall-clock
+            // time, so its interest can't be cached as a single global
+            // answer until the grace period has passed.
+            Interest::sometimes()
+        } else {
+            self.base.register_callsite(metadata)
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
+        if metadata.is_event() && self.in_grace_period() && self.suppressed_level < *metadata.level() {
+            return false;
+        }
+        self.base.enabled(metadata, ctx)
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        self.base.max_level_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::span;
+
+    #[derive(Clone, Default)]
+    struct CountingSubscriber {
+        events: Arc<Mutex<usize>>,
+    }
+
+    impl Subscriber for CountingSubscriber {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, _: &tracing_core::Event<'_>) {
+            *self.events.lock().unwrap() += 1;
+        }
+
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn events_above_the_suppressed_level_are_dropped_during_the_grace_period() {
+        let counter = CountingSubscriber::default();
+        let filter =
+            StartupGraceFilter::new(Duration::from_secs(60), LevelFilter::ERROR, LevelFilter::TRACE);
+        let subscriber = tracing_core::dispatcher::Dispatch::new(counter.clone().with(filter));
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            tracing::error!("always shown, even during grace");
+            tracing::info!("too verbose during grace, dropped");
+        });
+
+        assert_eq!(*counter.events.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn events_pass_through_to_base_once_the_grace_period_has_elapsed() {
+        let counter = CountingSubscriber::default();
+        let filter =
+            StartupGraceFilter::new(Duration::from_millis(1), LevelFilter::ERROR, LevelFilter::TRACE);
+        let subscriber = tracing_core::dispatcher::Dispatch::new(counter.clone().with(filter));
+
+        std::thread::sleep(Duration::from_millis(20));
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            tracing::info!("grace period has already elapsed");
+        });
+
+        assert_eq!(*counter.events.lock().unwrap(), 1);
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use tracing_core::{
+    subscriber::{Interest, Subscriber},
+    Metadata,
+};
+
+/// A filter that enables spans and events whose target starts with one of a
+/// configured set of prefixes.
+///
+/// `Targets` is intended for the common case of a large, static allow-list of
+/// target prefixes (as opposed to [`EnvFilter`]'s per-target level
+/// directives). Matching is done with a prefix trie rather than scanning
+/// every configured prefix for every callsite, so lookups are proportional
+/// to the length of the target being matched, not to the number of
+/// configured prefixes.
+///
+/// Like [`EnvFilter`]'s target directives, prefix matching is a plain byte
+/// prefix of the target string; `Targets` does not special-case `::`
+/// boundaries.
+///
+/// [`EnvFilter`]: struct.EnvFilter.html
+#[derive(Clone, Debug)]
+pub struct Targets {
+    nodes: Vec<Node>,
+}
+
+impl Default for Targets {
+    /// Returns an empty `Targets` filter, which disables every target.
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node::default()],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Node {
+    children: HashMap<u8, usize>,
+    is_prefix_end: bool,
+}
+
+impl Targets {
+    /// Returns a new `Targets` filter that enables spans and events whose
+    /// target starts with one of the given `prefixes`.
+    pub fn new<I>(prefixes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut targets = Self {
+            nodes: vec![Node::default()],
+        };
+        for prefix in prefixes {
+            targets.insert(prefix.as_ref());
+        }
+        targets
+    }
+
+    fn insert(&mut self, prefix: &str) {
+        let mut node = 0;
+        for &byte in prefix.as_bytes() {
+            node = match self.nodes[node].children.get(&byte) {
+                Some(&next) => next,
+                None => {
+                    self.nodes.push(Node::default());
+                    let next = self.nodes.len() - 1;
+                    self.nodes[node].children.insert(byte, next);
+                    next
+                }
+            };
+        }
+        self.nodes[node].is_prefix_end = true;
+    }
+
+    /// Returns `true` if `target` starts with one of this filter's
+    /// configured prefixes.
+    pub fn enabled(&self, target: &str) -> bool {
+        let mut node = 0;
+        if self.nodes[node].is_prefix_end {
+            return true;
+        }
+        for &byte in target.as_bytes() {
+            node = match self.nodes[node].children.get(&byte) {
+                Some(&next) => next,
+                None => return false,
+            };
+            if self.nodes[node].is_prefix_end {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl<S: Subscriber> crate::Layer<S> for Targets {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if self.enabled(metadata.target()) {
+            Interest::always()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, _: crate::layer::Context<'_, S>) -> bool {
+        Targets::enabled(self, metadata.target())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_exact_and_prefixed_targets() {
+        let targets = Targets::new(&["my_crate", "other_crate::mod_a"]);
+        assert!(targets.enabled("my_crate"));
+        assert!(targets.enabled("my_crate::sub_mod"));
+        assert!(targets.enabled("other_crate::mod_a::thing"));
+    }
+
+    #[test]
+    fn rejects_non_matching_targets() {
+        let targets = Targets::new(&["my_crate"]);
+        assert!(!targets.enabled("other_crate"));
+        assert!(!targets.enabled("my_cra"));
+        assert!(!targets.enabled(""));
+    }
+
+    #[test]
+    fn empty_prefix_matches_everything() {
+        let targets = Targets::new(&[""]);
+        assert!(targets.enabled("anything"));
+        assert!(targets.enabled(""));
+    }
+
+    #[test]
+    fn matches_parity_with_linear_scan_at_scale() {
+        let prefixes: Vec<String> = (0..64).map(|i| format!("crate_{:02}", i)).collect();
+        let targets = Targets::new(&prefixes);
+
+        let check = |target: &str| -> bool { prefixes.iter().any(|p| target.starts_with(p.as_str())) };
+
+        for i in 0..64 {
+            let hit = format!("crate_{:02}::module::function", i);
+            assert_eq!(targets.enabled(&hit), check(&hit));
+        }
+        assert_eq!(targets.enabled("crate_99::module"), check("crate_99::module"));
+        assert_eq!(targets.enabled("unrelated"), check("unrelated"));
+    }
+
+    #[test]
+    fn is_a_layer() {
+        use crate::layer::tests::NopSubscriber;
+        use crate::Layer;
+
+        fn assert_layer<S: tracing_core::Subscriber>(_l: impl Layer<S>) {}
+        assert_layer::<NopSubscriber>(Targets::new(&["my_crate"]));
+    }
+}
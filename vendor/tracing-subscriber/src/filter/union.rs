@@ -0,0 +1,232 @@
+use crate::layer::{Context, Layer};
+use tracing_core::{
+    span,
+    subscriber::{Interest, Subscriber},
+    Event, Metadata,
+};
+
+/// Combines two `Layer`s into one whose notion of "globally enabled" is the
+/// *union* of theirs, rather than the intersection [`Layered`] computes when
+/// two layers are simply stacked.
+///
+/// By default, stacking layers ANDs their [`enabled`]/[`register_callsite`]
+/// decisions: if the outer layer rejects a callsite, the inner layer is
+/// never even asked (see the [module-level documentation] on filtering).
+/// That's the right behavior when every layer in a stack agrees on one
+/// shared threshold, but it starves a layer with a broader or differently
+/// scoped interest — e.g. a formatting layer that only wants `INFO` and
+/// above stacked with a sampling layer that wants `TRACE` within a specific
+/// subtree. With plain stacking, a `TRACE` event the sampling layer cares
+/// about never reaches it, because the formatting layer's filter already
+/// said no.
+///
+/// `Union` fixes this for exactly two layers: its `enabled` and
+/// `register_callsite` report a callsite as enabled if *either* inner layer
+/// would enable it on its own, and its [`max_level_hint`] is the more
+/// verbose (i.e. numerically greater) of the two, so neither inner layer's
+/// `register_callsite` caching is starved by the other's.
+///
+/// This crate predates the `Registry`/per-layer `Filter` machinery later
+/// `tracing-subscriber` releases use to track *which* layer in a stack
+/// wants a given event (a `FilterId` bitmask recorded per-span). In its
+/// place, `Union` asks each inner layer's own `enabled` again before passing
+/// it an event, so `on_event` only reaches an inner layer that would have
+/// enabled that event on its own. Span notifications (`new_span`,
+/// `on_enter`, `on_exit`, `on_close`, etc.) are passed to both inner layers
+/// for every span the union lets through, exactly as if they'd been stacked
+/// normally; a layer that only cares about some spans must still ignore the
+/// others in its notification methods, per the [`Layer`] trait's filtering
+/// guidance.
+///
+/// [`Layered`]: ../layer/struct.Layered.html
+/// [`enabled`]: ../layer/trait.Layer.html#method.enabled
+/// [`register_callsite`]: ../layer/trait.Layer.html#method.register_callsite
+/// [`max_level_hint`]: ../layer/trait.Layer.html#method.max_level_hint
+/// [module-level documentation]: ../layer/trait.Layer.html#filtering-with-layers
+/// [`Layer`]: ../layer/trait.Layer.html
+#[derive(Clone, Debug)]
+pub struct Union<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> Union<A, B> {
+    /// Combines `a` and `b` into a single `Layer` whose global `enabled`,
+    /// `register_callsite`, and `max_level_hint` reflect the union of the
+    /// two, rather than their intersection.
+    pub fn new(a: A, b: B) -> Self {
+        Self { a, b }
+    }
+}
+
+impl<S, A, B> Layer<S> for Union<A, B>
+where
+    S: Subscriber,
+    A: Layer<S>,
+    B: Layer<S>,
+{
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        let a = self.a.register_callsite(metadata);
+        let b = self.b.register_callsite(metadata);
+        if a.is_never() && b.is_never() {
+            Interest::never()
+        } else if a.is_always() && b.is_always() {
+            Interest::always()
+        } else {
+            // One side wants this callsite and the other doesn't, or either
+            // side's interest is conditional — cache "sometimes" so this
+            // callsite is re-evaluated on every occurrence instead of a
+            // stale `never` starving whichever side actually wants it.
+            Interest::sometimes()
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
+        self.a.enabled(metadata, ctx.clone()) || self.b.enabled(metadata, ctx)
+    }
+
+    fn max_level_hint(&self) -> Option<crate::filter::LevelFilter> {
+        match (self.a.max_level_hint(), self.b.max_level_hint()) {
+            (Some(a), Some(b)) => Some(std::cmp::max(a, b)),
+            _ => None,
+        }
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        self.a.new_span(attrs, id, ctx.clone());
+        self.b.new_span(attrs, id, ctx);
+    }
+
+    fn on_record(&self, span: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        self.a.on_record(span, values, ctx.clone());
+        self.b.on_record(span, values, ctx);
+    }
+
+    fn on_follows_from(&self, span: &span::Id, follows: &span::Id, ctx: Context<'_, S>) {
+        self.a.on_follows_from(span, follows, ctx.clone());
+        self.b.on_follows_from(span, follows, ctx);
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if self.a.enabled(event.metadata(), ctx.clone()) {
+            self.a.on_event(event, ctx.clone());
+        }
+        if self.b.enabled(event.metadata(), ctx.clone()) {
+            self.b.on_event(event, ctx);
+        }
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        self.a.on_enter(id, ctx.clone());
+        self.b.on_enter(id, ctx);
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        self.a.on_exit(id, ctx.clone());
+        self.b.on_exit(id, ctx);
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        self.a.on_close(id.clone(), ctx.clone());
+        self.b.on_close(id, ctx);
+    }
+
+    fn on_id_change(&self, old: &span::Id, new: &span::Id, ctx: Context<'_, S>) {
+        self.a.on_id_change(old, new, ctx.clone());
+        self.b.on_id_change(old, new, ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::LevelFilter;
+    use crate::prelude::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::dispatcher::Dispatch;
+
+    #[derive(Clone, Default)]
+    struct RecordingLayer {
+        seen: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct MessageVisitor<'a>(&'a mut String);
+
+    impl<'a> tracing_core::field::Visit for MessageVisitor<'a> {
+        fn record_debug(
+            &mut self,
+            field: &tracing_core::field::Field,
+            value: &dyn std::fmt::Debug,
+        ) {
+            if field.name() == "message" {
+                *self.0 = format!("{:?}", value);
+            }
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for RecordingLayer {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            let mut message = String::new();
+            event.record(&mut MessageVisitor(&mut message));
+            self.seen.lock().unwrap().push(message);
+        }
+    }
+
+    struct AcceptAllSubscriber;
+
+    impl Subscriber for AcceptAllSubscriber {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn a_callsite_rejected_by_one_side_is_not_starved_by_the_other() {
+        // `info_layer` (paired with `info_recorder`) only wants INFO and
+        // above; `trace_layer` (paired with `trace_recorder`) wants
+        // everything. Plain stacking would let `info_layer`'s rejection of a
+        // TRACE event hide it from `trace_layer` entirely; `Union` must not.
+        let info_recorder = RecordingLayer::default();
+        let trace_recorder = RecordingLayer::default();
+
+        let info_layer = LevelFilter::INFO.and_then(info_recorder.clone());
+        let trace_layer = LevelFilter::TRACE.and_then(trace_recorder.clone());
+
+        let union = Union::new(info_layer, trace_layer);
+        let dispatch = Dispatch::new(AcceptAllSubscriber.with(union));
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::trace!("quiet");
+            tracing::info!("loud");
+        });
+
+        assert_eq!(*info_recorder.seen.lock().unwrap(), vec!["loud"]);
+        assert_eq!(
+            *trace_recorder.seen.lock().unwrap(),
+            vec!["quiet", "loud"]
+        );
+    }
+
+    #[test]
+    fn max_level_hint_is_the_more_verbose_of_the_two() {
+        let union = Union::new(LevelFilter::INFO, LevelFilter::TRACE);
+        assert_eq!(
+            Layer::<AcceptAllSubscriber>::max_level_hint(&union),
+            Some(LevelFilter::TRACE)
+        );
+    }
+}
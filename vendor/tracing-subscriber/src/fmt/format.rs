@@ -4,13 +4,20 @@ use super::time::{self, FormatTime, SystemTime};
 #[cfg(feature = "tracing-log")]
 use tracing_log::NormalizeEvent;
 
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt::{self, Write};
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tracing_core::{
+    callsite::Identifier,
     field::{self, Field},
-    Event, Level,
+    Event, Level, Metadata,
 };
 
+use crate::sync::RwLock;
+
 #[cfg(feature = "ansi")]
 use ansi_term::{Colour, Style};
 
@@ -44,6 +51,79 @@ impl<N> FormatEvent<N>
     }
 }
 
+thread_local! {
+    static TARGET_OVERRIDE: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Sets a target override for the current thread, to be preferred by
+/// [`Format`] over an event's own [`Metadata::target`] the next time it
+/// formats an event on this thread.
+///
+/// Since `Metadata` is `'static`, an event's target can't be rewritten in
+/// place; this thread-local is the workaround. It's intended to be set from
+/// a [`Layer::on_event`] hook — such as [`TargetRewriteLayer`] — that runs
+/// *before* the `fmt` layer in the `Layered` stack, so the override is
+/// already in place by the time `fmt` formats the event. The override is
+/// consumed (read and cleared) the first time it's read, so it only ever
+/// applies to a single event.
+///
+/// [`Layer::on_event`]: crate::layer::Layer::on_event
+/// [`Metadata::target`]: tracing_core::Metadata::target
+pub fn set_target_override(target: impl Into<String>) {
+    TARGET_OVERRIDE.with(|cell| *cell.borrow_mut() = Some(target.into()));
+}
+
+fn take_target_override() -> Option<String> {
+    TARGET_OVERRIDE.with(|cell| cell.borrow_mut().take())
+}
+
+/// A [`Layer`] that rewrites an event's displayed target before it reaches
+/// the `fmt` layer, by consulting `rewrite` and, if it returns `Some`,
+/// setting a [`set_target_override`] for the `fmt` layer to prefer.
+///
+/// This must be composed *before* (i.e. closer to the subscriber than) the
+/// `fmt` layer for the override to take effect, since layers are visited
+/// outermost-first and the override has to be set before `fmt::Layer::on_event`
+/// reads it:
+///
+/// ```
+/// # use tracing_subscriber::{fmt, Layer};
+/// # use tracing_subscriber::fmt::format::TargetRewriteLayer;
+/// let subscriber = fmt::Subscriber::new()
+///     .with(TargetRewriteLayer::new(|meta| {
+///         Some(format!("tenant.acme.{}", meta.target()))
+///     }));
+/// ```
+///
+/// [`Layer`]: crate::layer::Layer
+pub struct TargetRewriteLayer<F> {
+    rewrite: F,
+}
+
+impl<F> TargetRewriteLayer<F>
+where
+    F: Fn(&Metadata<'_>) -> Option<String>,
+{
+    /// Returns a new `TargetRewriteLayer` that calls `rewrite` with an
+    /// event's metadata to compute its overridden target, or `None` to
+    /// leave the target untouched.
+    pub fn new(rewrite: F) -> Self {
+        Self { rewrite }
+    }
+}
+
+impl<S, F> crate::Layer<S> for TargetRewriteLayer<F>
+where
+    S: tracing_core::Subscriber,
+    F: Fn(&Metadata<'_>) -> Option<String> + 'static,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: crate::layer::Context<'_, S>) {
+        if let Some(target) = (self.rewrite)(event.metadata()) {
+            set_target_override(target);
+        }
+    }
+}
+
 /// Marker for `Format` that indicates that the compact log format should be used.
 ///
 /// The compact format only includes the fields from the most recently entered span.
@@ -69,6 +149,46 @@ pub struct Format<F = Full, T = SystemTime> {
     timer: T,
     ansi: bool,
     display_target: bool,
+    display_current_span: bool,
+    display_span_list: bool,
+    resource: &'static str,
+    lowercase_level: bool,
+    line_ending: LineEnding,
+    sanitize_ansi_in_fields: bool,
+    dedup_span_list: bool,
+    collapse_repeats: bool,
+    schema_version: &'static str,
+    error_span_location: bool,
+    no_span_context_targets: crate::filter::Targets,
+    thread_migration: bool,
+    callsite_counts: Option<Arc<RwLock<HashMap<Identifier, AtomicU64>>>>,
+    field_dedup: bool,
+}
+
+/// The character sequence written after each formatted event.
+///
+/// Defaults to [`LineEnding::Lf`]. Set via [`Format::with_line_ending`] when
+/// piping `fmt`'s output to a system that performs its own record framing
+/// and does not want the automatic trailing newline, or to emit `\r\n` on
+/// Windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Terminate each event with `\n`.
+    Lf,
+    /// Terminate each event with `\r\n`.
+    CrLf,
+    /// Don't terminate events with anything.
+    None,
+}
+
+impl LineEnding {
+    fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::None => "",
+        }
+    }
 }
 
 impl Default for Format<Full, SystemTime> {
@@ -78,6 +198,20 @@ impl Default for Format<Full, SystemTime> {
             timer: SystemTime,
             ansi: true,
             display_target: true,
+            display_current_span: true,
+            display_span_list: true,
+            resource: "",
+            lowercase_level: false,
+            line_ending: LineEnding::Lf,
+            sanitize_ansi_in_fields: false,
+            dedup_span_list: false,
+            collapse_repeats: false,
+            schema_version: "",
+            error_span_location: false,
+            no_span_context_targets: crate::filter::Targets::default(),
+            thread_migration: false,
+            callsite_counts: None,
+            field_dedup: false,
         }
     }
 }
@@ -92,6 +226,20 @@ impl<F, T> Format<F, T> {
             timer: self.timer,
             ansi: self.ansi,
             display_target: self.display_target,
+            display_current_span: self.display_current_span,
+            display_span_list: self.display_span_list,
+            resource: self.resource,
+            lowercase_level: self.lowercase_level,
+            line_ending: self.line_ending,
+            sanitize_ansi_in_fields: self.sanitize_ansi_in_fields,
+            dedup_span_list: self.dedup_span_list,
+            collapse_repeats: self.collapse_repeats,
+            schema_version: self.schema_version,
+            error_span_location: self.error_span_location,
+            no_span_context_targets: self.no_span_context_targets.clone(),
+            thread_migration: self.thread_migration,
+            callsite_counts: self.callsite_counts.clone(),
+            field_dedup: self.field_dedup,
         }
     }
 
@@ -102,6 +250,20 @@ impl<F, T> Format<F, T> {
             timer,
             ansi: self.ansi,
             display_target: self.display_target,
+            display_current_span: self.display_current_span,
+            display_span_list: self.display_span_list,
+            resource: self.resource,
+            lowercase_level: self.lowercase_level,
+            line_ending: self.line_ending,
+            sanitize_ansi_in_fields: self.sanitize_ansi_in_fields,
+            dedup_span_list: self.dedup_span_list,
+            collapse_repeats: self.collapse_repeats,
+            schema_version: self.schema_version,
+            error_span_location: self.error_span_location,
+            no_span_context_targets: self.no_span_context_targets.clone(),
+            thread_migration: self.thread_migration,
+            callsite_counts: self.callsite_counts.clone(),
+            field_dedup: self.field_dedup,
         }
     }
 
@@ -112,6 +274,20 @@ impl<F, T> Format<F, T> {
             timer: (),
             ansi: self.ansi,
             display_target: self.display_target,
+            display_current_span: self.display_current_span,
+            display_span_list: self.display_span_list,
+            resource: self.resource,
+            lowercase_level: self.lowercase_level,
+            line_ending: self.line_ending,
+            sanitize_ansi_in_fields: self.sanitize_ansi_in_fields,
+            dedup_span_list: self.dedup_span_list,
+            collapse_repeats: self.collapse_repeats,
+            schema_version: self.schema_version,
+            error_span_location: self.error_span_location,
+            no_span_context_targets: self.no_span_context_targets.clone(),
+            thread_migration: self.thread_migration,
+            callsite_counts: self.callsite_counts.clone(),
+            field_dedup: self.field_dedup,
         }
     }
 
@@ -127,6 +303,265 @@ impl<F, T> Format<F, T> {
             ..self
         }
     }
+
+    /// Sets whether or not the event's innermost (currently executing) span
+    /// is displayed.
+    ///
+    /// In [`Compact`] mode this controls both the leading span-name prefix
+    /// and the trailing fields recorded on that span; in [`Full`] mode,
+    /// individual spans are already covered by [`with_span_list`], so this
+    /// has no additional effect.
+    ///
+    /// Defaults to `true`.
+    ///
+    /// [`with_span_list`]: Format::with_span_list
+    pub fn with_current_span(self, display_current_span: bool) -> Format<F, T> {
+        Format {
+            display_current_span,
+            ..self
+        }
+    }
+
+    /// Sets whether or not the full list of entered spans is displayed
+    /// before each event, in [`Full`] mode.
+    ///
+    /// Defaults to `true`. Has no effect in [`Compact`] mode, which never
+    /// displays the full span list (see [`with_current_span`] instead).
+    ///
+    /// [`with_current_span`]: Format::with_current_span
+    pub fn with_span_list(self, display_span_list: bool) -> Format<F, T> {
+        Format {
+            display_span_list,
+            ..self
+        }
+    }
+
+    /// Sets whether consecutive duplicate entries in the rendered span list
+    /// are collapsed into one.
+    ///
+    /// A recursive function that opens a new span of the same name on every
+    /// call produces a chain of ancestors that all share that name, so by
+    /// default the span list renders that name once per level of recursion.
+    /// Enabling this collapses consecutive entries with the same name down
+    /// to a single rendering, which is usually what's wanted since the
+    /// repeats don't add information beyond "this happened more than once".
+    ///
+    /// Note that this compares span *names*, not span identities: two
+    /// directly-nested spans that happen to share a name are collapsed even
+    /// if they're otherwise distinct spans (e.g. different fields).
+    ///
+    /// Defaults to `false`.
+    pub fn with_dedup_span_list(self, dedup_span_list: bool) -> Format<F, T> {
+        Format {
+            dedup_span_list,
+            ..self
+        }
+    }
+
+    /// Sets whether runs of consecutive identically-named spans in the
+    /// rendered span list are collapsed into a single `name×N` entry.
+    ///
+    /// This addresses the same noisy-recursion case as
+    /// [`with_dedup_span_list`], but rather than silently dropping the
+    /// repeats, it keeps a visible count: `retry:retry:retry:db` becomes
+    /// `retry×3:db` instead of `retry:db`, so depth information survives
+    /// the collapsing. If both options are enabled, this one wins and
+    /// `with_dedup_span_list` has no further effect.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`with_dedup_span_list`]: Format::with_dedup_span_list
+    pub fn with_collapsed_repeats(self, collapse_repeats: bool) -> Format<F, T> {
+        Format {
+            collapse_repeats,
+            ..self
+        }
+    }
+
+    /// Sets whether a span field shadowed by an event field of the same
+    /// name is hidden from the rendered span context.
+    ///
+    /// An event that records a field with the same name as one already
+    /// recorded on an enclosing span — for example, an event doing
+    /// `error!(request_id = %new_id, ...)` inside a span that also carries
+    /// a `request_id` field — otherwise prints both, which reads as
+    /// contradictory rather than as "the event's value overrides the
+    /// span's for this line." Enabling this strips the span's copy of any
+    /// field name that the event also records, so only the event's
+    /// (nearest, most specific) value is shown.
+    ///
+    /// This operates on each span's already-rendered `name=value` text
+    /// rather than on structured field values, since that's what spans
+    /// retain; it splits on spaces outside of `"`-quoted values, so a
+    /// custom `Debug` implementation that emits an unquoted space-separated
+    /// `key=value` pair of its own could defeat the heuristic. This is a
+    /// rare enough shape that it isn't worth carrying full per-field
+    /// storage on every span just to rule it out.
+    ///
+    /// Defaults to `false`, for backward compatibility.
+    pub fn with_field_dedup(self, field_dedup: bool) -> Format<F, T> {
+        Format {
+            field_dedup,
+            ..self
+        }
+    }
+
+    /// Attaches a static resource descriptor — such as the build version or
+    /// git commit the running binary was built from — that is rendered on
+    /// every formatted line, immediately after the span context.
+    ///
+    /// `resource` should be pre-formatted as it is meant to appear in the
+    /// log line, e.g. `"version=1.2.3 git=abcd123"`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use tracing_subscriber::fmt::format::Format;
+    ///
+    /// let format = Format::default().with_resource("version=1.2.3 git=abcd123");
+    /// ```
+    pub fn with_resource(self, resource: &'static str) -> Format<F, T> {
+        Format { resource, ..self }
+    }
+
+    /// Stamps a `schema=<version>` field on every formatted line.
+    ///
+    /// A log-ingestion pipeline that evolves its field layout over time can
+    /// use this to tell which schema a given line conforms to and route it
+    /// to the matching parser, without having to infer the version from the
+    /// fields actually present.
+    ///
+    /// Defaults to unset (the empty string), which renders nothing extra.
+    pub fn with_schema_version(self, schema_version: &'static str) -> Format<F, T> {
+        Format {
+            schema_version,
+            ..self
+        }
+    }
+
+    /// Sets whether `ERROR`-level events display the source file and line
+    /// where the *current* span was created, i.e. the span the event was
+    /// recorded in.
+    ///
+    /// This is meant to help with error reports: an error logged deep inside
+    /// some inner span often needs to be traced back to the call site that
+    /// opened that span, not just the location of the `error!` call itself.
+    /// When there is no current span, or its location is unknown, nothing
+    /// extra is printed.
+    ///
+    /// Defaults to `false`.
+    pub fn with_error_span_location(self, error_span_location: bool) -> Format<F, T> {
+        Format {
+            error_span_location,
+            ..self
+        }
+    }
+
+    /// Renders events whose target starts with one of the given prefixes
+    /// without the span context (current span / span list) prefix, even
+    /// when it would otherwise be shown.
+    ///
+    /// This is useful for quieting a noisy dependency's events without
+    /// losing span context for your own: events from `noisy_lib` are logged
+    /// without the enclosing span path, while everything else keeps it.
+    ///
+    /// Matching is a plain prefix match on the target string, the same
+    /// semantics as [`Targets`](crate::filter::Targets).
+    ///
+    /// Defaults to excluding no targets, i.e. showing span context for
+    /// everything.
+    pub fn without_span_context_for<I>(self, targets: I) -> Format<F, T>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        Format {
+            no_span_context_targets: crate::filter::Targets::new(targets),
+            ..self
+        }
+    }
+
+    /// Sets whether events show a `migrated=true` indicator when the
+    /// current span's creating thread differs from the thread the event is
+    /// being recorded on.
+    ///
+    /// Work-stealing executors move a task's continuation to whichever
+    /// worker thread happens to be free, so a span entered on one thread may
+    /// have events recorded against it from a different thread. This
+    /// surfaces that thread-hopping directly in logs, which otherwise has no
+    /// visible trace. Nothing is printed when there is no current span, or
+    /// when it's still on the thread that created it.
+    ///
+    /// Defaults to `false`.
+    pub fn with_thread_migration(self, thread_migration: bool) -> Format<F, T> {
+        Format {
+            thread_migration,
+            ..self
+        }
+    }
+
+    /// Sets whether events are suffixed with `[#N]`, where `N` is the number
+    /// of times this event's callsite has fired so far (including this one).
+    ///
+    /// This is a lightweight "how hot is this log line" aid for spotting a
+    /// noisy call site at a glance, without standing up a full metrics
+    /// layer. The counter is keyed by callsite identity, not by the
+    /// formatted message, so two events logged from the same `tracing::info!`
+    /// invocation share one counter even if their field values differ; two
+    /// textually identical messages logged from different call sites do not.
+    ///
+    /// Defaults to `false`, since the counter map adds a lock acquisition to
+    /// every event even when its contents are never read.
+    pub fn with_callsite_counts(self, enabled: bool) -> Format<F, T> {
+        Format {
+            callsite_counts: if enabled {
+                Some(Arc::new(RwLock::new(HashMap::new())))
+            } else {
+                None
+            },
+            ..self
+        }
+    }
+
+    /// Renders the level as lowercase (`info`, `warn`, ...) instead of the
+    /// default uppercase (`INFO`, `WARN`, ...), matching the style used by
+    /// syslog and many structured logging tools.
+    pub fn with_lowercase_level(self, lowercase: bool) -> Format<F, T> {
+        Format {
+            lowercase_level: lowercase,
+            ..self
+        }
+    }
+
+    /// Sets the character sequence written after each formatted event.
+    ///
+    /// Defaults to [`LineEnding::Lf`]. Pass [`LineEnding::None`] when piping
+    /// to a system that performs its own record framing, or
+    /// [`LineEnding::CrLf`] for conventional Windows line endings.
+    pub fn with_line_ending(self, line_ending: LineEnding) -> Format<F, T> {
+        Format {
+            line_ending,
+            ..self
+        }
+    }
+
+    /// Strips ANSI escape sequences from rendered field values before
+    /// writing them.
+    ///
+    /// Field values that originate from subprocess output or other
+    /// untrusted sources may already contain ANSI escape sequences. Left
+    /// alone, these corrupt plain-text log files and let an attacker forge
+    /// fake-looking log lines or terminal control sequences (a form of log
+    /// injection). Enabling this drops any ANSI escape sequence found in a
+    /// field's rendered text, in addition to the control-character escaping
+    /// the `message` field already receives.
+    ///
+    /// Defaults to `false`.
+    pub fn sanitize_ansi_in_fields(self, sanitize: bool) -> Format<F, T> {
+        Format {
+            sanitize_ansi_in_fields: sanitize,
+            ..self
+        }
+    }
 }
 
 impl<N, T> FormatEvent<N> for Format<Full, T>
@@ -146,23 +581,43 @@ where
         let meta = normalized_meta.as_ref().unwrap_or_else(|| event.metadata());
         #[cfg(not(feature = "tracing-log"))]
         let meta = event.metadata();
+        let target_override = take_target_override();
+        let target = target_override.as_deref().unwrap_or_else(|| meta.target());
+        let show_span_context = !self.no_span_context_targets.enabled(target);
+        let dedup_fields: Vec<&str> = if self.field_dedup {
+            meta.fields().iter().map(|f| f.name()).collect()
+        } else {
+            Vec::new()
+        };
         time::write(&self.timer, writer)?;
         write!(
             writer,
-            "{} {}{}: ",
-            FmtLevel::new(meta.level(), self.ansi),
-            FullCtx::new(&ctx, self.ansi),
-            if self.display_target {
-                meta.target()
-            } else {
-                ""
-            }
+            "{} {}{}{}{}{}{}: ",
+            FmtLevel::new(meta.level(), self.ansi, self.lowercase_level),
+            ResourceFields::new(self.resource),
+            SchemaField::new(self.schema_version),
+            ErrorSpanLocation::new(&ctx, self.error_span_location, meta.level()),
+            ThreadMigration::new(&ctx, self.thread_migration),
+            FullCtx::new(
+                &ctx,
+                self.ansi,
+                self.display_span_list && show_span_context,
+                self.dedup_span_list,
+                self.collapse_repeats,
+                &dedup_fields,
+            ),
+            if self.display_target { target } else { "" }
         )?;
-        {
+        if self.sanitize_ansi_in_fields {
+            let mut sanitizer = AnsiSanitizer::new(writer);
+            let mut recorder = ctx.new_visitor(&mut sanitizer, true);
+            event.record(&mut recorder);
+        } else {
             let mut recorder = ctx.new_visitor(writer, true);
             event.record(&mut recorder);
         }
-        writeln!(writer)
+        write!(writer, "{}", CallsiteCount::new(&self.callsite_counts, meta.callsite()))?;
+        write!(writer, "{}", self.line_ending.as_str())
     }
 }
 
@@ -183,38 +638,89 @@ where
         let meta = normalized_meta.as_ref().unwrap_or_else(|| event.metadata());
         #[cfg(not(feature = "tracing-log"))]
         let meta = event.metadata();
+        let target_override = take_target_override();
+        let target = target_override.as_deref().unwrap_or_else(|| meta.target());
+        let show_span_context = !self.no_span_context_targets.enabled(target);
         time::write(&self.timer, writer)?;
         write!(
             writer,
-            "{} {}{}: ",
-            FmtLevel::new(meta.level(), self.ansi),
-            FmtCtx::new(&ctx, self.ansi),
-            if self.display_target {
-                meta.target()
-            } else {
-                ""
-            }
+            "{} {}{}{}{}{}{}: ",
+            FmtLevel::new(meta.level(), self.ansi, self.lowercase_level),
+            ResourceFields::new(self.resource),
+            SchemaField::new(self.schema_version),
+            ErrorSpanLocation::new(&ctx, self.error_span_location, meta.level()),
+            ThreadMigration::new(&ctx, self.thread_migration),
+            FmtCtx::new(&ctx, self.ansi, self.display_current_span && show_span_context),
+            if self.display_target { target } else { "" }
         )?;
-        {
+        if self.sanitize_ansi_in_fields {
+            let mut sanitizer = AnsiSanitizer::new(writer);
+            let mut recorder = ctx.new_visitor(&mut sanitizer, true);
+            event.record(&mut recorder);
+        } else {
             let mut recorder = ctx.new_visitor(writer, true);
             event.record(&mut recorder);
         }
-        ctx.with_current(|(_, span)| write!(writer, " {}", span.fields()))
+        if self.display_current_span && show_span_context {
+            let dedup_fields: Vec<&str> = if self.field_dedup {
+                meta.fields().iter().map(|f| f.name()).collect()
+            } else {
+                Vec::new()
+            };
+            ctx.with_current(|(_, span)| {
+                let fields = filter_rendered_fields(span.fields(), &dedup_fields);
+                if fields.is_empty() {
+                    Ok(())
+                } else {
+                    write!(writer, " {}", fields)
+                }
+            })
             .unwrap_or(Ok(()))?;
-        writeln!(writer)
+        }
+        write!(writer, "{}", CallsiteCount::new(&self.callsite_counts, meta.callsite()))?;
+        write!(writer, "{}", self.line_ending.as_str())
     }
 }
 
 /// The default implementation of `NewVisitor` that records fields using the
 /// default format.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NewRecorder {
-    _p: (),
+    separator: &'static str,
+    keep_log_fields: bool,
+    message_field: Option<&'static str>,
 }
 
 impl NewRecorder {
     pub(crate) fn new() -> Self {
-        Self { _p: () }
+        Self {
+            separator: " ",
+            keep_log_fields: false,
+            message_field: None,
+        }
+    }
+
+    /// Returns a `NewRecorder` that joins rendered fields with `separator`
+    /// instead of the default single space.
+    pub(crate) fn with_separator(mut self, separator: &'static str) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Returns a `NewRecorder` that, when `keep` is `true`, renders the
+    /// `log.target`/`log.module_path`/`log.file`/`log.line` fields a `log`
+    /// record is normalized into instead of consuming them silently.
+    pub(crate) fn with_keep_log_fields(mut self, keep: bool) -> Self {
+        self.keep_log_fields = keep;
+        self
+    }
+
+    /// Returns a `NewRecorder` that promotes `field` to render in the
+    /// `message` position when an event has no literal `message` field of
+    /// its own.
+    pub(crate) fn with_message_field(mut self, field: &'static str) -> Self {
+        self.message_field = Some(field);
+        self
     }
 }
 
@@ -222,18 +728,51 @@ impl NewRecorder {
 pub struct Recorder<'a> {
     writer: &'a mut dyn Write,
     is_empty: bool,
+    separator: &'a str,
+    keep_log_fields: bool,
+    message_field: Option<&'static str>,
+    has_message: bool,
 }
 
 impl<'a> Recorder<'a> {
-    pub(crate) fn new(writer: &'a mut dyn Write, is_empty: bool) -> Self {
-        Self { writer, is_empty }
+    pub(crate) fn new(
+        writer: &'a mut dyn Write,
+        is_empty: bool,
+        separator: &'a str,
+        keep_log_fields: bool,
+        message_field: Option<&'static str>,
+    ) -> Self {
+        Self {
+            writer,
+            is_empty,
+            separator,
+            keep_log_fields,
+            message_field,
+            has_message: false,
+        }
     }
 
     fn maybe_pad(&mut self) {
         if self.is_empty {
             self.is_empty = false;
         } else {
-            let _ = write!(self.writer, " ");
+            let _ = write!(self.writer, "{}", self.separator);
+        }
+    }
+
+    /// Returns `true` if `field` should be rendered in the `message`
+    /// position (unquoted, with no `name=` prefix): either it's the literal
+    /// `message` field, or it's the configured `message_field` stand-in and
+    /// no literal `message` has been seen yet on this event. A literal
+    /// `message` field, were one present, is always recorded first by the
+    /// `tracing` macros, so by the time a later field is visited this
+    /// already reflects whether one exists.
+    fn is_message(&mut self, field: &Field) -> bool {
+        if field.name() == "message" {
+            self.has_message = true;
+            true
+        } else {
+            !self.has_message && self.message_field == Some(field.name())
         }
     }
 }
@@ -243,14 +782,20 @@ impl<'a> super::NewVisitor<'a> for NewRecorder {
 
     #[inline]
     fn make(&self, writer: &'a mut dyn Write, is_empty: bool) -> Self::Visitor {
-        Recorder::new(writer, is_empty)
+        Recorder::new(
+            writer,
+            is_empty,
+            self.separator,
+            self.keep_log_fields,
+            self.message_field,
+        )
     }
 }
 
 impl<'a> field::Visit for Recorder<'a> {
     fn record_str(&mut self, field: &Field, value: &str) {
-        if field.name() == "message" {
-            self.record_debug(field, &format_args!("{}", value))
+        if self.is_message(field) {
+            self.record_debug(field, &EscapeControlChars(value))
         } else {
             self.record_debug(field, &value)
         }
@@ -269,136 +814,494 @@ impl<'a> field::Visit for Recorder<'a> {
 
     fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
         self.maybe_pad();
-        let _ = match field.name() {
-            "message" => write!(self.writer, "{:?}", value),
-            // Skip fields that are actually log metadata that have already been handled
-            #[cfg(feature = "tracing-log")]
-            name if name.starts_with("log.") => Ok(()),
-            name if name.starts_with("r#") => write!(self.writer, "{}={:?}", &name[2..], value),
-            name => write!(self.writer, "{}={:?}", name, value),
+        let is_message = self.is_message(field);
+        let _ = if is_message {
+            write!(
+                self.writer,
+                "{}",
+                EscapeControlChars(&format!("{:?}", value))
+            )
+        } else {
+            match field.name() {
+                // Skip fields that are actually log metadata that have already been handled,
+                // unless the caller asked to see them via `with_log_internal_fields`.
+                #[cfg(feature = "tracing-log")]
+                name if name.starts_with("log.") && !self.keep_log_fields => Ok(()),
+                name if name.starts_with("r#") => write!(self.writer, "{}={:?}", &name[2..], value),
+                name => write!(self.writer, "{}={:?}", name, value),
+            }
         };
     }
 }
 
-// This has to be a manual impl, as `&mut dyn Writer` doesn't implement `Debug`.
-impl<'a> fmt::Debug for Recorder<'a> {
+/// Displays a `message` field's value as-is, except that ASCII control
+/// characters are rendered as their common escape sequence (`\n`, `\r`,
+/// `\t`) or as a `\xHH` escape.
+///
+/// Unlike other fields, `message` is written without `Debug`-quoting, so
+/// that it reads as a natural sentence rather than a quoted string. Without
+/// this, a message containing embedded control characters (for example, one
+/// built from unsanitized user input) could forge additional, fake-looking
+/// log lines in the output. `Debug` is implemented to forward to `Display`,
+/// matching how `record_debug` renders the `message` field.
+struct EscapeControlChars<'a>(&'a str);
+
+impl<'a> fmt::Display for EscapeControlChars<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Recorder")
-            .field("writer", &format_args!("<dyn fmt::Write>"))
-            .field("is_empty", &self.is_empty)
-            .finish()
+        for c in self.0.chars() {
+            match c {
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if c.is_control() => write!(f, "\\x{:02x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
     }
 }
 
-struct FmtCtx<'a, N> {
-    ctx: &'a span::Context<'a, N>,
-    ansi: bool,
+impl<'a> fmt::Debug for EscapeControlChars<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
 }
 
-impl<'a, N: 'a> FmtCtx<'a, N> {
-    pub(crate) fn new(ctx: &'a span::Context<'a, N>, ansi: bool) -> Self {
-        Self { ctx, ansi }
-    }
+/// A `fmt::Write` adapter that drops ANSI escape sequences from everything
+/// written through it.
+///
+/// Used by [`Format::sanitize_ansi_in_fields`] to sanitize field values that
+/// may already contain ANSI escape codes before they reach the log. Any
+/// `ESC` byte starts a dropped sequence; if it is followed by `[`, the whole
+/// CSI sequence (through its final byte in `0x40..=0x7e`) is dropped,
+/// otherwise only the two-character escape is dropped.
+///
+/// [`Format::sanitize_ansi_in_fields`]: struct.Format.html#method.sanitize_ansi_in_fields
+struct AnsiSanitizer<'a> {
+    inner: &'a mut dyn fmt::Write,
+    state: AnsiSanitizerState,
 }
 
-#[cfg(feature = "ansi")]
-impl<'a, N> fmt::Display for FmtCtx<'a, N>
-where
-    N: super::NewVisitor<'a>,
-{
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut seen = false;
-        self.ctx.visit_spans(|_, span| {
-            if seen {
-                f.pad(":")?;
-            }
-            seen = true;
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AnsiSanitizerState {
+    Normal,
+    SawEscape,
+    InCsiSequence,
+}
 
-            if self.ansi {
-                write!(f, "{}", Style::new().bold().paint(span.name()))
-            } else {
-                write!(f, "{}", span.name())
-            }
-        })?;
-        if seen {
-            f.pad(" ")?;
+impl<'a> AnsiSanitizer<'a> {
+    fn new(inner: &'a mut dyn fmt::Write) -> Self {
+        Self {
+            inner,
+            state: AnsiSanitizerState::Normal,
         }
-        Ok(())
     }
 }
 
-#[cfg(not(feature = "ansi"))]
-impl<'a, N> fmt::Display for FmtCtx<'a, N> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut seen = false;
-        self.ctx.visit_spans(|_, span| {
-            if seen {
-                f.pad(":")?;
+impl<'a> fmt::Write for AnsiSanitizer<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match self.state {
+                AnsiSanitizerState::Normal if c == '\u{1b}' => {
+                    self.state = AnsiSanitizerState::SawEscape;
+                }
+                AnsiSanitizerState::Normal => self.inner.write_char(c)?,
+                AnsiSanitizerState::SawEscape => {
+                    self.state = if c == '[' {
+                        AnsiSanitizerState::InCsiSequence
+                    } else {
+                        AnsiSanitizerState::Normal
+                    };
+                }
+                AnsiSanitizerState::InCsiSequence => {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        self.state = AnsiSanitizerState::Normal;
+                    }
+                }
             }
-            seen = true;
-            write!(f, "{}", span.name())
-        })?;
-        if seen {
-            f.pad(" ")?;
         }
         Ok(())
     }
 }
 
-struct FullCtx<'a, N> {
-    ctx: &'a span::Context<'a, N>,
-    ansi: bool,
+/// Formats a `Duration` as a short, human-readable string using the largest
+/// unit (seconds, milliseconds, microseconds, or nanoseconds) that keeps the
+/// magnitude between 1 and 1000, e.g. `2.50s`, `1.50ms`, `1.50µs`, `800ns`.
+///
+/// This is used by [`Format::with_humanize_durations`] to render span close
+/// durations, as an alternative to `Duration`'s `Debug` output.
+///
+/// [`Format::with_humanize_durations`]: struct.Format.html#method.with_humanize_durations
+pub(crate) fn format_duration_human(duration: std::time::Duration) -> String {
+    let nanos = duration.as_nanos();
+    if nanos >= 1_000_000_000 {
+        let secs = duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1_000_000_000.0;
+        format!("{:.2}s", secs)
+    } else if nanos >= 1_000_000 {
+        format!("{:.2}ms", nanos as f64 / 1_000_000.0)
+    } else if nanos >= 1_000 {
+        format!("{:.2}µs", nanos as f64 / 1_000.0)
+    } else {
+        format!("{}ns", nanos)
+    }
 }
 
-impl<'a, N: 'a> FullCtx<'a, N> {
-    pub(crate) fn new(ctx: &'a span::Context<'a, N>, ansi: bool) -> Self {
-        Self { ctx, ansi }
-    }
+/// A field value that has been recorded along with enough information to
+/// recover its original type.
+///
+/// Most `FormatEvent` implementations (the default text format included)
+/// only care about the `Debug`/`Display` representation of a field, and
+/// stringify every value as they record it. Structured formatters, such as
+/// JSON or logfmt, want to preserve the distinction between an integer, a
+/// float, a boolean, and a string so that the emitted token is unquoted or
+/// quoted appropriately. `TypedValue` is the shared representation used by
+/// those formatters.
+#[derive(Debug, Clone)]
+pub enum TypedValue {
+    /// A signed 64-bit integer, recorded via `record_i64`.
+    I64(i64),
+    /// An unsigned 64-bit integer, recorded via `record_u64`.
+    U64(u64),
+    /// A double-precision float, recorded via `record_f64`.
+    F64(f64),
+    /// A boolean, recorded via `record_bool`.
+    Bool(bool),
+    /// A string, recorded via `record_str`.
+    Str(String),
+    /// Any other value, recorded via `record_debug` and stringified with its
+    /// `Debug` representation.
+    Debug(String),
 }
 
-#[cfg(feature = "ansi")]
-impl<'a, N> fmt::Display for FullCtx<'a, N>
-where
-    N: super::NewVisitor<'a>,
-{
+impl fmt::Display for TypedValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut seen = false;
-        let style = if self.ansi {
-            Style::new().bold()
-        } else {
-            Style::new()
-        };
-        self.ctx.visit_spans(|_, span| {
-            write!(f, "{}", style.paint(span.name()))?;
-
-            seen = true;
-
-            let fields = span.fields();
-            if !fields.is_empty() {
-                write!(f, "{}{}{}", style.paint("{"), fields, style.paint("}"))?;
-            }
-            ":".fmt(f)
-        })?;
-        if seen {
-            f.pad(" ")?;
+        match self {
+            TypedValue::I64(v) => write!(f, "{}", v),
+            TypedValue::U64(v) => write!(f, "{}", v),
+            TypedValue::F64(v) => write!(f, "{}", v),
+            TypedValue::Bool(v) => write!(f, "{}", v),
+            TypedValue::Str(v) => write!(f, "{}", v),
+            TypedValue::Debug(v) => write!(f, "{}", v),
         }
-        Ok(())
     }
 }
 
-#[cfg(not(feature = "ansi"))]
-impl<'a, N> fmt::Display for FullCtx<'a, N> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+/// A `Visit` implementation that records each field's value as a
+/// [`TypedValue`], preserving its original type rather than immediately
+/// stringifying it.
+///
+/// This is intended to be shared by structured (e.g. JSON, logfmt)
+/// formatters, which need to decide per-value whether to emit a quoted
+/// string or a bare literal.
+///
+/// [`TypedValue`]: enum.TypedValue.html
+#[derive(Debug, Default)]
+pub struct TypedFields {
+    fields: Vec<(&'static str, TypedValue)>,
+}
+
+impl TypedFields {
+    /// Returns a new, empty `TypedFields` visitor.
+    pub fn new() -> Self {
+        Self { fields: Vec::new() }
+    }
+
+    /// Consumes this visitor, returning the fields it recorded in the order
+    /// they were visited.
+    pub fn into_fields(self) -> Vec<(&'static str, TypedValue)> {
+        self.fields
+    }
+}
+
+impl field::Visit for TypedFields {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields.push((field.name(), TypedValue::I64(value)));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields.push((field.name(), TypedValue::U64(value)));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.fields.push((field.name(), TypedValue::F64(value)));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields.push((field.name(), TypedValue::Bool(value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields
+            .push((field.name(), TypedValue::Str(value.to_owned())));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.fields
+            .push((field.name(), TypedValue::Debug(format!("{:?}", value))));
+    }
+}
+
+/// Formats events as a hierarchical tree reflecting the current span
+/// nesting, one line per ancestor span followed by a line for the event
+/// itself.
+///
+/// By default, ancestry is drawn using Unicode box-drawing characters
+/// (`│  ` for indentation, `└─ ` for the branch marker immediately before a
+/// name). Call [`with_ascii`] to switch to ASCII-safe guides (`|  ` and
+/// `+- `) for viewers that mangle non-ASCII output, or [`with_indent`] to set
+/// a custom indentation string (e.g. plain spaces) while keeping the current
+/// branch marker.
+///
+/// Construct one with [`pretty_tree`].
+///
+/// # Examples
+///
+/// For an event recorded two levels deep (inside a `request` span, which is
+/// inside a `server` span), the default output looks like:
+///
+/// ```text
+/// server
+/// └─ request
+/// │  └─ the event's message
+/// ```
+///
+/// [`with_ascii`]: #method.with_ascii
+/// [`with_indent`]: #method.with_indent
+/// [`pretty_tree`]: fn.pretty_tree.html
+#[derive(Debug, Clone)]
+pub struct PrettyTree {
+    indent: &'static str,
+    branch: &'static str,
+}
+
+impl Default for PrettyTree {
+    fn default() -> Self {
+        Self {
+            indent: "│  ",
+            branch: "└─ ",
+        }
+    }
+}
+
+impl PrettyTree {
+    /// Sets the string repeated for each ancestor level of indentation.
+    ///
+    /// This overrides the indentation half of whatever glyph set [`with_ascii`]
+    /// selected; call `with_ascii` first if you want its branch marker kept
+    /// alongside a custom indent.
+    ///
+    /// [`with_ascii`]: #method.with_ascii
+    pub fn with_indent(self, indent: &'static str) -> Self {
+        Self { indent, ..self }
+    }
+
+    /// Switches to ASCII-only guides (`|  ` for indentation, `+- ` for the
+    /// branch marker) instead of the default Unicode box-drawing characters.
+    ///
+    /// This resets both the indent and branch marker to the chosen glyph
+    /// set; call it before [`with_indent`] if you also want a custom indent.
+    ///
+    /// [`with_indent`]: #method.with_indent
+    pub fn with_ascii(self, ascii: bool) -> Self {
+        if ascii {
+            Self {
+                indent: "|  ",
+                branch: "+- ",
+            }
+        } else {
+            Self::default()
+        }
+    }
+
+    fn prefix(&self, depth: usize) -> String {
+        if depth == 0 {
+            String::new()
+        } else {
+            format!("{}{}", self.indent.repeat(depth - 1), self.branch)
+        }
+    }
+}
+
+/// Returns a new [`PrettyTree`] formatter with the default Unicode guides.
+pub fn pretty_tree() -> PrettyTree {
+    PrettyTree::default()
+}
+
+impl<N> FormatEvent<N> for PrettyTree
+where
+    N: for<'a> super::NewVisitor<'a>,
+{
+    fn format_event(
+        &self,
+        ctx: &span::Context<'_, N>,
+        writer: &mut dyn fmt::Write,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut depth = 0;
+        ctx.visit_spans(|_, span| {
+            writeln!(writer, "{}{}", self.prefix(depth), span.name())?;
+            depth += 1;
+            Ok(())
+        })?;
+
+        let mut fields = TypedFields::new();
+        event.record(&mut fields);
+        let message = fields
+            .into_fields()
+            .into_iter()
+            .find(|(name, _)| *name == "message")
+            .map(|(_, value)| value.to_string())
+            .unwrap_or_default();
+
+        writeln!(writer, "{}{}", self.prefix(depth), message)
+    }
+}
+
+#[cfg(test)]
+mod pretty_tree_tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::dispatcher::Dispatch;
+
+    struct BufWriter(Arc<Mutex<Vec<u8>>>);
+    impl std::io::Write for BufWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn run(formatter: PrettyTree) -> String {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .on_event(formatter)
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let server = tracing::info_span!("server");
+            let _server = server.enter();
+            let request = tracing::info_span!("request");
+            let _request = request.enter();
+            tracing::info!("handled");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        out
+    }
+
+    #[test]
+    fn unicode_guides_are_the_default() {
+        let out = run(pretty_tree());
+        assert_eq!(out, "server\n└─ request\n│  └─ handled\n");
+    }
+
+    #[test]
+    fn ascii_guides_match_the_configured_glyphs() {
+        let out = run(pretty_tree().with_ascii(true));
+        assert_eq!(out, "server\n+- request\n|  +- handled\n");
+    }
+
+    #[test]
+    fn with_indent_overrides_just_the_indentation() {
+        let out = run(pretty_tree().with_ascii(true).with_indent("  "));
+        assert_eq!(out, "server\n+- request\n  +- handled\n");
+    }
+}
+
+#[cfg(test)]
+mod target_rewrite_tests {
+    use super::*;
+    use crate::layer::SubscriberExt;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::dispatcher::Dispatch;
+
+    struct BufWriter(Arc<Mutex<Vec<u8>>>);
+    impl std::io::Write for BufWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn rewritten_target_appears_in_output() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let fmt_subscriber = crate::fmt::Subscriber::builder()
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .without_time()
+            .finish();
+        let subscriber =
+            fmt_subscriber.with(TargetRewriteLayer::new(|meta| {
+                Some(format!("tenant.acme.{}", meta.target()))
+            }));
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("hello");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(
+            out.contains("tenant.acme."),
+            "expected rewritten target in output, got: {}",
+            out
+        );
+    }
+}
+
+// This has to be a manual impl, as `&mut dyn Writer` doesn't implement `Debug`.
+impl<'a> fmt::Debug for Recorder<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("writer", &format_args!("<dyn fmt::Write>"))
+            .field("is_empty", &self.is_empty)
+            .finish()
+    }
+}
+
+struct FmtCtx<'a, N> {
+    ctx: &'a span::Context<'a, N>,
+    ansi: bool,
+    enabled: bool,
+}
+
+impl<'a, N: 'a> FmtCtx<'a, N> {
+    pub(crate) fn new(ctx: &'a span::Context<'a, N>, ansi: bool, enabled: bool) -> Self {
+        Self { ctx, ansi, enabled }
+    }
+}
+
+#[cfg(feature = "ansi")]
+impl<'a, N> fmt::Display for FmtCtx<'a, N>
+where
+    N: super::NewVisitor<'a>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.enabled {
+            return Ok(());
+        }
         let mut seen = false;
         self.ctx.visit_spans(|_, span| {
-            write!(f, "{}", span.name())?;
+            if seen {
+                f.pad(":")?;
+            }
             seen = true;
 
-            let fields = span.fields();
-            if !fields.is_empty() {
-                write!(f, "{{{}}}", fields)?;
+            if self.ansi {
+                write!(f, "{}", Style::new().bold().paint(span.name()))
+            } else {
+                write!(f, "{}", span.name())
             }
-            ":".fmt(f)
         })?;
         if seen {
             f.pad(" ")?;
@@ -407,49 +1310,1428 @@ impl<'a, N> fmt::Display for FullCtx<'a, N> {
     }
 }
 
-struct FmtLevel<'a> {
-    level: &'a Level,
-    ansi: bool,
+#[cfg(not(feature = "ansi"))]
+impl<'a, N> fmt::Display for FmtCtx<'a, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.enabled {
+            return Ok(());
+        }
+        let mut seen = false;
+        self.ctx.visit_spans(|_, span| {
+            if seen {
+                f.pad(":")?;
+            }
+            seen = true;
+            write!(f, "{}", span.name())
+        })?;
+        if seen {
+            f.pad(" ")?;
+        }
+        Ok(())
+    }
 }
 
-impl<'a> FmtLevel<'a> {
-    pub(crate) fn new(level: &'a Level, ansi: bool) -> Self {
-        Self { level, ansi }
+/// Renders the resource fields set by [`Format::with_resource`], with a
+/// trailing space when non-empty so it composes cleanly with whatever
+/// follows it on the line.
+struct ResourceFields<'a> {
+    resource: &'a str,
+}
+
+impl<'a> ResourceFields<'a> {
+    pub(crate) fn new(resource: &'a str) -> Self {
+        Self { resource }
     }
 }
 
-#[cfg(not(feature = "ansi"))]
-impl<'a> fmt::Display for FmtLevel<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self.level {
-            Level::TRACE => f.pad("TRACE"),
-            Level::DEBUG => f.pad("DEBUG"),
-            Level::INFO => f.pad("INFO"),
-            Level::WARN => f.pad("WARN"),
-            Level::ERROR => f.pad("ERROR"),
+impl<'a> fmt::Display for ResourceFields<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.resource.is_empty() {
+            Ok(())
+        } else {
+            write!(f, "{} ", self.resource)
         }
     }
 }
 
-#[cfg(feature = "ansi")]
-impl<'a> fmt::Display for FmtLevel<'a> {
+struct SchemaField<'a> {
+    schema_version: &'a str,
+}
+
+impl<'a> SchemaField<'a> {
+    pub(crate) fn new(schema_version: &'a str) -> Self {
+        Self { schema_version }
+    }
+}
+
+impl<'a> fmt::Display for SchemaField<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.schema_version.is_empty() {
+            Ok(())
+        } else {
+            write!(f, "schema={} ", self.schema_version)
+        }
+    }
+}
+
+/// Resolves, at the time an event is formatted, the file and line where the
+/// event's current span was created — so that the rendered line can be
+/// traced back to the call site that opened the span, not just the call
+/// site of the `error!` macro itself.
+struct ErrorSpanLocation {
+    location: Option<(&'static str, u32)>,
+}
+
+impl ErrorSpanLocation {
+    fn new<N>(ctx: &span::Context<'_, N>, enabled: bool, level: &Level) -> Self {
+        let location = if enabled && *level == Level::ERROR {
+            ctx.with_current(|(_, span)| match (span.file(), span.line()) {
+                (Some(file), Some(line)) => Some((file, line)),
+                _ => None,
+            })
+            .flatten()
+        } else {
+            None
+        };
+        Self { location }
+    }
+}
+
+impl fmt::Display for ErrorSpanLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((file, line)) => write!(f, "span opened at {}:{} ", file, line),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Resolves, at the time an event is formatted, whether the current span's
+/// creating thread differs from the thread the event is being recorded on —
+/// so a `migrated=true` indicator can be rendered to surface that thread
+/// hopping directly in logs.
+struct ThreadMigration {
+    migrated: bool,
+}
+
+impl ThreadMigration {
+    fn new<N>(ctx: &span::Context<'_, N>, enabled: bool) -> Self {
+        let migrated = enabled
+            && ctx
+                .with_current(|(_, span)| span.created_thread() != std::thread::current().id())
+                .unwrap_or(false);
+        Self { migrated }
+    }
+}
+
+impl fmt::Display for ThreadMigration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.migrated {
+            write!(f, "migrated=true ")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Resolves, at the time an event is formatted, how many times this event's
+/// callsite has fired so far (including this one) — so a ` [#N]` suffix can
+/// be rendered at the end of the line to surface hot call sites at a glance.
+struct CallsiteCount(Option<u64>);
+
+impl CallsiteCount {
+    fn new(
+        counts: &Option<Arc<RwLock<HashMap<Identifier, AtomicU64>>>>,
+        callsite: Identifier,
+    ) -> Self {
+        let counts = match counts {
+            Some(counts) => counts,
+            None => return Self(None),
+        };
+
+        if let Ok(counts) = counts.read() {
+            if let Some(count) = counts.get(&callsite) {
+                return Self(Some(count.fetch_add(1, Ordering::Relaxed) + 1));
+            }
+        }
+
+        // First time this callsite has fired: take the write lock to insert
+        // its counter. A second thread racing to insert the same callsite
+        // for the first time may each record `1`; both counts are accurate
+        // for their own event, just not globally ordered relative to each
+        // other, which is fine for an approximate "how hot is this" signal.
+        let mut counts = match counts.write() {
+            Ok(counts) => counts,
+            Err(_) => return Self(None),
+        };
+        let count = counts
+            .entry(callsite)
+            .or_insert_with(|| AtomicU64::new(0))
+            .fetch_add(1, Ordering::Relaxed)
+            + 1;
+        Self(Some(count))
+    }
+}
+
+impl fmt::Display for CallsiteCount {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.ansi {
-            match *self.level {
-                Level::TRACE => write!(f, "{}", Colour::Purple.paint("TRACE")),
-                Level::DEBUG => write!(f, "{}", Colour::Blue.paint("DEBUG")),
-                Level::INFO => write!(f, "{}", Colour::Green.paint(" INFO")),
-                Level::WARN => write!(f, "{}", Colour::Yellow.paint(" WARN")),
-                Level::ERROR => write!(f, "{}", Colour::Red.paint("ERROR")),
+        match self.0 {
+            Some(count) => write!(f, " [#{}]", count),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns `fields` with any top-level `name=value` entry removed whose
+/// `name` appears in `exclude`, for [`Format::with_field_dedup`].
+///
+/// `fields` is already-rendered text (see [`super::span::Span::fields`]),
+/// not structured data, so entries are located by splitting on spaces that
+/// are not inside a `"`-quoted value — matching how [`Recorder`] renders
+/// field values, where a string value's `Debug` representation is the only
+/// thing that can contain an embedded space.
+fn filter_rendered_fields(fields: &str, exclude: &[&str]) -> String {
+    if exclude.is_empty() || fields.is_empty() {
+        return fields.to_owned();
+    }
+
+    let mut out = String::with_capacity(fields.len());
+    for entry in split_unquoted_spaces(fields) {
+        let name = entry.split('=').next().unwrap_or(entry);
+        if exclude.contains(&name) {
+            continue;
+        }
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(entry);
+    }
+    out
+}
+
+/// Splits `s` on ASCII spaces, except for spaces inside a `"`-delimited
+/// (with `\"` escaping) substring.
+fn split_unquoted_spaces(s: &str) -> Vec<&str> {
+    let mut entries = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ' ' if !in_quotes => {
+                if i > start {
+                    entries.push(&s[start..i]);
+                }
+                start = i + 1;
             }
+            _ => {}
+        }
+    }
+    if start < s.len() {
+        entries.push(&s[start..]);
+    }
+    entries
+}
+
+struct FullCtx<'a, N> {
+    ctx: &'a span::Context<'a, N>,
+    ansi: bool,
+    enabled: bool,
+    dedup: bool,
+    collapse_repeats: bool,
+    dedup_fields: &'a [&'a str],
+}
+
+impl<'a, N: 'a> FullCtx<'a, N> {
+    pub(crate) fn new(
+        ctx: &'a span::Context<'a, N>,
+        ansi: bool,
+        enabled: bool,
+        dedup: bool,
+        collapse_repeats: bool,
+        dedup_fields: &'a [&'a str],
+    ) -> Self {
+        Self {
+            ctx,
+            ansi,
+            enabled,
+            dedup,
+            collapse_repeats,
+            dedup_fields,
+        }
+    }
+
+    /// Collects `(name, fields)` for every span in the current context, in
+    /// root-to-leaf order, cloning each span's rendered fields (with any
+    /// `dedup_fields` entries stripped) so they outlive the lock held by
+    /// [`span::Context::visit_spans`].
+    fn collect_entries(&self) -> Result<Vec<(&'static str, String)>, fmt::Error> {
+        let mut entries = Vec::new();
+        self.ctx.visit_spans(|_id, span| {
+            entries.push((
+                span.name(),
+                filter_rendered_fields(span.fields(), self.dedup_fields),
+            ));
+            Ok(())
+        })?;
+        Ok(entries)
+    }
+}
+
+#[cfg(feature = "ansi")]
+impl<'a, N> fmt::Display for FullCtx<'a, N>
+where
+    N: super::NewVisitor<'a>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.enabled {
+            return Ok(());
+        }
+        let style = if self.ansi {
+            Style::new().bold()
         } else {
-            match *self.level {
-                Level::TRACE => f.pad("TRACE"),
-                Level::DEBUG => f.pad("DEBUG"),
-                Level::INFO => f.pad("INFO"),
-                Level::WARN => f.pad("WARN"),
-                Level::ERROR => f.pad("ERROR"),
+            Style::new()
+        };
+
+        if self.collapse_repeats {
+            let entries = self.collect_entries()?;
+            let mut i = 0;
+            while i < entries.len() {
+                let (name, fields) = &entries[i];
+                let mut count = 1;
+                while i + count < entries.len() && entries[i + count].0 == *name {
+                    count += 1;
+                }
+                write!(f, "{}", style.paint(*name))?;
+                if count > 1 {
+                    write!(f, "×{}", count)?;
+                }
+                if !fields.is_empty() {
+                    write!(f, "{}{}{}", style.paint("{"), fields, style.paint("}"))?;
+                }
+                ":".fmt(f)?;
+                i += count;
             }
+            if !entries.is_empty() {
+                f.pad(" ")?;
+            }
+            return Ok(());
         }
+
+        let mut seen = false;
+        let mut last_name: Option<&'static str> = None;
+        self.ctx.visit_spans(|_id, span| {
+            if self.dedup && last_name == Some(span.name()) {
+                return Ok(());
+            }
+            last_name = Some(span.name());
+
+            write!(f, "{}", style.paint(span.name()))?;
+
+            seen = true;
+
+            let fields = filter_rendered_fields(span.fields(), self.dedup_fields);
+            if !fields.is_empty() {
+                write!(f, "{}{}{}", style.paint("{"), fields, style.paint("}"))?;
+            }
+            ":".fmt(f)
+        })?;
+        if seen {
+            f.pad(" ")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "ansi"))]
+impl<'a, N> fmt::Display for FullCtx<'a, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        if self.collapse_repeats {
+            let entries = self.collect_entries()?;
+            let mut i = 0;
+            while i < entries.len() {
+                let (name, fields) = &entries[i];
+                let mut count = 1;
+                while i + count < entries.len() && entries[i + count].0 == *name {
+                    count += 1;
+                }
+                write!(f, "{}", name)?;
+                if count > 1 {
+                    write!(f, "×{}", count)?;
+                }
+                if !fields.is_empty() {
+                    write!(f, "{{{}}}", fields)?;
+                }
+                ":".fmt(f)?;
+                i += count;
+            }
+            if !entries.is_empty() {
+                f.pad(" ")?;
+            }
+            return Ok(());
+        }
+
+        let mut seen = false;
+        let mut last_name: Option<&'static str> = None;
+        self.ctx.visit_spans(|_id, span| {
+            if self.dedup && last_name == Some(span.name()) {
+                return Ok(());
+            }
+            last_name = Some(span.name());
+
+            write!(f, "{}", span.name())?;
+            seen = true;
+
+            let fields = filter_rendered_fields(span.fields(), self.dedup_fields);
+            if !fields.is_empty() {
+                write!(f, "{{{}}}", fields)?;
+            }
+            ":".fmt(f)
+        })?;
+        if seen {
+            f.pad(" ")?;
+        }
+        Ok(())
+    }
+}
+
+struct FmtLevel<'a> {
+    level: &'a Level,
+    ansi: bool,
+    lowercase: bool,
+}
+
+impl<'a> FmtLevel<'a> {
+    pub(crate) fn new(level: &'a Level, ansi: bool, lowercase: bool) -> Self {
+        Self {
+            level,
+            ansi,
+            lowercase,
+        }
+    }
+
+    /// The level's name, with no extra padding.
+    fn name(&self) -> &'static str {
+        match (self.level.clone(), self.lowercase) {
+            (Level::TRACE, false) => "TRACE",
+            (Level::DEBUG, false) => "DEBUG",
+            (Level::INFO, false) => "INFO",
+            (Level::WARN, false) => "WARN",
+            (Level::ERROR, false) => "ERROR",
+            (Level::TRACE, true) => "trace",
+            (Level::DEBUG, true) => "debug",
+            (Level::INFO, true) => "info",
+            (Level::WARN, true) => "warn",
+            (Level::ERROR, true) => "error",
+        }
+    }
+
+    /// The level's name, with a leading space on the four-letter `INFO`/`WARN`
+    /// forms so that they line up with the five-letter `TRACE`/`DEBUG`/`ERROR`.
+    fn padded_name(&self) -> &'static str {
+        match (self.level.clone(), self.lowercase) {
+            (Level::INFO, false) => " INFO",
+            (Level::WARN, false) => " WARN",
+            (Level::INFO, true) => " info",
+            (Level::WARN, true) => " warn",
+            _ => self.name(),
+        }
+    }
+}
+
+#[cfg(not(feature = "ansi"))]
+impl<'a> fmt::Display for FmtLevel<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+#[cfg(feature = "ansi")]
+impl<'a> fmt::Display for FmtLevel<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.ansi {
+            return f.pad(self.name());
+        }
+        match *self.level {
+            Level::TRACE => write!(f, "{}", Colour::Purple.paint(self.padded_name())),
+            Level::DEBUG => write!(f, "{}", Colour::Blue.paint(self.padded_name())),
+            Level::INFO => write!(f, "{}", Colour::Green.paint(self.padded_name())),
+            Level::WARN => write!(f, "{}", Colour::Yellow.paint(self.padded_name())),
+            Level::ERROR => write!(f, "{}", Colour::Red.paint(self.padded_name())),
+        }
+    }
+}
+
+/// Formats events as [GELF 1.1] JSON objects, one per line, suitable for
+/// ingestion by Graylog.
+///
+/// Requires the `gelf` feature flag.
+///
+/// Tracing fields are emitted as `_`-prefixed additional fields, as required
+/// by the GELF spec, so that they cannot collide with the reserved
+/// `version`/`host`/`short_message`/`timestamp`/`level` keys. The `message`
+/// field (if present) becomes `short_message` instead of an additional
+/// field.
+///
+/// [GELF 1.1]: https://docs.graylog.org/docs/gelf
+#[cfg(feature = "gelf")]
+#[derive(Debug, Clone)]
+pub struct Gelf {
+    host: String,
+}
+
+#[cfg(feature = "gelf")]
+impl Gelf {
+    /// Returns a new `Gelf` formatter that reports the given `host`.
+    pub fn new(host: impl Into<String>) -> Self {
+        Self { host: host.into() }
+    }
+}
+
+#[cfg(feature = "gelf")]
+impl<N> FormatEvent<N> for Gelf
+where
+    N: for<'a> super::NewVisitor<'a>,
+{
+    fn format_event(
+        &self,
+        _ctx: &span::Context<'_, N>,
+        writer: &mut dyn fmt::Write,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        let timestamp = now.as_secs() as f64 + f64::from(now.subsec_nanos()) / 1_000_000_000.0;
+
+        let mut fields = TypedFields::new();
+        event.record(&mut fields);
+
+        let mut short_message = String::new();
+        let mut extra = Vec::new();
+        for (name, value) in fields.into_fields() {
+            if name == "message" {
+                short_message = value.to_string();
+            } else {
+                extra.push((name, value));
+            }
+        }
+
+        write!(
+            writer,
+            "{{\"version\":\"1.1\",\"host\":{},\"short_message\":{},\"timestamp\":{},\"level\":{}",
+            json_string(&self.host),
+            json_string(&short_message),
+            timestamp,
+            gelf_level(meta.level()),
+        )?;
+
+        for (name, value) in &extra {
+            write!(writer, ",\"_{}\":{}", name, json_value(value))?;
+        }
+
+        writeln!(writer, "}}")
+    }
+}
+
+/// Maps a `tracing` `Level` to its syslog-numeric equivalent, as used by the
+/// GELF `level` field.
+#[cfg(feature = "gelf")]
+fn gelf_level(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        Level::DEBUG | Level::TRACE => 7,
+    }
+}
+
+#[cfg(any(feature = "gelf", feature = "json"))]
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(any(feature = "gelf", feature = "json"))]
+fn json_value(v: &TypedValue) -> String {
+    match v {
+        TypedValue::I64(n) => n.to_string(),
+        TypedValue::U64(n) => n.to_string(),
+        TypedValue::F64(n) => n.to_string(),
+        TypedValue::Bool(b) => b.to_string(),
+        TypedValue::Str(s) => json_string(s),
+        TypedValue::Debug(s) => json_string(s),
+    }
+}
+
+#[cfg(all(test, feature = "gelf"))]
+mod gelf_tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::dispatcher::Dispatch;
+
+    #[test]
+    fn warn_emits_gelf_structure_with_syslog_level() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .on_event(Gelf::new("my-host"))
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        struct BufWriter(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for BufWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::warn!(request_id = 42u64, "disk usage high");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("\"version\":\"1.1\""));
+        assert!(out.contains("\"host\":\"my-host\""));
+        assert!(out.contains("\"short_message\":\"disk usage high\""));
+        assert!(out.contains("\"level\":4"));
+        assert!(out.contains("\"_request_id\":42"));
+    }
+}
+
+/// Formats events as newline-delimited JSON objects.
+///
+/// Requires the `json` feature flag.
+///
+/// Each event is encoded as a JSON object with `level`, `target`,
+/// `timestamp`, and `spans` (the names of the current span context, from
+/// root to leaf), plus one entry per recorded field. Field type fidelity is
+/// preserved via [`TypedValue`]: an integer, a float, a boolean, and a
+/// string are emitted as their native JSON types rather than all being
+/// stringified, so `answer = 42` is emitted as `"answer":42`, not
+/// `"answer":"42"`.
+///
+/// [`TypedValue`]: enum.TypedValue.html
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Default)]
+pub struct Json {
+    schema_version: &'static str,
+    span_id_only: bool,
+}
+
+#[cfg(feature = "json")]
+impl Json {
+    /// Returns a new `Json` formatter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stamps a `"schema":"<version>"` field on every emitted object.
+    ///
+    /// See [`Format::with_schema_version`] for the motivation; this is the
+    /// same idea applied to the JSON formatter.
+    ///
+    /// [`Format::with_schema_version`]: struct.Format.html#method.with_schema_version
+    pub fn with_schema_version(self, schema_version: &'static str) -> Self {
+        Self {
+            schema_version,
+            ..self
+        }
+    }
+
+    /// Emits a single `"span_id":"<hex>"` field naming the current span,
+    /// instead of the `"spans":[...]` array of every ancestor's name.
+    ///
+    /// For high-volume structured logs, repeating the full span name path on
+    /// every event is expensive in bytes. Enabling this trades that per-event
+    /// cost for a join: downstream tooling reconstructs the name (and any
+    /// other context) by matching this id against the `span.id`-to-name
+    /// mapping recorded by [`SpanEventsLayer`]'s `open` events, which are
+    /// emitted once per span rather than once per event.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`SpanEventsLayer`]: crate::span_events::SpanEventsLayer
+    pub fn with_span_id_only(self, span_id_only: bool) -> Self {
+        Self {
+            span_id_only,
+            ..self
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl<N> FormatEvent<N> for Json
+where
+    N: for<'a> super::NewVisitor<'a>,
+{
+    fn format_event(
+        &self,
+        ctx: &span::Context<'_, N>,
+        writer: &mut dyn fmt::Write,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        let timestamp = now.as_secs() as f64 + f64::from(now.subsec_nanos()) / 1_000_000_000.0;
+
+        let mut fields = TypedFields::new();
+        event.record(&mut fields);
+
+        write!(
+            writer,
+            "{{\"level\":{},\"target\":{},\"timestamp\":{}",
+            json_string(&meta.level().to_string()),
+            json_string(meta.target()),
+            timestamp,
+        )?;
+
+        if !self.schema_version.is_empty() {
+            write!(writer, ",\"schema\":{}", json_string(self.schema_version))?;
+        }
+
+        for (name, value) in fields.into_fields() {
+            write!(writer, ",{}:{}", json_string(name), json_value(&value))?;
+        }
+
+        if self.span_id_only {
+            if let Some(id) = ctx.with_current(|(id, _)| id.into_u64()) {
+                write!(writer, ",\"span_id\":{}", json_string(&format!("{:x}", id)))?;
+            }
+        } else {
+            write!(writer, ",\"spans\":[")?;
+            let mut first = true;
+            ctx.visit_spans(|_, span| {
+                if !first {
+                    write!(writer, ",")?;
+                }
+                first = false;
+                write!(writer, "{}", json_string(span.name()))
+            })?;
+            write!(writer, "]")?;
+        }
+
+        writeln!(writer, "}}")
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod json_tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::dispatcher::Dispatch;
+
+    #[test]
+    fn typed_fields_are_emitted_as_native_json_types() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .on_event(Json::new())
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        struct BufWriter(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for BufWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!(answer = 42, flag = true, "the question");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("\"answer\":42"));
+        assert!(out.contains("\"flag\":true"));
+        assert!(!out.contains("\"answer\":\"42\""));
+        assert!(!out.contains("\"flag\":\"true\""));
+    }
+
+    #[test]
+    fn schema_version_is_stamped_on_every_object() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .on_event(Json::new().with_schema_version("2"))
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        struct BufWriter(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for BufWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("hello");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("\"schema\":\"2\""));
+    }
+
+    #[test]
+    fn span_id_only_emits_a_hex_id_instead_of_the_spans_array() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .on_event(Json::new().with_span_id_only(true))
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        struct BufWriter(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for BufWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("checkout");
+            let _enter = span.enter();
+            tracing::info!("hello");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("\"span_id\":"));
+        assert!(!out.contains("\"spans\":"));
+        assert!(!out.contains("checkout"));
+    }
+}
+
+/// Formats events as [logfmt] `key=value` pairs, one event per line.
+///
+/// Requires the `logfmt` feature flag.
+///
+/// Each line starts with `time`, `level`, and `target`, followed by
+/// `message` (if the event has one) and then one `key=value` pair per
+/// recorded field, in the order they were recorded. A value containing a
+/// space, an equals sign, or a double quote is rendered as a double-quoted,
+/// escaped string; every other value is rendered bare.
+///
+/// [logfmt]: https://brandur.org/logfmt
+#[cfg(feature = "logfmt")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Logfmt {
+    span_id_only: bool,
+}
+
+#[cfg(feature = "logfmt")]
+impl Logfmt {
+    /// Returns a new `Logfmt` formatter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a `span_id=<hex>` field naming the current span.
+    ///
+    /// For high-volume structured logs, repeating the full span context on
+    /// every line is expensive in bytes. Enabling this adds just the id;
+    /// downstream tooling reconstructs the name (and any other context) by
+    /// matching it against the `span.id`-to-name mapping recorded by
+    /// [`SpanEventsLayer`]'s `open` events, which are emitted once per span
+    /// rather than once per line.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`SpanEventsLayer`]: crate::span_events::SpanEventsLayer
+    pub fn with_span_id_only(self, span_id_only: bool) -> Self {
+        Self { span_id_only }
+    }
+}
+
+#[cfg(feature = "logfmt")]
+impl<N> FormatEvent<N> for Logfmt
+where
+    N: for<'a> super::NewVisitor<'a>,
+{
+    fn format_event(
+        &self,
+        ctx: &span::Context<'_, N>,
+        writer: &mut dyn fmt::Write,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        let timestamp = now.as_secs() as f64 + f64::from(now.subsec_nanos()) / 1_000_000_000.0;
+
+        write!(
+            writer,
+            "time={} level={} target={}",
+            logfmt_value(&timestamp.to_string()),
+            meta.level().to_string().to_lowercase(),
+            logfmt_value(meta.target()),
+        )?;
+
+        if self.span_id_only {
+            if let Some(id) = ctx.with_current(|(id, _)| id.into_u64()) {
+                write!(writer, " span_id={:x}", id)?;
+            }
+        }
+
+        let mut fields = TypedFields::new();
+        event.record(&mut fields);
+
+        let mut extra = Vec::new();
+        for (name, value) in fields.into_fields() {
+            if name == "message" {
+                write!(writer, " message={}", logfmt_value(&value.to_string()))?;
+            } else {
+                extra.push((name, value));
+            }
+        }
+
+        for (name, value) in &extra {
+            write!(writer, " {}={}", name, logfmt_value(&value.to_string()))?;
+        }
+
+        writeln!(writer)
+    }
+}
+
+/// Renders a logfmt value, double-quoting and escaping it if it contains a
+/// space, an equals sign, a double quote, or is empty — all of which would
+/// otherwise be ambiguous or unparseable as a bare token.
+#[cfg(feature = "logfmt")]
+fn logfmt_value(s: &str) -> String {
+    let needs_quoting = s.is_empty() || s.contains(|c: char| c == ' ' || c == '=' || c == '"');
+    if !needs_quoting {
+        return s.to_string();
+    }
+
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(all(test, feature = "logfmt"))]
+mod logfmt_tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::dispatcher::Dispatch;
+
+    struct BufWriter(Arc<Mutex<Vec<u8>>>);
+    impl std::io::Write for BufWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn level_and_target_are_present() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .on_event(Logfmt::new())
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("the question");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("level=info"));
+        assert!(out.contains("target=tracing_subscriber::fmt::format::logfmt_tests"));
+        assert!(out.contains("message=\"the question\""));
+    }
+
+    #[test]
+    fn a_value_containing_a_space_is_quoted() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .on_event(Logfmt::new())
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!(detail = "two words", "hello");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("detail=\"two words\""));
+    }
+
+    #[test]
+    fn a_plain_value_is_not_quoted() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .on_event(Logfmt::new())
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!(answer = 42, "hello");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("answer=42"));
+        assert!(!out.contains("answer=\"42\""));
+    }
+
+    #[test]
+    fn span_id_only_emits_a_hex_id_instead_of_no_span_context_at_all() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .on_event(Logfmt::new().with_span_id_only(true))
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("checkout");
+            let _enter = span.enter();
+            tracing::info!("hello");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("span_id="));
+        assert!(!out.contains("checkout"));
+    }
+}
+
+/// Formats events as [MessagePack]-encoded maps, one per line.
+///
+/// Requires the `msgpack` feature flag.
+///
+/// Each event is encoded as a MessagePack map (`level`, `target`,
+/// `timestamp`, one entry per recorded field, and `spans` for the current
+/// span context) with field type fidelity preserved via [`TypedValue`] — an
+/// integer, a float, a boolean, and a string are encoded as their native
+/// MessagePack types rather than all being stringified.
+///
+/// [`FormatEvent::format_event`] writes through a `&mut dyn fmt::Write`,
+/// which (like the rest of this module's formatters) only accepts valid
+/// UTF-8. Since raw MessagePack bytes are not generally valid UTF-8, each
+/// encoded map is base64-encoded before being written, one map per line;
+/// the line boundary is the self-delimiting framing between records. The
+/// wire format is therefore base64-encoded MessagePack, not raw MessagePack
+/// bytes — callers that want to re-derive the original bytes should base64-
+/// decode each line before passing it to a MessagePack decoder.
+///
+/// [MessagePack]: https://msgpack.org/
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Default)]
+pub struct MsgPack {
+    _p: (),
+}
+
+#[cfg(feature = "msgpack")]
+impl MsgPack {
+    /// Returns a new `MsgPack` formatter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl<N> FormatEvent<N> for MsgPack
+where
+    N: for<'a> super::NewVisitor<'a>,
+{
+    fn format_event(
+        &self,
+        ctx: &span::Context<'_, N>,
+        writer: &mut dyn fmt::Write,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        let timestamp = now.as_secs() as f64 + f64::from(now.subsec_nanos()) / 1_000_000_000.0;
+
+        let mut fields = TypedFields::new();
+        event.record(&mut fields);
+        let fields = fields.into_fields();
+
+        let mut spans = Vec::new();
+        let _ = ctx.visit_spans(|_, span| {
+            spans.push(span.name().to_owned());
+            Ok::<(), fmt::Error>(())
+        });
+
+        let mut entries: Vec<(&str, msgpack::Value)> = vec![
+            ("level", msgpack::Value::Str(meta.level().to_string())),
+            ("target", msgpack::Value::Str(meta.target().to_owned())),
+            ("timestamp", msgpack::Value::F64(timestamp)),
+        ];
+        for (name, value) in &fields {
+            entries.push((name, msgpack::Value::from(value)));
+        }
+        entries.push((
+            "spans",
+            msgpack::Value::Array(spans.into_iter().map(msgpack::Value::Str).collect()),
+        ));
+
+        let mut bytes = Vec::new();
+        msgpack::encode_map(&mut bytes, &entries);
+
+        writeln!(writer, "{}", msgpack::base64_encode(&bytes))
+    }
+}
+
+/// A minimal, hand-rolled [MessagePack] byte encoder, covering just the
+/// value kinds [`TypedValue`] can produce, plus strings, floats, and arrays
+/// of strings for the span-context field. This avoids pulling in a
+/// `serde`/`rmp` dependency for the one formatter that needs it.
+///
+/// [MessagePack]: https://msgpack.org/
+#[cfg(feature = "msgpack")]
+mod msgpack {
+    use super::TypedValue;
+
+    pub(super) enum Value {
+        I64(i64),
+        U64(u64),
+        F64(f64),
+        Bool(bool),
+        Str(String),
+        Array(Vec<Value>),
+    }
+
+    impl From<&TypedValue> for Value {
+        fn from(v: &TypedValue) -> Self {
+            match v {
+                TypedValue::I64(n) => Value::I64(*n),
+                TypedValue::U64(n) => Value::U64(*n),
+                TypedValue::F64(n) => Value::F64(*n),
+                TypedValue::Bool(b) => Value::Bool(*b),
+                TypedValue::Str(s) => Value::Str(s.clone()),
+                TypedValue::Debug(s) => Value::Str(s.clone()),
+            }
+        }
+    }
+
+    fn encode_str(out: &mut Vec<u8>, s: &str) {
+        let bytes = s.as_bytes();
+        let len = bytes.len();
+        if len < 32 {
+            out.push(0xa0 | len as u8);
+        } else if len <= 0xff {
+            out.push(0xd9);
+            out.push(len as u8);
+        } else if len <= 0xffff {
+            out.push(0xda);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        } else {
+            out.push(0xdb);
+            out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+        out.extend_from_slice(bytes);
+    }
+
+    fn encode_value(out: &mut Vec<u8>, value: &Value) {
+        match value {
+            Value::I64(n) => {
+                out.push(0xd3);
+                out.extend_from_slice(&n.to_be_bytes());
+            }
+            Value::U64(n) => {
+                out.push(0xcf);
+                out.extend_from_slice(&n.to_be_bytes());
+            }
+            Value::F64(n) => {
+                out.push(0xcb);
+                out.extend_from_slice(&n.to_be_bytes());
+            }
+            Value::Bool(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+            Value::Str(s) => encode_str(out, s),
+            Value::Array(items) => {
+                let len = items.len();
+                if len < 16 {
+                    out.push(0x90 | len as u8);
+                } else if len <= 0xffff {
+                    out.push(0xdc);
+                    out.extend_from_slice(&(len as u16).to_be_bytes());
+                } else {
+                    out.push(0xdd);
+                    out.extend_from_slice(&(len as u32).to_be_bytes());
+                }
+                for item in items {
+                    encode_value(out, item);
+                }
+            }
+        }
+    }
+
+    pub(super) fn encode_map(out: &mut Vec<u8>, entries: &[(&str, Value)]) {
+        let len = entries.len();
+        if len < 16 {
+            out.push(0x80 | len as u8);
+        } else if len <= 0xffff {
+            out.push(0xde);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        } else {
+            out.push(0xdf);
+            out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+        for (key, value) in entries {
+            encode_str(out, key);
+            encode_value(out, value);
+        }
+    }
+
+    const BASE64_ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    pub(super) fn base64_encode(bytes: &[u8]) -> String {
+        let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+        for chunk in bytes.chunks(3) {
+            let b0 = chunk[0];
+            let b1 = *chunk.get(1).unwrap_or(&0);
+            let b2 = *chunk.get(2).unwrap_or(&0);
+
+            out.push(BASE64_ALPHABET[(b0 >> 2) as usize] as char);
+            out.push(BASE64_ALPHABET[(((b0 & 0x03) << 4) | (b1 >> 4)) as usize] as char);
+            out.push(if chunk.len() > 1 {
+                BASE64_ALPHABET[(((b1 & 0x0f) << 2) | (b2 >> 6)) as usize] as char
+            } else {
+                '='
+            });
+            out.push(if chunk.len() > 2 {
+                BASE64_ALPHABET[(b2 & 0x3f) as usize] as char
+            } else {
+                '='
+            });
+        }
+        out
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn base64_matches_known_vectors() {
+            assert_eq!(base64_encode(b""), "");
+            assert_eq!(base64_encode(b"f"), "Zg==");
+            assert_eq!(base64_encode(b"fo"), "Zm8=");
+            assert_eq!(base64_encode(b"foo"), "Zm9v");
+            assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        }
+
+        #[test]
+        fn encodes_fixmap_and_fixstr() {
+            let mut out = Vec::new();
+            encode_map(&mut out, &[("a", Value::Str("b".to_owned()))]);
+            // fixmap with 1 entry, fixstr "a", fixstr "b"
+            assert_eq!(out, vec![0x81, 0xa1, b'a', 0xa1, b'b']);
+        }
+
+        #[test]
+        fn encodes_typed_values_preserving_kind() {
+            let mut out = Vec::new();
+            encode_value(&mut out, &Value::I64(-1));
+            assert_eq!(out, vec![0xd3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+
+            let mut out = Vec::new();
+            encode_value(&mut out, &Value::Bool(true));
+            assert_eq!(out, vec![0xc3]);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "msgpack"))]
+mod msgpack_tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::dispatcher::Dispatch;
+
+    struct BufWriter(Arc<Mutex<Vec<u8>>>);
+    impl std::io::Write for BufWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn emits_one_base64_line_per_event() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .on_event(MsgPack::new())
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::warn!(request_id = 42u64, "disk usage high");
+            tracing::info!(ratio = 0.5f64, "half done");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for line in &lines {
+            assert!(!line.is_empty());
+            // Every line should be valid, padded base64.
+            assert_eq!(line.len() % 4, 0);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "fmt"))]
+mod field_dedup_tests {
+    use std::sync::{Arc, Mutex};
+    use tracing_core::dispatcher::Dispatch;
+
+    struct BufWriter(Arc<Mutex<Vec<u8>>>);
+    impl std::io::Write for BufWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn full_mode_hides_a_span_field_shadowed_by_the_event() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .with_field_dedup(true)
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("request", request_id = 1);
+            let _enter = span.enter();
+            tracing::info!(request_id = 2, "retried");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        // The event's own `request_id=2` survives; the span's shadowed
+        // `request_id=1` does not.
+        assert!(out.contains("request_id=2"));
+        assert!(!out.contains("request_id=1"));
+    }
+
+    #[test]
+    fn field_dedup_off_by_default_shows_both_values() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("request", request_id = 1);
+            let _enter = span.enter();
+            tracing::info!(request_id = 2, "retried");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("request_id=2"));
+        assert!(out.contains("request_id=1"));
+    }
+
+    #[test]
+    fn compact_mode_hides_a_span_field_shadowed_by_the_event() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let writer_buf = buf.clone();
+        let subscriber = crate::fmt::Subscriber::builder()
+            .compact()
+            .with_field_dedup(true)
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || BufWriter(writer_buf.clone()))
+            .finish();
+
+        let dispatch = Dispatch::new(subscriber);
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("request", request_id = 1);
+            let _enter = span.enter();
+            tracing::info!(request_id = 2, "retried");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("request_id=2"));
+        assert!(!out.contains("request_id=1"));
     }
 }
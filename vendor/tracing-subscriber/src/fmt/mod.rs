@@ -22,7 +22,24 @@ use crate::filter::LevelFilter;
 use crate::layer::{self, Layer};
 
 #[doc(inline)]
-pub use self::{format::FormatEvent, span::Context, writer::MakeWriter};
+pub use self::{
+    format::FormatEvent,
+    span::Context,
+    writer::{
+        non_blocking, ErrorRoutedWriter, MakeWriter, NonBlocking, RingWriter, RingWriterHandle,
+        Utf8Policy, WorkerGuard,
+    },
+};
+
+/// Returns the span `Id` handed out for every span when
+/// [`Builder::without_spans`] has disabled span tracking. Since no span data
+/// is ever stored, every span is represented by this same sentinel.
+///
+/// [`Builder::without_spans`]: struct.Builder.html#method.without_spans
+#[inline]
+fn no_span_id() -> tracing_core::span::Id {
+    tracing_core::span::Id::from_u64(0xDEAD_FACE)
+}
 
 /// A `Subscriber` that logs formatted representations of `tracing` events.
 ///
@@ -67,10 +84,88 @@ pub struct Builder<
     make_writer: W,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 struct Settings {
     inherit_fields: bool,
     initial_span_capacity: usize,
+    span_tracking: bool,
+    close_duration: CloseDuration,
+    span_location: bool,
+    invalid_utf8_policy: writer::Utf8Policy,
+    capture_panics: bool,
+    write_retry: WriteRetry,
+    intern_metadata: bool,
+}
+
+/// A bounded retry/backoff policy for transient `WouldBlock` errors from a
+/// [`MakeWriter`](writer::MakeWriter)'s writer.
+///
+/// The default policy performs no retries: a `WouldBlock` error is treated
+/// the same as any other write error (the event is silently dropped), which
+/// matches this subscriber's historical behavior.
+#[derive(Debug, Clone, Copy)]
+struct WriteRetry {
+    max_attempts: usize,
+    backoff: std::time::Duration,
+}
+
+impl Default for WriteRetry {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff: std::time::Duration::from_millis(0),
+        }
+    }
+}
+
+/// Writes `bytes` to `writer`, retrying according to `retry` when the write
+/// fails with [`io::ErrorKind::WouldBlock`], up to `retry.max_attempts`
+/// attempts in total. Any other error, or a `WouldBlock` on the final
+/// attempt, is silently dropped, matching this subscriber's long-standing
+/// best-effort write semantics.
+fn write_with_retry<W: io::Write>(writer: &mut W, bytes: &[u8], retry: &WriteRetry) {
+    for attempt in 1..=retry.max_attempts.max(1) {
+        match writer.write_all(bytes) {
+            Ok(()) => return,
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock && attempt < retry.max_attempts => {
+                std::thread::sleep(retry.backoff);
+            }
+            Err(_) => return,
+        }
+    }
+}
+
+/// A snapshot of a [`Builder`]'s formatting configuration: its field
+/// visitor, event formatter, and toggles such as [`without_spans`] or
+/// [`with_span_close_timing`] — everything except the filter and writer.
+///
+/// Capturing a `FmtConfig` with [`Builder::config`] and re-applying it to
+/// other builders with [`Builder::with_config`] keeps formatting consistent
+/// across subscribers that log to different destinations (for example,
+/// console and file), while still allowing each builder's writer to vary
+/// independently.
+///
+/// [`without_spans`]: struct.Builder.html#method.without_spans
+/// [`with_span_close_timing`]: struct.Builder.html#method.with_span_close_timing
+/// [`Builder::config`]: struct.Builder.html#method.config
+/// [`Builder::with_config`]: struct.Builder.html#method.with_config
+#[derive(Debug, Clone)]
+pub struct FmtConfig<N = format::NewRecorder, E = format::Format<format::Full>> {
+    new_visitor: N,
+    fmt_event: E,
+    settings: Settings,
+}
+
+/// Whether (and how) a line is logged when a span closes, recording how long
+/// it was open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CloseDuration {
+    /// Don't log anything when a span closes.
+    Off,
+    /// Log the duration using `Duration`'s `Debug` representation.
+    Raw,
+    /// Log the duration using [`format::format_duration_human`].
+    Human,
 }
 
 impl Subscriber {
@@ -99,6 +194,31 @@ impl Default for Subscriber {
         Builder::default().finish()
     }
 }
+
+impl<N, E, F, W> Subscriber<N, E, F, W>
+where
+    W: self::writer::ShutdownWriter,
+{
+    /// Flushes and shuts down this subscriber's writer, if it owns a
+    /// background worker (see [`non_blocking`]), blocking for up to five
+    /// seconds.
+    ///
+    /// This subscriber wraps a single [`MakeWriter`], so "propagating" a
+    /// shutdown means forwarding it to that one writer; returns the number
+    /// of writes still buffered when this method returned (normally `0`).
+    ///
+    /// [`non_blocking`]: writer/fn.non_blocking.html
+    /// [`MakeWriter`]: writer/trait.MakeWriter.html
+    pub fn shutdown(&self) -> usize {
+        self.shutdown_timeout(std::time::Duration::from_secs(5))
+    }
+
+    /// Like [`shutdown`](Subscriber::shutdown), but with an explicit
+    /// timeout.
+    pub fn shutdown_timeout(&self, timeout: std::time::Duration) -> usize {
+        self.inner.inner_ref().make_writer.shutdown(timeout)
+    }
+}
 // === impl Subscriber ===
 
 impl<N, E, F, W> tracing_core::Subscriber for Subscriber<N, E, F, W>
@@ -141,7 +261,6 @@ where
 
     #[inline]
     fn enter(&self, id: &span::Id) {
-        // TODO: add on_enter hook
         self.inner.enter(id);
     }
 
@@ -185,6 +304,49 @@ where
     }
 }
 
+impl<N, E, W> Formatter<N, E, W>
+where
+    W: MakeWriter,
+{
+    fn write_open_line(&self, name: &'static str, metadata: &'static Metadata<'static>) {
+        use std::fmt::Write as _;
+
+        let (file, line) = match (metadata.file(), metadata.line()) {
+            (Some(file), Some(line)) => (file, line),
+            _ => return,
+        };
+        let mut buf = String::new();
+        if write!(buf, "{} opened at {}:{}\n", name, file, line).is_ok() {
+            let mut writer = self.make_writer.make_writer_for(metadata);
+            let bytes = writer::sanitize_utf8(buf.as_bytes(), self.settings.invalid_utf8_policy);
+            write_with_retry(&mut writer, bytes.as_bytes(), &self.settings.write_retry);
+        }
+    }
+
+    fn write_close_line(&self, name: &'static str, busy: std::time::Duration, idle: std::time::Duration) {
+        use std::fmt::Write as _;
+
+        let fmt_duration = |d: std::time::Duration| match self.settings.close_duration {
+            CloseDuration::Human => format::format_duration_human(d),
+            _ => format!("{:?}", d),
+        };
+        let mut buf = String::new();
+        if write!(
+            buf,
+            "close {}: time.busy={} time.idle={}\n",
+            name,
+            fmt_duration(busy),
+            fmt_duration(idle)
+        )
+        .is_ok()
+        {
+            let mut writer = self.make_writer.make_writer();
+            let bytes = writer::sanitize_utf8(buf.as_bytes(), self.settings.invalid_utf8_policy);
+            write_with_retry(&mut writer, bytes.as_bytes(), &self.settings.write_retry);
+        }
+    }
+}
+
 impl<N, E, W> tracing_core::Subscriber for Formatter<N, E, W>
 where
     N: for<'a> NewVisitor<'a> + 'static,
@@ -201,11 +363,21 @@ where
 
     #[inline]
     fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
-        self.spans.new_span(attrs, &self.new_visitor)
+        if !self.settings.span_tracking {
+            return no_span_id();
+        }
+        let id = self.spans.new_span(attrs, &self.new_visitor);
+        if self.settings.span_location {
+            self.write_open_line(attrs.metadata().name(), attrs.metadata());
+        }
+        id
     }
 
     #[inline]
     fn record(&self, span: &span::Id, values: &span::Record<'_>) {
+        if !self.settings.span_tracking {
+            return;
+        }
         self.spans.record(span, values, &self.new_visitor)
     }
 
@@ -234,8 +406,9 @@ where
             };
 
             if self.fmt_event.format_event(&self.ctx(), buf, event).is_ok() {
-                let mut writer = self.make_writer.make_writer();
-                let _ = io::Write::write_all(&mut writer, buf.as_bytes());
+                let mut writer = self.make_writer.make_writer_for(event.metadata());
+                let bytes = writer::sanitize_utf8(buf.as_bytes(), self.settings.invalid_utf8_policy);
+                write_with_retry(&mut writer, bytes.as_bytes(), &self.settings.write_retry);
             }
 
             buf.clear();
@@ -243,15 +416,23 @@ where
     }
 
     fn enter(&self, id: &span::Id) {
-        // TODO: add on_enter hook
+        if !self.settings.span_tracking {
+            return;
+        }
         self.spans.push(id);
     }
 
     fn exit(&self, id: &span::Id) {
+        if !self.settings.span_tracking {
+            return;
+        }
         self.spans.pop(id);
     }
 
     fn current_span(&self) -> span::Current {
+        if !self.settings.span_tracking {
+            return span::Current::none();
+        }
         if let Some(id) = self.spans.current() {
             if let Some(meta) = self.spans.get(&id).map(|span| span.metadata()) {
                 return span::Current::new(id, meta);
@@ -262,12 +443,31 @@ where
 
     #[inline]
     fn clone_span(&self, id: &span::Id) -> span::Id {
+        if !self.settings.span_tracking {
+            return id.clone();
+        }
         self.spans.clone_span(id)
     }
 
     #[inline]
     fn try_close(&self, id: span::Id) -> bool {
-        self.spans.drop_span(id)
+        if !self.settings.span_tracking {
+            return false;
+        }
+        if self.settings.close_duration == CloseDuration::Off {
+            return self.spans.drop_span(id);
+        }
+        let name_and_timings = self
+            .spans
+            .get(&id)
+            .map(|span| (span.name(), span.busy(), span.idle()));
+        let closed = self.spans.drop_span(id);
+        if closed {
+            if let Some((name, busy, idle)) = name_and_timings {
+                self.write_close_line(name, busy, idle);
+            }
+        }
+        closed
     }
 
     unsafe fn downcast_raw(&self, id: TypeId) -> Option<*const ()> {
@@ -326,10 +526,16 @@ where
 {
     /// Finish the builder, returning a new `FmtSubscriber`.
     pub fn finish(self) -> Subscriber<N, E, F, W> {
+        if self.settings.capture_panics {
+            crate::panics::install_panic_hook();
+        }
         let subscriber = Formatter {
             new_visitor: self.new_visitor,
             fmt_event: self.fmt_event,
-            spans: span::Store::with_capacity(self.settings.initial_span_capacity),
+            spans: span::Store::new(
+                self.settings.initial_span_capacity,
+                self.settings.intern_metadata,
+            ),
             settings: self.settings,
             make_writer: self.make_writer,
         };
@@ -337,6 +543,93 @@ where
             inner: self.filter.with_subscriber(subscriber),
         }
     }
+
+    /// Finish the builder, wrapping the result in a [`Dispatch`].
+    ///
+    /// This is equivalent to calling [`finish`] and passing the result to
+    /// [`Dispatch::new`], but saves advanced callers who want to register
+    /// the `Dispatch` themselves (for example, with
+    /// [`dispatcher::with_default`] for a scoped rather than global
+    /// subscriber) from having to name the concrete subscriber type.
+    ///
+    /// [`finish`]: #method.finish
+    /// [`Dispatch`]: https://docs.rs/tracing-core/latest/tracing_core/dispatcher/struct.Dispatch.html
+    /// [`Dispatch::new`]: https://docs.rs/tracing-core/latest/tracing_core/dispatcher/struct.Dispatch.html#method.new
+    /// [`dispatcher::with_default`]: https://docs.rs/tracing-core/latest/tracing_core/dispatcher/fn.with_default.html
+    pub fn finish_dispatch(self) -> tracing_core::dispatcher::Dispatch
+    where
+        layer::Layered<F, Formatter<N, E, W>>: tracing_core::Subscriber + Send + Sync,
+    {
+        tracing_core::dispatcher::Dispatch::new(self.finish())
+    }
+
+    /// Install this builder's subscriber as the global default.
+    ///
+    /// This is equivalent to calling [`finish_dispatch`] and passing the
+    /// result to [`tracing::subscriber::set_global_default`], but returns an
+    /// error rather than panicking if a global default subscriber has
+    /// already been set.
+    ///
+    /// [`finish_dispatch`]: #method.finish_dispatch
+    /// [`tracing::subscriber::set_global_default`]: https://docs.rs/tracing/latest/tracing/subscriber/fn.set_global_default.html
+    pub fn try_init(self) -> Result<(), tracing_core::dispatcher::SetGlobalDefaultError>
+    where
+        layer::Layered<F, Formatter<N, E, W>>: tracing_core::Subscriber + Send + Sync,
+    {
+        tracing_core::dispatcher::set_global_default(self.finish_dispatch())
+    }
+
+    /// Install this builder's subscriber as the global default.
+    ///
+    /// # Panics
+    /// Panics if a global default subscriber has already been set.
+    pub fn init(self)
+    where
+        layer::Layered<F, Formatter<N, E, W>>: tracing_core::Subscriber + Send + Sync,
+    {
+        self.try_init()
+            .expect("failed to set global default subscriber")
+    }
+}
+
+/// Builds and installs a global default `Subscriber` combining an
+/// [`EnvFilter`] read from `RUST_LOG` (defaulting to `INFO` when unset or
+/// invalid) with the default text formatter writing to stderr.
+///
+/// This bundles the "`EnvFilter` from the environment, fmt to stderr, init"
+/// setup that most binaries using this crate end up writing by hand. For
+/// anything more specific than the defaults below, build a [`Subscriber`]
+/// with [`Subscriber::builder`] and call [`Builder::try_init`] instead.
+///
+/// [`EnvFilter`]: ../filter/struct.EnvFilter.html
+/// [`Subscriber`]: struct.Subscriber.html
+/// [`Subscriber::builder`]: struct.Subscriber.html#method.builder
+/// [`Builder::try_init`]: struct.Builder.html#method.try_init
+#[cfg(feature = "env-filter")]
+pub fn init_from_env() -> Result<(), tracing_core::dispatcher::SetGlobalDefaultError> {
+    try_init_from_env_with(LevelFilter::INFO, io::stderr)
+}
+
+/// Like [`init_from_env`], but allows overriding the default level used when
+/// `RUST_LOG` is unset or invalid, and the [`MakeWriter`] events are written
+/// to.
+///
+/// [`init_from_env`]: fn.init_from_env.html
+/// [`MakeWriter`]: trait.MakeWriter.html
+#[cfg(feature = "env-filter")]
+pub fn try_init_from_env_with<W>(
+    default_level: LevelFilter,
+    make_writer: W,
+) -> Result<(), tracing_core::dispatcher::SetGlobalDefaultError>
+where
+    W: MakeWriter + Send + Sync + 'static,
+{
+    let filter = crate::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| crate::EnvFilter::default().add_directive(default_level.into()));
+    Subscriber::builder()
+        .with_env_filter(filter)
+        .with_writer(make_writer)
+        .try_init()
 }
 
 impl<N, L, T, F, W> Builder<N, format::Format<L, T>, F, W>
@@ -381,6 +674,233 @@ where
             ..self
         }
     }
+
+    /// Sets whether or not the event's innermost (currently executing) span
+    /// is displayed.
+    ///
+    /// See [`Format::with_current_span`].
+    ///
+    /// [`Format::with_current_span`]: format/struct.Format.html#method.with_current_span
+    pub fn with_current_span(
+        self,
+        display_current_span: bool,
+    ) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.with_current_span(display_current_span),
+            ..self
+        }
+    }
+
+    /// Sets whether or not the full list of entered spans is displayed
+    /// before each event, in [`Full`] mode.
+    ///
+    /// See [`Format::with_span_list`].
+    ///
+    /// [`Full`]: format/struct.Full.html
+    /// [`Format::with_span_list`]: format/struct.Format.html#method.with_span_list
+    pub fn with_span_list(
+        self,
+        display_span_list: bool,
+    ) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.with_span_list(display_span_list),
+            ..self
+        }
+    }
+
+    /// Sets whether consecutive duplicate entries in the rendered span list
+    /// are collapsed into one.
+    ///
+    /// See [`Format::with_dedup_span_list`].
+    ///
+    /// [`Format::with_dedup_span_list`]: format/struct.Format.html#method.with_dedup_span_list
+    pub fn with_dedup_span_list(
+        self,
+        dedup_span_list: bool,
+    ) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.with_dedup_span_list(dedup_span_list),
+            ..self
+        }
+    }
+
+    /// Sets whether runs of consecutive identically-named spans in the
+    /// rendered span list are collapsed into a single `name×N` entry.
+    ///
+    /// See [`Format::with_collapsed_repeats`].
+    ///
+    /// [`Format::with_collapsed_repeats`]: format/struct.Format.html#method.with_collapsed_repeats
+    pub fn with_collapsed_repeats(
+        self,
+        collapse_repeats: bool,
+    ) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.with_collapsed_repeats(collapse_repeats),
+            ..self
+        }
+    }
+
+    /// Sets whether a span field shadowed by an event field of the same
+    /// name is hidden from the rendered span context.
+    ///
+    /// See [`Format::with_field_dedup`].
+    ///
+    /// [`Format::with_field_dedup`]: format/struct.Format.html#method.with_field_dedup
+    pub fn with_field_dedup(
+        self,
+        field_dedup: bool,
+    ) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.with_field_dedup(field_dedup),
+            ..self
+        }
+    }
+
+    /// Attaches a static resource descriptor — such as the build version or
+    /// git commit the running binary was built from — that is rendered on
+    /// every formatted line.
+    ///
+    /// See [`Format::with_resource`].
+    ///
+    /// [`Format::with_resource`]: format/struct.Format.html#method.with_resource
+    pub fn with_resource(self, resource: &'static str) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.with_resource(resource),
+            ..self
+        }
+    }
+
+    /// Stamps a `schema=<version>` field on every formatted line, so a
+    /// log-ingestion pipeline can tell which field layout a given line
+    /// conforms to.
+    ///
+    /// See [`Format::with_schema_version`].
+    ///
+    /// [`Format::with_schema_version`]: format/struct.Format.html#method.with_schema_version
+    pub fn with_schema_version(
+        self,
+        schema_version: &'static str,
+    ) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.with_schema_version(schema_version),
+            ..self
+        }
+    }
+
+    /// Prints the file and line where the current span was created on
+    /// `ERROR`-level events, so an error can be traced back to the call
+    /// site that opened the span it occurred in.
+    ///
+    /// See [`Format::with_error_span_location`].
+    ///
+    /// [`Format::with_error_span_location`]: format/struct.Format.html#method.with_error_span_location
+    pub fn with_error_span_location(
+        self,
+        error_span_location: bool,
+    ) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.with_error_span_location(error_span_location),
+            ..self
+        }
+    }
+
+    /// Renders events whose target starts with one of the given prefixes
+    /// without the span context prefix, even when it would otherwise be
+    /// shown.
+    ///
+    /// See [`Format::without_span_context_for`].
+    ///
+    /// [`Format::without_span_context_for`]: format/struct.Format.html#method.without_span_context_for
+    pub fn without_span_context_for<I>(self, targets: I) -> Builder<N, format::Format<L, T>, F, W>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        Builder {
+            fmt_event: self.fmt_event.without_span_context_for(targets),
+            ..self
+        }
+    }
+
+    /// Shows a `migrated=true` indicator on events whose current span was
+    /// created on a different thread than the one the event is being
+    /// recorded on.
+    ///
+    /// See [`Format::with_thread_migration`].
+    ///
+    /// [`Format::with_thread_migration`]: format/struct.Format.html#method.with_thread_migration
+    pub fn with_thread_migration(
+        self,
+        thread_migration: bool,
+    ) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.with_thread_migration(thread_migration),
+            ..self
+        }
+    }
+
+    /// Suffixes events with `[#N]`, where `N` is the number of times this
+    /// event's callsite has fired so far.
+    ///
+    /// See [`Format::with_callsite_counts`].
+    ///
+    /// [`Format::with_callsite_counts`]: format/struct.Format.html#method.with_callsite_counts
+    pub fn with_callsite_counts(
+        self,
+        enabled: bool,
+    ) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.with_callsite_counts(enabled),
+            ..self
+        }
+    }
+
+    /// Renders the level as lowercase (`info`, `warn`, ...) instead of the
+    /// default uppercase, matching the style used by syslog.
+    ///
+    /// See [`Format::with_lowercase_level`].
+    ///
+    /// [`Format::with_lowercase_level`]: format/struct.Format.html#method.with_lowercase_level
+    pub fn with_lowercase_level(
+        self,
+        lowercase: bool,
+    ) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.with_lowercase_level(lowercase),
+            ..self
+        }
+    }
+
+    /// Sets the character sequence written after each formatted event.
+    ///
+    /// See [`Format::with_line_ending`].
+    ///
+    /// [`Format::with_line_ending`]: format/struct.Format.html#method.with_line_ending
+    pub fn with_line_ending(
+        self,
+        line_ending: format::LineEnding,
+    ) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.with_line_ending(line_ending),
+            ..self
+        }
+    }
+
+    /// Strips ANSI escape sequences from rendered field values before
+    /// writing them.
+    ///
+    /// See [`Format::sanitize_ansi_in_fields`].
+    ///
+    /// [`Format::sanitize_ansi_in_fields`]: format/struct.Format.html#method.sanitize_ansi_in_fields
+    pub fn sanitize_ansi_in_fields(
+        self,
+        sanitize: bool,
+    ) -> Builder<N, format::Format<L, T>, F, W> {
+        Builder {
+            fmt_event: self.fmt_event.sanitize_ansi_in_fields(sanitize),
+            ..self
+        }
+    }
 }
 
 #[cfg(feature = "env-filter")]
@@ -416,6 +936,86 @@ where
     }
 }
 
+impl<E, F, W> Builder<format::NewRecorder, E, F, W> {
+    /// Sets the string used to separate rendered fields from one another
+    /// (the default is a single space).
+    ///
+    /// The `message` field, when present, is always rendered first and is
+    /// unaffected by this setting; `separator` only appears *between*
+    /// fields, including between `message` and the first field that follows
+    /// it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tracing_subscriber::FmtSubscriber;
+    ///
+    /// let subscriber = FmtSubscriber::builder()
+    ///     .with_field_separator(" | ")
+    ///     .finish();
+    /// ```
+    pub fn with_field_separator(self, separator: &'static str) -> Self {
+        Builder {
+            new_visitor: self.new_visitor.with_separator(separator),
+            fmt_event: self.fmt_event,
+            filter: self.filter,
+            settings: self.settings,
+            make_writer: self.make_writer,
+        }
+    }
+
+    /// Sets whether the `log.target`/`log.module_path`/`log.file`/`log.line`
+    /// fields a `tracing-log`-normalized `log` record carries are rendered
+    /// as ordinary fields, rather than consumed silently.
+    ///
+    /// These fields exist so the standard columns (target, file, line) can
+    /// be populated from the original `log` record; once that's done,
+    /// they're redundant and are dropped by default. Enabling this keeps
+    /// them visible, which is useful when something downstream still wants
+    /// to inspect them directly.
+    ///
+    /// Defaults to `false`. Has no effect unless the `tracing-log` feature
+    /// is enabled.
+    pub fn with_log_internal_fields(self, keep: bool) -> Self {
+        Builder {
+            new_visitor: self.new_visitor.with_keep_log_fields(keep),
+            fmt_event: self.fmt_event,
+            filter: self.filter,
+            settings: self.settings,
+            make_writer: self.make_writer,
+        }
+    }
+
+    /// Promotes `field` to render in the `message` position — unquoted,
+    /// rather than as `name=value` — for any event that has no literal
+    /// `message` field of its own.
+    ///
+    /// This is useful for field-only events, such as
+    /// `event!(Level::INFO, error = %e)`, which otherwise render with no
+    /// leading message text at all. If the named field and a literal
+    /// `message` both appear on the same event, `message` wins and the
+    /// named field renders normally as `name=value`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tracing_subscriber::FmtSubscriber;
+    ///
+    /// let subscriber = FmtSubscriber::builder()
+    ///     .with_message_field("error")
+    ///     .finish();
+    /// ```
+    pub fn with_message_field(self, field: &'static str) -> Self {
+        Builder {
+            new_visitor: self.new_visitor.with_message_field(field),
+            fmt_event: self.fmt_event,
+            filter: self.filter,
+            settings: self.settings,
+            make_writer: self.make_writer,
+        }
+    }
+}
+
 impl<N, E, F, W> Builder<N, E, F, W> {
     /// Sets the Visitor that the subscriber being built will use to record
     /// fields.
@@ -610,13 +1210,179 @@ impl<N, E, F, W> Builder<N, E, F, W> {
         }
     }
 
-    // TODO(eliza): should this be publicly exposed?
-    // /// Configures the initial capacity for the span slab used to store
-    // /// in-progress span data. This may be used for tuning the subscriber's
-    // /// allocation performance, but in general does not need to be manually configured..
-    // pub fn initial_span_capacity(self, initial_span_capacity: usize) -> Self {
-    //     Builder {
-    //         settings: Settings {
+    /// Disables span tracking entirely.
+    ///
+    /// By default, the constructed subscriber records span metadata and
+    /// fields in an internal slab so that event output can include the
+    /// current span context. For event-only workloads that never format or
+    /// otherwise rely on span context, this bookkeeping is pure overhead.
+    ///
+    /// When this is set, `new_span`, `enter`, `exit`, and `record` become
+    /// no-ops, every span is assigned the same placeholder `Id`, and
+    /// [`Context::visit_spans`] and [`Context::with_current`] will never
+    /// observe a span, even though `tracing::span!` and `#[instrument]`
+    /// continue to work without error.
+    ///
+    /// [`Context::visit_spans`]: span/struct.Context.html#method.visit_spans
+    /// [`Context::with_current`]: span/struct.Context.html#method.with_current
+    pub fn without_spans(self) -> Self {
+        Builder {
+            settings: Settings {
+                span_tracking: false,
+                ..self.settings
+            },
+            ..self
+        }
+    }
+
+    /// Enables logging a line when a span closes, recording how long it was
+    /// open.
+    ///
+    /// By default, no such line is logged. When enabled, the duration is
+    /// rendered using `Duration`'s `Debug` implementation; pass
+    /// `human_readable: true` to render it instead as a short string using
+    /// the largest sensible unit (e.g. `2.50ms` rather than `2.5ms`, or
+    /// `1.20s` rather than `1.204918s`).
+    pub fn with_span_close_timing(self, human_readable: bool) -> Self {
+        Builder {
+            settings: Settings {
+                close_duration: if human_readable {
+                    CloseDuration::Human
+                } else {
+                    CloseDuration::Raw
+                },
+                ..self.settings
+            },
+            ..self
+        }
+    }
+
+    /// Enables logging the source file and line where each span was created.
+    ///
+    /// By default, no such information is logged. When enabled, the line
+    /// logged when a span opens (i.e. when it is first entered) includes
+    /// `opened at <file>:<line>`, taken from the span's [`Metadata`].
+    ///
+    /// [`Metadata`]: https://docs.rs/tracing-core/0.1.5/tracing_core/struct.Metadata.html
+    pub fn with_span_location(self, display_location: bool) -> Self {
+        Builder {
+            settings: Settings {
+                span_location: display_location,
+                ..self.settings
+            },
+            ..self
+        }
+    }
+
+    /// Installs a panic hook that reports panics as `ERROR`-level tracing
+    /// events when [`finish`] is called.
+    ///
+    /// Panics normally bypass `tracing` entirely. When this is enabled, a
+    /// panic on any thread is reported as an event (with target and name
+    /// `"panic"`, carrying the panic message and source location) in
+    /// whatever span was current on the panicking thread, before chaining to
+    /// the panic hook that was previously installed.
+    ///
+    /// See [`crate::panics::install_panic_hook`] for details.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`finish`]: #method.finish
+    pub fn capture_panics(self, capture_panics: bool) -> Self {
+        Builder {
+            settings: Settings {
+                capture_panics,
+                ..self.settings
+            },
+            ..self
+        }
+    }
+
+    /// Retries a write up to `attempts` times, sleeping `backoff` between
+    /// attempts, when the [`MakeWriter`]'s writer fails with
+    /// [`io::ErrorKind::WouldBlock`] — for example, a non-blocking socket
+    /// that isn't ready yet, or the `MockWriter` used in this crate's own
+    /// tests under lock contention.
+    ///
+    /// `attempts` is the total number of attempts, including the first; `1`
+    /// (the default) disables retrying, matching prior behavior where a
+    /// `WouldBlock` silently drops the event. A `WouldBlock` on the final
+    /// attempt is still dropped silently — this bounds the retry to avoid
+    /// blocking indefinitely on a writer that never becomes ready.
+    ///
+    /// [`MakeWriter`]: writer::MakeWriter
+    /// [`io::ErrorKind::WouldBlock`]: std::io::ErrorKind::WouldBlock
+    pub fn with_span_retry(self, attempts: usize, backoff: std::time::Duration) -> Self {
+        Builder {
+            settings: Settings {
+                write_retry: WriteRetry {
+                    max_attempts: attempts,
+                    backoff,
+                },
+                ..self.settings
+            },
+            ..self
+        }
+    }
+
+    /// Interns span [`Metadata`](tracing_core::Metadata) by content (name,
+    /// target, level, source location, and field names) rather than by
+    /// callsite identity, so spans that are logically identical but were
+    /// emitted from separate callsites — most commonly, the same
+    /// `#[instrument]`d generic function monomorphized for many type
+    /// parameters — share a single `Metadata` reference in this
+    /// subscriber's span registry.
+    ///
+    /// This only reduces how many distinct `Metadata` pointers the registry
+    /// itself tracks; it can't reclaim the memory the monomorphized statics
+    /// occupy in the binary, since that's fixed by the compiler and linker
+    /// before the subscriber ever runs. It's a small, opt-in win for
+    /// processes that keep very many spans open at once across pervasively
+    /// monomorphized instrumentation.
+    ///
+    /// Defaults to `false`. Every span accessor (`name`, `metadata`,
+    /// `fields`, `file`, `line`, `parent`, `elapsed`, `created_thread`)
+    /// keeps working exactly as before; only which `Metadata` pointer it
+    /// reads from is affected.
+    pub fn with_interned_metadata(self, intern_metadata: bool) -> Self {
+        Builder {
+            settings: Settings {
+                intern_metadata,
+                ..self.settings
+            },
+            ..self
+        }
+    }
+
+    /// Sets the [`Utf8Policy`] used when converting a formatted line to
+    /// bytes before handing it to the [`MakeWriter`].
+    ///
+    /// Every formatter in this crate builds its output as a `String`,
+    /// which is valid UTF-8 by construction, so this only has an
+    /// observable effect on a custom [`FormatEvent`] implementation that
+    /// constructs its buffer from raw, possibly non-UTF8 bytes. Defaults
+    /// to [`Utf8Policy::Lossy`].
+    ///
+    /// [`Utf8Policy`]: writer::Utf8Policy
+    /// [`MakeWriter`]: writer::MakeWriter
+    /// [`FormatEvent`]: format::FormatEvent
+    pub fn with_invalid_utf8(self, policy: writer::Utf8Policy) -> Self {
+        Builder {
+            settings: Settings {
+                invalid_utf8_policy: policy,
+                ..self.settings
+            },
+            ..self
+        }
+    }
+
+    // TODO(eliza): should this be publicly exposed?
+    // /// Configures the initial capacity for the span slab used to store
+    // /// in-progress span data. This may be used for tuning the subscriber's
+    // /// allocation performance, but in general does not need to be manually configured..
+    // pub fn initial_span_capacity(self, initial_span_capacity: usize) -> Self {
+    //     Builder {
+    //         settings: Settings {
     //             initial_span_capacity,
     //             ..self.settings
     //         },
@@ -653,6 +1419,122 @@ impl<N, E, F, W> Builder<N, E, F, W> {
             make_writer,
         }
     }
+
+    /// Routes `ERROR`-level events to `error_writer`, leaving everything
+    /// else going to whatever [`MakeWriter`] is already set (by default,
+    /// `stdout`).
+    ///
+    /// This is sugar for the common "errors to stderr, the rest to stdout"
+    /// split, without reaching for a custom [`MakeWriter`] combinator:
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// let subscriber = tracing_subscriber::fmt::Subscriber::builder()
+    ///     .with_writer(io::stdout)
+    ///     .with_error_writer(io::stderr)
+    ///     .finish();
+    /// ```
+    ///
+    /// For routing on more than `ERROR`, or on something other than level,
+    /// implement [`MakeWriter::make_writer_for`] directly.
+    ///
+    /// [`MakeWriter`]: trait.MakeWriter.html
+    /// [`MakeWriter::make_writer_for`]: trait.MakeWriter.html#method.make_writer_for
+    pub fn with_error_writer<W2>(
+        self,
+        error_writer: W2,
+    ) -> Builder<N, E, F, self::writer::ErrorRoutedWriter<W, W2>>
+    where
+        W: MakeWriter + 'static,
+        W::Writer: 'static,
+        W2: MakeWriter + 'static,
+        W2::Writer: 'static,
+    {
+        Builder {
+            new_visitor: self.new_visitor,
+            fmt_event: self.fmt_event,
+            filter: self.filter,
+            settings: self.settings,
+            make_writer: self::writer::ErrorRoutedWriter::new(self.make_writer, error_writer),
+        }
+    }
+
+    /// Sets the [`MakeWriter`] that the subscriber being built will use to
+    /// write events, wrapping it so that it can be swapped out later via the
+    /// returned [`writer::ReloadHandle`].
+    ///
+    /// This is useful when the final log destination is only known after the
+    /// subscriber has already been constructed and installed (for example,
+    /// after reading configuration). Calling `handle.set_writer(..)` on the
+    /// returned handle redirects all subsequent output; writes already in
+    /// flight against the previous writer run to completion unaffected.
+    ///
+    /// [`MakeWriter`]: trait.MakeWriter.html
+    /// [`writer::ReloadHandle`]: writer/struct.ReloadHandle.html
+    pub fn with_reloadable_writer<W2>(
+        self,
+        make_writer: W2,
+    ) -> (Builder<N, E, F, self::writer::Reload<W2>>, self::writer::ReloadHandle<W2>)
+    where
+        W2: MakeWriter + 'static,
+    {
+        let (make_writer, handle) = self::writer::Reload::new(make_writer);
+        let builder = Builder {
+            new_visitor: self.new_visitor,
+            fmt_event: self.fmt_event,
+            filter: self.filter,
+            settings: self.settings,
+            make_writer,
+        };
+        (builder, handle)
+    }
+
+    /// Returns a cloneable snapshot of this builder's formatting
+    /// configuration (its field visitor, event formatter, and toggles), not
+    /// including its filter or writer.
+    ///
+    /// See [`FmtConfig`] and [`Builder::with_config`].
+    pub fn config(&self) -> FmtConfig<N, E>
+    where
+        N: Clone,
+        E: Clone,
+    {
+        FmtConfig {
+            new_visitor: self.new_visitor.clone(),
+            fmt_event: self.fmt_event.clone(),
+            settings: self.settings,
+        }
+    }
+
+    /// Applies a [`FmtConfig`] captured by [`Builder::config`], replacing
+    /// this builder's field visitor, event formatter, and toggles. The
+    /// filter and writer are left unchanged.
+    ///
+    /// This is useful for configuring several builders that log to
+    /// different destinations identically, varying only the writer:
+    ///
+    /// ```rust
+    /// use tracing_subscriber::fmt::Subscriber;
+    ///
+    /// let shared = Subscriber::builder().with_ansi(false).config();
+    /// let console = Subscriber::builder()
+    ///     .with_config(shared.clone())
+    ///     .with_writer(std::io::stdout);
+    /// let file = Subscriber::builder()
+    ///     .with_config(shared)
+    ///     .with_writer(std::io::stderr);
+    /// # let _ = (console.finish(), file.finish());
+    /// ```
+    pub fn with_config<N2, E2>(self, config: FmtConfig<N2, E2>) -> Builder<N2, E2, F, W> {
+        Builder {
+            new_visitor: config.new_visitor,
+            fmt_event: config.fmt_event,
+            filter: self.filter,
+            settings: config.settings,
+            make_writer: self.make_writer,
+        }
+    }
 }
 
 impl Default for Settings {
@@ -660,6 +1542,13 @@ impl Default for Settings {
         Self {
             inherit_fields: false,
             initial_span_capacity: 32,
+            span_tracking: true,
+            close_duration: CloseDuration::Off,
+            span_location: false,
+            invalid_utf8_policy: writer::Utf8Policy::default(),
+            capture_panics: false,
+            write_retry: WriteRetry::default(),
+            intern_metadata: false,
         }
     }
 }
@@ -684,6 +1573,1147 @@ mod test {
         let _dispatch = Dispatch::new(subscriber);
     }
 
+    #[test]
+    fn reload_writer_mid_stream() {
+        use std::sync::{Arc, Mutex};
+
+        fn make_writer(buf: Arc<Mutex<Vec<u8>>>) -> impl Fn() -> Box<dyn std::io::Write> {
+            move || Box::new(BufWriter(buf.clone())) as Box<dyn std::io::Write>
+        }
+
+        struct BufWriter(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for BufWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buf1 = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = Arc::new(Mutex::new(Vec::new()));
+
+        let (builder, handle) = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_reloadable_writer(make_writer(buf1.clone()));
+        let subscriber = builder.finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("to the first buffer");
+        });
+        assert!(!buf1.lock().unwrap().is_empty());
+        assert!(buf2.lock().unwrap().is_empty());
+
+        handle.set_writer(make_writer(buf2.clone()));
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("to the second buffer");
+        });
+        let buf1_len = buf1.lock().unwrap().len();
+        assert!(!buf2.lock().unwrap().is_empty());
+        // No more data was written to the first buffer after reload.
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("still the second buffer");
+        });
+        assert_eq!(buf1.lock().unwrap().len(), buf1_len);
+    }
+
+    #[test]
+    fn without_spans_skips_span_tracking() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .without_spans()
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("a_span");
+            let _enter = span.enter();
+            tracing::info!("inside a span that is not actually tracked");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(!output.contains("a_span"));
+        assert!(output.contains("inside a span that is not actually tracked"));
+    }
+
+    #[test]
+    fn span_close_timing_is_human_readable() {
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+        use std::time::Duration;
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_span_close_timing(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("timed_span");
+            let _enter = span.enter();
+            thread::sleep(Duration::from_millis(1));
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("close timed_span: time.busy="));
+    }
+
+    #[test]
+    fn span_close_timing_reports_both_busy_and_idle() {
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+        use std::time::Duration;
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_span_close_timing(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let parent = tracing::info_span!("parent_span");
+            let _parent_enter = parent.enter();
+            {
+                let child = tracing::info_span!("child_span");
+                let _child_enter = child.enter();
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("close child_span: time.busy="));
+        assert!(output.contains("close parent_span: time.busy="));
+        assert!(output.contains("time.idle="));
+    }
+
+    #[test]
+    fn with_resource_renders_on_every_line() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_resource("version=1.2.3 git=abcd123")
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("hello");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("version=1.2.3 git=abcd123"));
+    }
+
+    #[test]
+    fn suppressing_current_span_and_span_list_hides_all_span_context() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_current_span(false)
+            .with_span_list(false)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("my_span", answer = 42);
+            let _enter = span.enter();
+            tracing::info!("hello");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(!output.contains("my_span"));
+        assert!(!output.contains("answer"));
+        assert!(output.contains("hello"));
+    }
+
+    #[test]
+    fn dedup_span_list_collapses_consecutive_same_named_spans() {
+        use std::sync::{Arc, Mutex};
+
+        fn recurse(depth: usize) {
+            let span = tracing::info_span!("recurse");
+            let _enter = span.enter();
+            if depth > 0 {
+                recurse(depth - 1);
+            } else {
+                tracing::info!("bottomed out");
+            }
+        }
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_dedup_span_list(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            recurse(3);
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        let line = output.lines().last().unwrap();
+        assert_eq!(line.matches("recurse").count(), 1);
+    }
+
+    #[test]
+    fn collapsed_repeats_shows_a_count_instead_of_dropping_entries() {
+        use std::sync::{Arc, Mutex};
+
+        fn recurse(depth: usize) {
+            let span = tracing::info_span!("retry");
+            let _enter = span.enter();
+            if depth > 0 {
+                recurse(depth - 1);
+            } else {
+                let db = tracing::info_span!("db");
+                let _enter = db.enter();
+                tracing::info!("bottomed out");
+            }
+        }
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_collapsed_repeats(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            recurse(2);
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        let line = output.lines().last().unwrap();
+        assert!(line.contains("retry×3:db:"));
+    }
+
+    #[test]
+    fn schema_version_is_stamped_on_every_line() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_schema_version("2")
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("hello");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("schema=2 "));
+    }
+
+    #[test]
+    fn error_span_location_is_printed_for_error_events_in_a_span() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_error_span_location(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("db_query");
+            let _enter = span.enter();
+            tracing::error!("boom");
+            tracing::warn!("not an error, no location");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].contains(&format!("span opened at {}:", file!())));
+        assert!(!lines[1].contains("span opened at"));
+    }
+
+    #[test]
+    fn error_span_location_is_silent_by_default() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("db_query");
+            let _enter = span.enter();
+            tracing::error!("boom");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(!output.contains("span opened at"));
+    }
+
+    #[test]
+    fn capture_panics_logs_a_panic_as_an_error_event() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .capture_panics(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        // Run on a child thread so the panic is contained there. The panic
+        // hook this installs is process-wide, so the default hook is
+        // restored once the thread has finished.
+        let handle = std::thread::spawn(move || {
+            tracing_core::dispatcher::with_default(&dispatch, || {
+                let _ = std::panic::catch_unwind(|| {
+                    panic!("kaboom");
+                });
+            });
+        });
+        handle.join().unwrap();
+        let _ = std::panic::take_hook();
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("ERROR"));
+        assert!(output.contains("kaboom"));
+    }
+
+    #[test]
+    fn span_retry_recovers_from_transient_would_block_errors() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let attempts2 = attempts.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_span_retry(3, std::time::Duration::from_millis(0))
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>, Arc<AtomicUsize>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        // The first two writes fail as if the underlying
+                        // sink (e.g. a non-blocking socket) weren't ready
+                        // yet; the third succeeds.
+                        if self.1.fetch_add(1, Ordering::SeqCst) < 2 {
+                            return Err(std::io::Error::from(std::io::ErrorKind::WouldBlock));
+                        }
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone(), attempts2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::error!("retried event");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("retried event"));
+    }
+
+    #[test]
+    fn span_retry_defaults_to_no_retry() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let attempts2 = attempts.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>, Arc<AtomicUsize>);
+                impl std::io::Write for W {
+                    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                        self.1.fetch_add(1, Ordering::SeqCst);
+                        Err(std::io::Error::from(std::io::ErrorKind::WouldBlock))
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone(), attempts2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::error!("dropped event");
+        });
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert!(buf.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn thread_migration_is_flagged_when_a_span_is_entered_on_another_thread() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_thread_migration(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            // Create the span on this (the "worker") thread, but log inside
+            // it after moving to a different thread — exactly what happens
+            // when a work-stealing executor resumes a task's continuation on
+            // whatever thread is free.
+            let span = tracing::info_span!("task");
+            let dispatch = dispatch.clone();
+            std::thread::spawn(move || {
+                // The default dispatcher is set per thread, so the new thread
+                // is pointed at the same subscriber.
+                tracing_core::dispatcher::with_default(&dispatch, || {
+                    let _enter = span.enter();
+                    tracing::info!("resumed elsewhere");
+                })
+            })
+            .join()
+            .unwrap();
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("migrated=true"));
+        assert!(output.contains("resumed elsewhere"));
+    }
+
+    #[test]
+    fn thread_migration_is_silent_when_entered_on_the_creating_thread() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_thread_migration(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("task");
+            let _enter = span.enter();
+            tracing::info!("still on the same thread");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(!output.contains("migrated=true"));
+    }
+
+    #[test]
+    fn span_context_is_suppressed_only_for_excluded_targets() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .without_span_context_for(&["noisy_lib"])
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("our_work");
+            let _enter = span.enter();
+            tracing::info!(target: "noisy_lib::inner", "chatter");
+            tracing::info!(target: "our_crate", "important");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(!lines[0].contains("our_work"));
+        assert!(lines[1].contains("our_work"));
+    }
+
+    #[test]
+    fn sanitize_ansi_in_fields_strips_escape_sequences() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .sanitize_ansi_in_fields(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!(output = %"\u{1b}[31mred\u{1b}[0m text", "subprocess said hi");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        // The timestamp column is wrapped in dimming escapes even with
+        // `with_ansi(false)`, so only the rendered fields are checked.
+        let fields = &output[output.find("subprocess said hi").unwrap()..];
+        assert!(!fields.contains('\u{1b}'));
+        assert!(output.contains("red"));
+        assert!(output.contains("text"));
+    }
+
+    #[test]
+    fn field_separator_joins_fields_with_the_configured_string() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_field_separator(" | ")
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!(a = 1, b = 2, "hi");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("hi | a=1 | b=2"));
+    }
+
+    #[test]
+    #[cfg(feature = "tracing-log")]
+    fn with_log_internal_fields_keeps_the_normalized_log_fields() {
+        use std::sync::{Arc, Mutex};
+
+        let _ = tracing_log::LogTracer::init();
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_log_internal_fields(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            log::info!("hello from log");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("log.target"));
+    }
+
+    #[test]
+    fn error_writer_receives_only_error_level_events() {
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone)]
+        struct W(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for W {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let main_buf = Arc::new(Mutex::new(Vec::new()));
+        let error_buf = Arc::new(Mutex::new(Vec::new()));
+        let main_writer = W(main_buf.clone());
+        let error_writer = W(error_buf.clone());
+
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || main_writer.clone())
+            .with_error_writer(move || error_writer.clone())
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("everything is fine");
+            tracing::error!("everything is on fire");
+        });
+
+        let main_output = String::from_utf8(main_buf.lock().unwrap().clone()).unwrap();
+        let error_output = String::from_utf8(error_buf.lock().unwrap().clone()).unwrap();
+
+        assert!(main_output.contains("everything is fine"));
+        assert!(!main_output.contains("everything is on fire"));
+        assert!(error_output.contains("everything is on fire"));
+        assert!(!error_output.contains("everything is fine"));
+    }
+
+    #[test]
+    fn lowercase_level_renders_syslog_style() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_lowercase_level(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::warn!("uh oh");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("warn"));
+        assert!(!output.contains("WARN"));
+    }
+
+    #[test]
+    fn message_control_chars_are_escaped() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("{}", "line one\nERROR forged_field=1\tvalue");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        // The only newline left is the line ending written after the event.
+        assert_eq!(output.matches('\n').count(), 1);
+        assert!(output.contains("line one\\nERROR forged_field=1\\tvalue"));
+    }
+
+    #[test]
+    fn with_config_shares_formatting_across_builders() {
+        use std::sync::{Arc, Mutex};
+
+        struct W(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for W {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let shared = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_lowercase_level(true)
+            .config();
+
+        let buf1 = Arc::new(Mutex::new(Vec::new()));
+        let buf1_clone = buf1.clone();
+        let subscriber1 = Subscriber::builder()
+            .with_config(shared.clone())
+            .with_writer(move || -> Box<dyn std::io::Write> { Box::new(W(buf1_clone.clone())) })
+            .finish();
+
+        let buf2 = Arc::new(Mutex::new(Vec::new()));
+        let buf2_clone = buf2.clone();
+        let subscriber2 = Subscriber::builder()
+            .with_config(shared)
+            .with_writer(move || -> Box<dyn std::io::Write> { Box::new(W(buf2_clone.clone())) })
+            .finish();
+
+        tracing_core::dispatcher::with_default(&Dispatch::new(subscriber1), || {
+            tracing::warn!("to the console");
+        });
+        tracing_core::dispatcher::with_default(&Dispatch::new(subscriber2), || {
+            tracing::warn!("to the file");
+        });
+
+        let out1 = String::from_utf8(buf1.lock().unwrap().clone()).unwrap();
+        let out2 = String::from_utf8(buf2.lock().unwrap().clone()).unwrap();
+        // Both builders share the same formatting config (no ANSI, lowercase
+        // levels), and differ only in where they write.
+        assert!(out1.contains("warn") && !out1.contains("WARN"));
+        assert!(out2.contains("warn") && !out2.contains("WARN"));
+    }
+
+    #[test]
+    fn line_ending_controls_the_terminator() {
+        use std::sync::{Arc, Mutex};
+
+        fn captured(line_ending: format::LineEnding) -> String {
+            let buf = Arc::new(Mutex::new(Vec::new()));
+            let buf2 = buf.clone();
+            let subscriber = Subscriber::builder()
+                .without_time()
+                .with_ansi(false)
+                .with_line_ending(line_ending)
+                .with_writer(move || -> Box<dyn std::io::Write> {
+                    struct W(Arc<Mutex<Vec<u8>>>);
+                    impl std::io::Write for W {
+                        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                            self.0.lock().unwrap().extend_from_slice(buf);
+                            Ok(buf.len())
+                        }
+                        fn flush(&mut self) -> std::io::Result<()> {
+                            Ok(())
+                        }
+                    }
+                    Box::new(W(buf2.clone()))
+                })
+                .finish();
+            let dispatch = Dispatch::new(subscriber);
+
+            tracing_core::dispatcher::with_default(&dispatch, || {
+                tracing::info!("hello");
+            });
+
+            let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+            out
+        }
+
+        assert!(captured(format::LineEnding::Lf).ends_with('\n'));
+        assert!(!captured(format::LineEnding::Lf).ends_with("\r\n"));
+        assert!(captured(format::LineEnding::CrLf).ends_with("\r\n"));
+        assert!(!captured(format::LineEnding::None).ends_with('\n'));
+        assert!(captured(format::LineEnding::None).ends_with("hello"));
+    }
+
+    #[test]
+    fn span_location_is_logged_when_enabled() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_span_location(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info_span!("my_span");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(out.contains("my_span opened at"));
+        assert!(out.contains(file!()));
+    }
+
+    #[test]
+    fn span_location_is_silent_by_default() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info_span!("my_span");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(!out.contains("opened at"));
+    }
+
+    #[test]
+    fn invalid_utf8_policy_hex_escapes_bytes_from_an_unsafe_format_event() {
+        use std::sync::{Arc, Mutex};
+
+        struct RawBytesFormatter;
+        impl<N> format::FormatEvent<N> for RawBytesFormatter {
+            fn format_event(
+                &self,
+                _ctx: &span::Context<'_, N>,
+                writer: &mut dyn std::fmt::Write,
+                _event: &Event<'_>,
+            ) -> std::fmt::Result {
+                // SAFETY: intentionally not safe — this exercises the
+                // `Utf8Policy` enforced at the writer boundary, which is
+                // the only thing standing between this and invalid UTF-8
+                // reaching the `MakeWriter`.
+                let invalid = unsafe { std::str::from_utf8_unchecked(&[b'h', b'i', 0xff]) };
+                writer.write_str(invalid)
+            }
+        }
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .on_event(RawBytesFormatter)
+            .with_invalid_utf8(writer::Utf8Policy::HexEscape)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("ignored");
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert_eq!(out, "hi\\xff");
+    }
+
+    #[test]
+    fn try_init_errors_on_second_call() {
+        // `try_init`/`set_global_default` installs a process-wide global,
+        // so this relies on being the only test in the process that installs
+        // one successfully; run in isolation if it becomes flaky under a
+        // test harness that shares a process with other `try_init` callers.
+        let first = Subscriber::builder().try_init();
+        let second = Subscriber::builder().try_init();
+
+        assert!(first.is_ok() || second.is_ok());
+        assert!(first.is_err() || second.is_err());
+    }
+
+    #[test]
+    fn finish_dispatch_returns_a_usable_dispatch() {
+        let dispatch = Subscriber::builder().finish_dispatch();
+        assert!(dispatch.downcast_ref::<Subscriber>().is_some());
+    }
+
     #[test]
     fn subscriber_downcasts() {
         let subscriber = Subscriber::new();
@@ -699,4 +2729,164 @@ mod test {
         assert!(dispatch.downcast_ref::<LevelFilter>().is_some());
         assert!(dispatch.downcast_ref::<format::Format>().is_some())
     }
+
+    #[test]
+    fn callsite_counts_number_repeated_events_from_the_same_callsite() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_callsite_counts(true)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            for _ in 0..3 {
+                tracing::info!("hot line");
+            }
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("[#1]"));
+        assert!(lines[1].ends_with("[#2]"));
+        assert!(lines[2].ends_with("[#3]"));
+    }
+
+    #[test]
+    fn callsite_counts_are_off_by_default() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("plain line");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(!output.contains("[#"));
+    }
+
+    #[test]
+    fn with_message_field_promotes_the_named_field_when_no_message_is_present() {
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .without_time()
+            .with_ansi(false)
+            .with_target(false)
+            .with_message_field("error")
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(subscriber);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::event!(tracing::Level::ERROR, error = %"disk full");
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(
+            output.contains("disk full\n") || output.ends_with("disk full\n"),
+            "expected the `error` field to render in the message position, got: {:?}",
+            output
+        );
+        assert!(
+            !output.contains("error=disk full") && !output.contains("error=\"disk full\""),
+            "the promoted field should not also render as `name=value`, got: {:?}",
+            output
+        );
+    }
+
+    #[test]
+    fn compact_formatter_renders_terse_span_lifecycle_markers() {
+        use crate::prelude::*;
+        use crate::span_events::SpanEventsLayer;
+        use std::sync::{Arc, Mutex};
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let buf2 = buf.clone();
+        let subscriber = Subscriber::builder()
+            .compact()
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || -> Box<dyn std::io::Write> {
+                struct W(Arc<Mutex<Vec<u8>>>);
+                impl std::io::Write for W {
+                    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                        self.0.lock().unwrap().extend_from_slice(buf);
+                        Ok(buf.len())
+                    }
+                    fn flush(&mut self) -> std::io::Result<()> {
+                        Ok(())
+                    }
+                }
+                Box::new(W(buf2.clone()))
+            })
+            .finish();
+        let dispatch = Dispatch::new(
+            subscriber
+                .with(SpanEventsLayer::new(tracing_core::Level::INFO).with_compact_markers(true)),
+        );
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("task");
+            let _enter = span.enter();
+            drop(_enter);
+            drop(span);
+        });
+
+        let output = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("marker=\"+span\""));
+        assert!(output.contains("marker=\"-span\""));
+    }
 }
@@ -1,14 +1,16 @@
 use std::{
     cell::RefCell,
+    collections::HashMap,
     fmt, mem, str,
-    sync::atomic::{self, AtomicUsize, Ordering},
+    sync::atomic::{self, AtomicU64, AtomicUsize, Ordering},
+    time::{Duration, Instant},
 };
 
 use crate::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use owning_ref::OwningHandle;
 
 pub(crate) use tracing_core::span::{Attributes, Current, Id, Record};
-use tracing_core::{dispatcher, Metadata};
+use tracing_core::{dispatcher, Level, Metadata};
 
 pub struct Span<'a> {
     lock: OwningHandle<RwLockReadGuard<'a, Slab>, RwLockReadGuard<'a, Slot>>,
@@ -35,6 +37,69 @@ pub(crate) struct Store {
 
     // The head of the slab's "free list".
     next: AtomicUsize,
+
+    // Canonicalizes `Metadata` that describe logically identical spans (same
+    // name, target, level, source location, and field names) but originate
+    // from distinct callsites — most commonly, the same `#[instrument]`d
+    // generic function monomorphized for several type parameters, each
+    // monomorphization getting its own static `Metadata`. `None` unless
+    // interning was requested via `Builder::with_interned_metadata`.
+    interned: Option<RwLock<HashMap<MetadataShape, &'static Metadata<'static>>>>,
+}
+
+/// A content-based key identifying the "shape" of a [`Metadata`]: its name,
+/// target, level, source location, and field names, but not its callsite
+/// identity. Two callsites with an equal `MetadataShape` describe spans that
+/// are indistinguishable once opened.
+///
+/// `Level` isn't `Hash`, so it's deliberately excluded from the `Hash` impl
+/// and checked only in `Eq`; that's sound (equal keys still hash equal) and
+/// just means two `MetadataShape`s differing only in level share a hash
+/// bucket, which is harmless for this cache's size.
+#[derive(Debug)]
+struct MetadataShape {
+    name: &'static str,
+    target: &'static str,
+    level: Level,
+    file: Option<&'static str>,
+    line: Option<u32>,
+    fields: Vec<&'static str>,
+}
+
+impl MetadataShape {
+    fn of(metadata: &'static Metadata<'static>) -> Self {
+        Self {
+            name: metadata.name(),
+            target: metadata.target(),
+            level: metadata.level().clone(),
+            file: metadata.file(),
+            line: metadata.line(),
+            fields: metadata.fields().iter().map(|f| f.name()).collect(),
+        }
+    }
+}
+
+impl PartialEq for MetadataShape {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.target == other.target
+            && self.level == other.level
+            && self.file == other.file
+            && self.line == other.line
+            && self.fields == other.fields
+    }
+}
+
+impl Eq for MetadataShape {}
+
+impl std::hash::Hash for MetadataShape {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.target.hash(state);
+        self.file.hash(state);
+        self.line.hash(state);
+        self.fields.hash(state);
+    }
 }
 
 #[derive(Debug)]
@@ -43,6 +108,18 @@ pub(crate) struct Data {
     metadata: &'static Metadata<'static>,
     ref_count: AtomicUsize,
     is_empty: bool,
+    created: Instant,
+    created_thread: std::thread::ThreadId,
+    // Busy time accumulated so far across all completed enter/exit pairs,
+    // in nanoseconds. Entering a child span pauses the parent's timer (see
+    // `Store::push`/`Store::pop`), so this only ever accounts for time
+    // during which this span, specifically, was the innermost one on the
+    // thread's span stack -- i.e. the span's own "self time", excluding
+    // time attributed to any of its children.
+    busy_nanos: AtomicU64,
+    // The instant this span was most recently entered, if it is currently
+    // the innermost entered span on some thread's stack.
+    entered_at: RwLock<Option<Instant>>,
 }
 
 #[derive(Debug)]
@@ -93,10 +170,35 @@ impl<'a> Span<'a> {
         }
     }
 
+    /// Returns this span's fields, pre-rendered as a string by the
+    /// [`NewVisitor`] passed to [`Store::new_span`]/[`Store::record`] at the
+    /// time they were recorded.
+    ///
+    /// Like event fields, a span field recorded via `field::display(..)`
+    /// (the `%value` shorthand) renders with its `Display` form rather than
+    /// its `Debug` form: spans and events are recorded through the same
+    /// [`NewVisitor`] (by default, [`format::NewRecorder`]), so the same
+    /// `Debug`-delegates-to-`Display` trick that preserves the distinction
+    /// for event fields applies here too, with no special-casing needed.
+    ///
+    /// [`format::NewRecorder`]: super::format::NewRecorder
     pub fn fields(&self) -> &str {
         self.lock.fields.as_ref()
     }
 
+    /// Returns the source file where this span was created, if it is known.
+    pub fn file(&self) -> Option<&'static str> {
+        self.metadata().file()
+    }
+
+    /// Returns the line number in [`file`] where this span was created, if
+    /// it is known.
+    ///
+    /// [`file`]: #method.file
+    pub fn line(&self) -> Option<u32> {
+        self.metadata().line()
+    }
+
     pub fn parent(&self) -> Option<&Id> {
         match self.lock.span {
             State::Full(ref data) => data.parent.as_ref(),
@@ -104,6 +206,65 @@ impl<'a> Span<'a> {
         }
     }
 
+    /// Returns how long this span has been open, measured from when it was
+    /// created to now.
+    pub fn elapsed(&self) -> std::time::Duration {
+        match self.lock.span {
+            State::Full(ref data) => data.created.elapsed(),
+            State::Empty(_) => unreachable!(),
+        }
+    }
+
+    /// Returns this span's "self time": the total time it has spent as the
+    /// innermost entered span on some thread's stack, not counting time
+    /// spent inside any of its children.
+    ///
+    /// Entering a child span pauses the parent's busy timer for as long as
+    /// the child remains entered, so this is a true self-time measurement,
+    /// not just `elapsed()` minus one child's time -- it correctly excludes
+    /// time spent in grandchildren, siblings entered by the same parent,
+    /// and so on.
+    pub fn busy(&self) -> std::time::Duration {
+        match self.lock.span {
+            State::Full(ref data) => data.busy(),
+            State::Empty(_) => unreachable!(),
+        }
+    }
+
+    /// Returns how long this span has been open but *not* the innermost
+    /// entered span -- `elapsed()` minus `busy()`.
+    ///
+    /// This is time spent in a child span, plus any time between this
+    /// span's creation, its being entered, its being exited, and its
+    /// eventual close during which nothing on this span's subtree was
+    /// entered at all.
+    pub fn idle(&self) -> std::time::Duration {
+        self.elapsed().saturating_sub(self.busy())
+    }
+
+    pub(crate) fn pause_busy(&self) {
+        match self.lock.span {
+            State::Full(ref data) => data.pause(),
+            State::Empty(_) => {}
+        }
+    }
+
+    pub(crate) fn resume_busy(&self) {
+        match self.lock.span {
+            State::Full(ref data) => data.resume(),
+            State::Empty(_) => {}
+        }
+    }
+
+    /// Returns the [`ThreadId`](std::thread::ThreadId) of the thread this
+    /// span was created on.
+    pub fn created_thread(&self) -> std::thread::ThreadId {
+        match self.lock.span {
+            State::Full(ref data) => data.created_thread,
+            State::Empty(_) => unreachable!(),
+        }
+    }
+
     #[inline(always)]
     fn with_parent<'store, F, E>(
         self,
@@ -225,14 +386,49 @@ fn id_to_idx(id: &Id) -> usize {
 
 impl Store {
     pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self::new(capacity, false)
+    }
+
+    pub(crate) fn new(capacity: usize, intern_metadata: bool) -> Self {
         Store {
             inner: RwLock::new(Slab {
                 slab: Vec::with_capacity(capacity),
             }),
             next: AtomicUsize::new(0),
+            interned: if intern_metadata {
+                Some(RwLock::new(HashMap::new()))
+            } else {
+                None
+            },
         }
     }
 
+    /// Returns `metadata` unchanged, unless metadata interning is enabled and
+    /// an equivalently-shaped `Metadata` (see [`MetadataShape`]) from a
+    /// different callsite was already seen, in which case that earlier one
+    /// is returned instead.
+    ///
+    /// This only reduces how many distinct `Metadata` pointers the *store*
+    /// itself tracks; it can't reclaim the memory occupied by the
+    /// monomorphized statics themselves (that's determined by the compiler
+    /// and linker, not by anything a `Subscriber` does at runtime).
+    fn intern(&self, metadata: &'static Metadata<'static>) -> &'static Metadata<'static> {
+        let interned = match &self.interned {
+            Some(interned) => interned,
+            None => return metadata,
+        };
+
+        if let Some(canonical) = try_lock!(interned.read(), else return metadata)
+            .get(&MetadataShape::of(metadata))
+        {
+            return canonical;
+        }
+
+        *try_lock!(interned.write(), else return metadata)
+            .entry(MetadataShape::of(metadata))
+            .or_insert(metadata)
+    }
+
     #[inline]
     pub(crate) fn current(&self) -> Option<Id> {
         CONTEXT
@@ -241,14 +437,31 @@ impl Store {
     }
 
     pub(crate) fn push(&self, id: &Id) {
-        let _ = CONTEXT.try_with(|current| {
+        let prev = CONTEXT.try_with(|current| {
             let mut current = current.borrow_mut();
             if current.contains(id) {
                 // Ignore duplicate enters.
-                return;
+                return None;
             }
+            let prev = current.last().cloned();
             current.push(self.clone_span(id));
+            Some(prev)
         });
+
+        // Pause the previously-innermost span's busy timer (if there was
+        // one) now that `id` has displaced it, then start `id`'s own timer.
+        // This has to happen outside the `CONTEXT` borrow above, since
+        // `self.get` takes its own locks on the slab.
+        if let Ok(Some(prev)) = prev {
+            if let Some(prev) = prev {
+                if let Some(span) = self.get(&prev) {
+                    span.pause_busy();
+                }
+            }
+            if let Some(span) = self.get(id) {
+                span.resume_busy();
+            }
+        }
     }
 
     pub(crate) fn pop(&self, expected_id: &Id) {
@@ -264,6 +477,21 @@ impl Store {
             .ok()
             .and_then(|i| i);
         if let Some(id) = id {
+            if let Some(span) = self.get(&id) {
+                span.pause_busy();
+            }
+            // Resume whichever span is now innermost, if any -- it was
+            // paused when `id` was entered, and should keep accruing busy
+            // time now that `id` is gone.
+            let new_top = CONTEXT
+                .try_with(|current| current.borrow().last().cloned())
+                .ok()
+                .and_then(|i| i);
+            if let Some(new_top) = new_top {
+                if let Some(span) = self.get(&new_top) {
+                    span.resume_busy();
+                }
+            }
             let _ = self.drop_span(id);
         }
     }
@@ -421,12 +649,46 @@ impl Data {
             attrs.parent().map(|id| store.clone_span(id))
         };
         Self {
-            metadata: attrs.metadata(),
+            metadata: store.intern(attrs.metadata()),
             parent,
             ref_count: AtomicUsize::new(1),
             is_empty: true,
+            created: Instant::now(),
+            created_thread: std::thread::current().id(),
+            busy_nanos: AtomicU64::new(0),
+            entered_at: RwLock::new(None),
+        }
+    }
+
+    /// Pauses this span's busy-time timer, if it is running, adding the
+    /// time since it was last resumed to `busy_nanos`.
+    fn pause(&self) {
+        let mut entered_at = try_lock!(self.entered_at.write());
+        if let Some(start) = entered_at.take() {
+            self.busy_nanos
+                .fetch_add(duration_as_nanos(start.elapsed()), Ordering::Relaxed);
         }
     }
+
+    /// Resumes this span's busy-time timer.
+    fn resume(&self) {
+        *try_lock!(self.entered_at.write()) = Some(Instant::now());
+    }
+
+    /// Returns the total time this span has spent as the innermost entered
+    /// span on some thread's stack, including any time it's currently
+    /// entered for right now.
+    fn busy(&self) -> Duration {
+        let nanos = self.busy_nanos.load(Ordering::Relaxed)
+            + try_lock!(self.entered_at.read(), else return Duration::from_nanos(self.busy_nanos.load(Ordering::Relaxed)))
+                .map(|start| duration_as_nanos(start.elapsed()))
+                .unwrap_or(0);
+        Duration::from_nanos(nanos)
+    }
+}
+
+fn duration_as_nanos(d: Duration) -> u64 {
+    d.as_nanos().min(u128::from(u64::max_value())) as u64
 }
 
 impl Drop for Data {
@@ -577,3 +839,175 @@ impl Slab {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_core::{field::FieldSet, identify_callsite, metadata::Kind};
+
+    struct CallsiteA;
+    static CALLSITE_A: CallsiteA = CallsiteA;
+    impl tracing_core::callsite::Callsite for CallsiteA {
+        fn set_interest(&self, _: tracing_core::subscriber::Interest) {}
+        fn metadata(&self) -> &'static Metadata<'static> {
+            &META_A
+        }
+    }
+
+    struct CallsiteB;
+    static CALLSITE_B: CallsiteB = CallsiteB;
+    impl tracing_core::callsite::Callsite for CallsiteB {
+        fn set_interest(&self, _: tracing_core::subscriber::Interest) {}
+        fn metadata(&self) -> &'static Metadata<'static> {
+            &META_B
+        }
+    }
+
+    // Two distinct statics standing in for the same `#[instrument]`d
+    // function monomorphized for two different type parameters: same name,
+    // target, level, location, and field, but different callsite identity
+    // (and thus different addresses).
+    static META_A: Metadata<'static> = Metadata::new(
+        "monomorphized_fn",
+        "my_crate",
+        tracing_core::Level::INFO,
+        Some("src/lib.rs"),
+        Some(42),
+        None,
+        FieldSet::new(&["arg"], identify_callsite!(&CALLSITE_A)),
+        Kind::SPAN,
+    );
+    static META_B: Metadata<'static> = Metadata::new(
+        "monomorphized_fn",
+        "my_crate",
+        tracing_core::Level::INFO,
+        Some("src/lib.rs"),
+        Some(42),
+        None,
+        FieldSet::new(&["arg"], identify_callsite!(&CALLSITE_B)),
+        Kind::SPAN,
+    );
+    static META_DIFFERENT_LINE: Metadata<'static> = Metadata::new(
+        "monomorphized_fn",
+        "my_crate",
+        tracing_core::Level::INFO,
+        Some("src/lib.rs"),
+        Some(99),
+        None,
+        FieldSet::new(&["arg"], identify_callsite!(&CALLSITE_B)),
+        Kind::SPAN,
+    );
+
+    #[test]
+    fn interning_canonicalizes_equivalently_shaped_metadata() {
+        let store = Store::new(8, true);
+        let first = store.intern(&META_A);
+        let second = store.intern(&META_B);
+        assert_eq!(
+            first as *const _, second as *const _,
+            "equivalently-shaped metadata from different callsites should intern to the same pointer"
+        );
+        assert_eq!(first as *const _, &META_A as *const _);
+    }
+
+    #[test]
+    fn interning_keeps_differently_shaped_metadata_distinct() {
+        let store = Store::new(8, true);
+        let a = store.intern(&META_A);
+        let different = store.intern(&META_DIFFERENT_LINE);
+        assert_ne!(a as *const _, different as *const _);
+    }
+
+    #[test]
+    fn interning_is_a_no_op_when_disabled() {
+        let store = Store::new(8, false);
+        let first = store.intern(&META_A);
+        let second = store.intern(&META_B);
+        // With interning off, each `Metadata` is returned unchanged.
+        assert_eq!(first as *const _, &META_A as *const _);
+        assert_eq!(second as *const _, &META_B as *const _);
+    }
+
+    fn new_root_span(store: &Store, metadata: &'static Metadata<'static>) -> Id {
+        let fields = metadata.fields();
+        let values: [(&tracing_core::field::Field, Option<&dyn tracing_core::field::Value>); 0] =
+            [];
+        let value_set = fields.value_set(&values);
+        let attrs = Attributes::new_root(metadata, &value_set);
+        store.new_span(&attrs, &super::super::format::NewRecorder::new())
+    }
+
+    #[test]
+    fn busy_time_excludes_time_spent_in_a_child_span() {
+        use std::thread;
+
+        let store = Store::with_capacity(8);
+
+        let parent = new_root_span(&store, &META_A);
+        store.push(&parent);
+        {
+            let child = new_root_span(&store, &META_B);
+            store.push(&child);
+            thread::sleep(std::time::Duration::from_millis(5));
+            store.pop(&child);
+        }
+        thread::sleep(std::time::Duration::from_millis(1));
+
+        // Read the timings while the parent is still open (and once again
+        // the innermost entered span, now that its child has exited), since
+        // popping it would close and remove it from the store.
+        let span = store.get(&parent).expect("parent span should still be open");
+        assert!(
+            span.busy() < Duration::from_millis(3),
+            "parent's self time ({:?}) should not include the 5ms its child was entered for",
+            span.busy()
+        );
+        assert!(
+            span.idle() >= Duration::from_millis(5),
+            "parent's idle time ({:?}) should cover the time its child was entered",
+            span.idle()
+        );
+    }
+
+    struct CallsiteC;
+    static CALLSITE_C: CallsiteC = CallsiteC;
+    impl tracing_core::callsite::Callsite for CallsiteC {
+        fn set_interest(&self, _: tracing_core::subscriber::Interest) {}
+        fn metadata(&self) -> &'static Metadata<'static> {
+            &META_C
+        }
+    }
+    static META_C: Metadata<'static> = Metadata::new(
+        "has_a_display_field",
+        "my_crate",
+        tracing_core::Level::INFO,
+        Some("src/lib.rs"),
+        Some(42),
+        None,
+        FieldSet::new(&["value"], identify_callsite!(&CALLSITE_C)),
+        Kind::SPAN,
+    );
+
+    #[test]
+    fn a_display_recorded_span_field_renders_without_debug_quoting() {
+        use tracing_core::field::Value;
+
+        let store = Store::with_capacity(8);
+        let fields = META_C.fields();
+        let value = tracing_core::field::display("hello");
+        let field = fields.field("value").unwrap();
+        let values: [(&tracing_core::field::Field, Option<&dyn Value>); 1] =
+            [(&field, Some(&value as &dyn Value))];
+        let value_set = fields.value_set(&values);
+        let attrs = Attributes::new_root(&META_C, &value_set);
+        let id = store.new_span(&attrs, &super::super::format::NewRecorder::new());
+
+        let span = store.get(&id).expect("span should be open");
+        assert_eq!(
+            span.fields(),
+            "value=hello",
+            "a `%`-recorded span field should render in its `Display` form, \
+             not `Debug`-quoted as `value=\"hello\"`"
+        );
+    }
+}
@@ -57,22 +57,72 @@ pub struct SystemTime;
 /// Retrieve and print the relative elapsed wall-clock time since an epoch.
 ///
 /// The `Default` implementation for `Uptime` makes the epoch the current time.
+///
+/// By default, the elapsed time is rendered the same way it always has been
+/// (seconds space-padded to 4 columns, nanosecond precision). Calling
+/// [`with_width`] and/or [`with_precision`] switches to a zero-padded,
+/// fixed-width rendering instead, so every line's timestamp occupies the
+/// same number of columns — handy for keeping a log's message column
+/// aligned. `with_precision` truncates (rather than rounds) the elapsed
+/// time to the requested number of sub-second digits.
+///
+/// [`with_width`]: #method.with_width
+/// [`with_precision`]: #method.with_precision
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Uptime {
     epoch: Instant,
+    width: Option<usize>,
+    precision: Option<usize>,
 }
 
 impl Default for Uptime {
     fn default() -> Self {
         Uptime {
             epoch: Instant::now(),
+            width: None,
+            precision: None,
         }
     }
 }
 
 impl From<Instant> for Uptime {
     fn from(epoch: Instant) -> Self {
-        Uptime { epoch }
+        Uptime {
+            epoch,
+            width: None,
+            precision: None,
+        }
+    }
+}
+
+impl Uptime {
+    /// Returns a new `Uptime` timer with its epoch set to the current time,
+    /// equivalent to [`Uptime::default`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the minimum width, in columns, of the whole-seconds portion of
+    /// the rendered elapsed time. The value is zero-padded to this width
+    /// rather than space-padded, so columns stay aligned even as the uptime
+    /// grows past the configured width.
+    pub fn with_width(self, width: usize) -> Self {
+        Self {
+            width: Some(width),
+            ..self
+        }
+    }
+
+    /// Sets the number of sub-second digits rendered, from 0 to 9. The
+    /// elapsed nanoseconds are truncated, not rounded, to this many digits.
+    /// Values above 9 (the precision of the underlying `Duration`) are
+    /// clamped to 9. A precision of 0 still renders a single `0` after the
+    /// decimal point.
+    pub fn with_precision(self, precision: usize) -> Self {
+        Self {
+            precision: Some(precision.min(9)),
+            ..self
+        }
     }
 }
 
@@ -91,8 +141,97 @@ impl FormatTime for SystemTime {
 
 impl FormatTime for Uptime {
     fn format_time(&self, w: &mut dyn fmt::Write) -> fmt::Result {
-        let e = self.epoch.elapsed();
-        write!(w, "{:4}.{:09}s ", e.as_secs(), e.subsec_nanos())
+        self.write_elapsed(self.epoch.elapsed(), w)
+    }
+}
+
+impl Uptime {
+    fn write_elapsed(&self, e: std::time::Duration, w: &mut dyn fmt::Write) -> fmt::Result {
+        if self.width.is_none() && self.precision.is_none() {
+            return write!(w, "{:4}.{:09}s ", e.as_secs(), e.subsec_nanos());
+        }
+
+        let width = self.width.unwrap_or(4);
+        let precision = self.precision.unwrap_or(9);
+        let subsec = if precision >= 9 {
+            e.subsec_nanos() as u64
+        } else {
+            e.subsec_nanos() as u64 / 10u64.pow(9 - precision as u32)
+        };
+        write!(
+            w,
+            "{:0width$}.{:0precision$}s ",
+            e.as_secs(),
+            subsec,
+            width = width,
+            precision = precision
+        )
+    }
+}
+
+/// A [`FormatTime`] implementation that always renders a fixed, manually
+/// advanceable timestamp, for deterministic golden-file or snapshot tests
+/// whose expected output embeds a timestamp.
+///
+/// The starting timestamp is parsed once, from an RFC 3339 string, by
+/// [`FixedTime::at`]. [`FixedTime::advance`] moves that timestamp forward by
+/// a given [`Duration`] for all subsequent renders, so tests exercising
+/// elapsed-time behaviour can still be deterministic without touching the
+/// real clock.
+///
+/// Requires the `chrono` feature, which is enabled by default.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "chrono")]
+/// # fn docs() {
+/// use tracing_subscriber::fmt::time::FixedTime;
+///
+/// let timer = FixedTime::at("2020-01-01T00:00:00Z").expect("valid timestamp");
+/// let subscriber = tracing_subscriber::fmt().with_timer(timer);
+/// # }
+/// ```
+#[cfg(feature = "chrono")]
+#[derive(Debug, Clone)]
+pub struct FixedTime {
+    start: chrono::DateTime<chrono::FixedOffset>,
+    advanced: std::sync::Arc<std::sync::Mutex<chrono::Duration>>,
+}
+
+#[cfg(feature = "chrono")]
+impl FixedTime {
+    /// Parses `timestamp` as RFC 3339 (e.g. `"2020-01-01T00:00:00Z"`) and
+    /// returns a `FixedTime` that always renders it, until [`advance`] is
+    /// called.
+    ///
+    /// [`advance`]: FixedTime::advance
+    pub fn at(timestamp: &str) -> chrono::ParseResult<Self> {
+        let start = chrono::DateTime::parse_from_rfc3339(timestamp)?;
+        Ok(Self {
+            start,
+            advanced: std::sync::Arc::new(std::sync::Mutex::new(chrono::Duration::zero())),
+        })
+    }
+
+    /// Moves this timer's rendered timestamp forward by `duration`.
+    ///
+    /// This accumulates: calling `advance` twice moves the timestamp forward
+    /// by the sum of both durations. Cloned `FixedTime`s share the same
+    /// accumulated offset, so a timer can be advanced from outside the
+    /// formatter that holds it.
+    pub fn advance(&self, duration: std::time::Duration) {
+        let mut advanced = try_lock!(self.advanced.lock());
+        *advanced = *advanced
+            + chrono::Duration::from_std(duration).unwrap_or_else(|_| chrono::Duration::zero());
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl FormatTime for FixedTime {
+    fn format_time(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        let advanced = *try_lock!(self.advanced.lock(), else return Ok(()));
+        write!(w, "{} ", (self.start + advanced).format("%b %d %H:%M:%S%.3f"))
     }
 }
 
@@ -117,3 +256,97 @@ where
 {
     timer.format_time(writer)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // Builds an `Uptime` with the given width/precision already applied,
+    // paired with the exact elapsed time `render` formats it at, so the
+    // rendered digits don't depend on how long the test takes to run.
+    fn elapsed(
+        elapsed: Duration,
+        width: Option<usize>,
+        precision: Option<usize>,
+    ) -> (Uptime, Duration) {
+        let timer = Uptime {
+            epoch: Instant::now(),
+            width,
+            precision,
+        };
+        (timer, elapsed)
+    }
+
+    fn render((timer, elapsed): (Uptime, Duration)) -> String {
+        let mut out = String::new();
+        timer.write_elapsed(elapsed, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn default_format_is_unchanged() {
+        let timer = elapsed(Duration::from_nanos(12_345_678_900), None, None);
+        assert_eq!(render(timer), "  12.345678900s ");
+    }
+
+    #[test]
+    fn width_and_precision_zero_pad_and_truncate() {
+        let timer = elapsed(Duration::from_nanos(12_345_678_900), Some(7), Some(6));
+        assert_eq!(render(timer), "0000012.345678s ");
+    }
+
+    #[test]
+    fn width_does_not_truncate_a_wider_value() {
+        let timer = elapsed(Duration::from_secs(12345), Some(2), Some(0));
+        assert_eq!(render(timer), "12345.0s ");
+    }
+
+    #[test]
+    fn precision_zero_drops_fractional_precision() {
+        let timer = elapsed(Duration::from_nanos(12_345_678_900), None, Some(0));
+        assert_eq!(render(timer), "0012.0s ");
+    }
+
+    #[test]
+    fn precision_above_nine_is_clamped_by_the_builder() {
+        let timer = Uptime::new().with_precision(20);
+        assert_eq!(timer.precision, Some(9));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn fixed_time_always_renders_the_same_timestamp() {
+        let timer = FixedTime::at("2020-01-01T00:00:00Z").unwrap();
+        let mut out = String::new();
+        timer.format_time(&mut out).unwrap();
+        assert_eq!(out, "Jan 01 00:00:00.000 ");
+
+        out.clear();
+        timer.format_time(&mut out).unwrap();
+        assert_eq!(out, "Jan 01 00:00:00.000 ", "repeated renders must not drift");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn fixed_time_advance_moves_subsequent_renders_forward() {
+        let timer = FixedTime::at("2020-01-01T00:00:00Z").unwrap();
+        timer.advance(Duration::from_millis(1_500));
+
+        let mut out = String::new();
+        timer.format_time(&mut out).unwrap();
+        assert_eq!(out, "Jan 01 00:00:01.500 ");
+
+        // Advancing again accumulates on top of the previous offset.
+        timer.advance(Duration::from_secs(60));
+        out.clear();
+        timer.format_time(&mut out).unwrap();
+        assert_eq!(out, "Jan 01 00:01:01.500 ");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn fixed_time_rejects_an_invalid_timestamp() {
+        assert!(FixedTime::at("not a timestamp").is_err());
+    }
+}
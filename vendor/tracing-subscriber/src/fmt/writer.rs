@@ -2,7 +2,18 @@
 //!
 //! [`io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
 
-use std::io;
+use crate::sync::RwLock;
+use std::{
+    io,
+    io::Write as _,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tracing_core::{Level, Metadata};
 
 /// A type that can create [`io::Write`] instances.
 ///
@@ -38,6 +49,22 @@ pub trait MakeWriter {
     /// [`io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
     /// [`MakeWriter`]: trait.MakeWriter.html
     fn make_writer(&self) -> Self::Writer;
+
+    /// Returns an instance of [`Writer`] for the event or span described by
+    /// `meta`.
+    ///
+    /// This lets a `MakeWriter` route different records to different
+    /// destinations (for example, [`ErrorRoutedWriter`] sends `ERROR`-level
+    /// events to a separate writer). The default implementation ignores
+    /// `meta` and just calls [`make_writer`].
+    ///
+    /// [`Writer`]: #associatedtype.Writer
+    /// [`ErrorRoutedWriter`]: struct.ErrorRoutedWriter.html
+    /// [`make_writer`]: #tymethod.make_writer
+    fn make_writer_for(&self, meta: &Metadata<'_>) -> Self::Writer {
+        let _ = meta;
+        self.make_writer()
+    }
 }
 
 impl<F, W> MakeWriter for F
@@ -52,6 +79,1111 @@ where
     }
 }
 
+/// A [`MakeWriter`] that sends `ERROR`-level events to one writer and
+/// everything else to another.
+///
+/// This is sugar over the metadata-aware [`make_writer_for`] hook for the
+/// single most common case of per-level routing: errors to `stderr`, the
+/// rest to `stdout`. Constructed via [`Builder::with_error_writer`]; for
+/// anything more elaborate (routing on more than one level, or on something
+/// other than level), implement [`MakeWriter`] directly and override
+/// [`make_writer_for`].
+///
+/// [`make_writer_for`]: trait.MakeWriter.html#method.make_writer_for
+/// [`MakeWriter`]: trait.MakeWriter.html
+/// [`Builder::with_error_writer`]: ../struct.Builder.html#method.with_error_writer
+#[derive(Clone, Debug)]
+pub struct ErrorRoutedWriter<M, E> {
+    main: M,
+    error: E,
+}
+
+impl<M, E> ErrorRoutedWriter<M, E> {
+    /// Returns a new `ErrorRoutedWriter` that sends `ERROR`-level events to
+    /// `error` and everything else to `main`.
+    pub fn new(main: M, error: E) -> Self {
+        Self { main, error }
+    }
+}
+
+impl<M, E> MakeWriter for ErrorRoutedWriter<M, E>
+where
+    M: MakeWriter,
+    M::Writer: 'static,
+    E: MakeWriter,
+    E::Writer: 'static,
+{
+    type Writer = Box<dyn io::Write>;
+
+    fn make_writer(&self) -> Self::Writer {
+        Box::new(self.main.make_writer())
+    }
+
+    fn make_writer_for(&self, meta: &Metadata<'_>) -> Self::Writer {
+        if *meta.level() == Level::ERROR {
+            Box::new(self.error.make_writer())
+        } else {
+            Box::new(self.main.make_writer())
+        }
+    }
+}
+
+/// A `MakeWriter` whose inner `MakeWriter` can be swapped out at runtime
+/// through an associated [`ReloadHandle`].
+///
+/// This is primarily useful when the eventual log destination isn't known
+/// until after the subscriber has already been built and installed: the
+/// subscriber can be constructed with a placeholder writer, and the real one
+/// swapped in once it's available.
+///
+/// A write that is already in flight against the old `MakeWriter` when
+/// [`ReloadHandle::set_writer`] is called will run to completion against the
+/// old writer; only subsequent calls to `make_writer` observe the new one.
+///
+/// [`ReloadHandle::set_writer`]: struct.ReloadHandle.html#method.set_writer
+#[derive(Debug)]
+pub struct Reload<M> {
+    inner: Arc<RwLock<M>>,
+}
+
+/// A handle that allows swapping the `MakeWriter` backing a [`Reload`] at
+/// runtime.
+///
+/// [`Reload`]: struct.Reload.html
+#[derive(Debug)]
+pub struct ReloadHandle<M> {
+    inner: Arc<RwLock<M>>,
+}
+
+impl<M: MakeWriter> Reload<M> {
+    /// Wraps `make_writer`, returning a `Reload` and a [`ReloadHandle`] that
+    /// can be used to swap it out later.
+    ///
+    /// [`ReloadHandle`]: struct.ReloadHandle.html
+    pub fn new(make_writer: M) -> (Self, ReloadHandle<M>) {
+        let inner = Arc::new(RwLock::new(make_writer));
+        let handle = ReloadHandle {
+            inner: inner.clone(),
+        };
+        (Self { inner }, handle)
+    }
+}
+
+impl<M: MakeWriter> MakeWriter for Reload<M> {
+    type Writer = M::Writer;
+
+    fn make_writer(&self) -> Self::Writer {
+        self.inner
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .make_writer()
+    }
+
+    fn make_writer_for(&self, meta: &Metadata<'_>) -> Self::Writer {
+        self.inner
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .make_writer_for(meta)
+    }
+}
+
+impl<M> ReloadHandle<M> {
+    /// Replaces the wrapped `MakeWriter`, so that subsequent calls to
+    /// `make_writer` use `new_writer` instead.
+    ///
+    /// Any write already in progress against the previous `MakeWriter`'s
+    /// output is unaffected; only writers created after this call observe
+    /// the change.
+    pub fn set_writer(&self, new_writer: M) {
+        if let Ok(mut inner) = self.inner.write() {
+            *inner = new_writer;
+        }
+    }
+}
+
+impl<M> Clone for ReloadHandle<M> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// A [`MakeWriter`] whose writes are handed off to a background worker
+/// thread, so that the calling thread never blocks on I/O.
+///
+/// Constructed with [`non_blocking`], which also returns a [`WorkerGuard`]
+/// that must be kept alive for as long as writes should be flushed (and
+/// ideally shut down explicitly, via [`WorkerGuard::shutdown`], before the
+/// process exits).
+///
+/// [`non_blocking`]: fn.non_blocking.html
+/// [`WorkerGuard`]: struct.WorkerGuard.html
+#[derive(Debug, Clone)]
+pub struct NonBlocking {
+    sender: mpsc::Sender<Msg>,
+    buffered: Arc<AtomicUsize>,
+}
+
+enum Msg {
+    Write(Vec<u8>),
+    Flush(mpsc::Sender<()>),
+    Shutdown(mpsc::Sender<()>),
+}
+
+impl std::fmt::Debug for Msg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Msg::Write(buf) => f.debug_tuple("Write").field(&buf.len()).finish(),
+            Msg::Flush(_) => f.debug_tuple("Flush").finish(),
+            Msg::Shutdown(_) => f.debug_tuple("Shutdown").finish(),
+        }
+    }
+}
+
+/// Wraps an [`io::Write`] so that writes to it happen on a dedicated
+/// background thread, returning a [`NonBlocking`] writer and a
+/// [`WorkerGuard`] that owns the background thread.
+///
+/// The `WorkerGuard` should be held for as long as the returned
+/// `NonBlocking` (or any clone of it) may still be written to; dropping it
+/// makes a final best-effort attempt to flush, bounded by a short timeout.
+/// For a deterministic drain (for example, right before
+/// `std::process::exit`), call [`WorkerGuard::shutdown`] explicitly instead
+/// of relying on `Drop`.
+///
+/// [`io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+/// [`WorkerGuard::shutdown`]: struct.WorkerGuard.html#method.shutdown
+pub fn non_blocking<W>(mut writer: W) -> (NonBlocking, WorkerGuard)
+where
+    W: io::Write + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel::<Msg>();
+    let buffered = Arc::new(AtomicUsize::new(0));
+    let done = Arc::new(AtomicBool::new(false));
+
+    let worker_buffered = buffered.clone();
+    let worker_done = done.clone();
+    let handle = thread::spawn(move || {
+        for msg in receiver.iter() {
+            match msg {
+                Msg::Write(buf) => {
+                    let _ = writer.write_all(&buf);
+                    worker_buffered.fetch_sub(1, Ordering::SeqCst);
+                }
+                Msg::Flush(ack) => {
+                    let _ = writer.flush();
+                    let _ = ack.send(());
+                }
+                Msg::Shutdown(ack) => {
+                    let _ = writer.flush();
+                    let _ = ack.send(());
+                    break;
+                }
+            }
+        }
+        worker_done.store(true, Ordering::SeqCst);
+    });
+
+    let non_blocking = NonBlocking {
+        sender: sender.clone(),
+        buffered: buffered.clone(),
+    };
+    let guard = WorkerGuard {
+        handle: Some(handle),
+        sender,
+        buffered,
+        done,
+    };
+    (non_blocking, guard)
+}
+
+impl MakeWriter for NonBlocking {
+    type Writer = NonBlockingWriter;
+
+    fn make_writer(&self) -> Self::Writer {
+        NonBlockingWriter {
+            sender: self.sender.clone(),
+            buffered: self.buffered.clone(),
+        }
+    }
+}
+
+impl NonBlocking {
+    /// Sends a shutdown message to the background worker and waits (up to
+    /// `timeout`) for it to drain and flush everything queued so far.
+    ///
+    /// Returns the number of writes that were still buffered when this
+    /// method returned; this is `0` unless `timeout` elapsed first.
+    ///
+    /// Unlike [`WorkerGuard::shutdown`], this does not join the worker
+    /// thread (this type doesn't own the `JoinHandle`); it only waits for
+    /// the acknowledgement that the backlog has been drained.
+    ///
+    /// [`WorkerGuard::shutdown`]: struct.WorkerGuard.html#method.shutdown
+    pub fn shutdown(&self, timeout: Duration) -> usize {
+        let (ack, ack_rx) = mpsc::channel();
+        if self.sender.send(Msg::Shutdown(ack)).is_ok() {
+            let _ = ack_rx.recv_timeout(timeout);
+        }
+        self.buffered.load(Ordering::SeqCst)
+    }
+}
+
+/// The [`io::Write`] implementation handed out by [`NonBlocking::make_writer`].
+///
+/// [`io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+/// [`NonBlocking::make_writer`]: struct.NonBlocking.html#method.make_writer
+#[derive(Debug)]
+pub struct NonBlockingWriter {
+    sender: mpsc::Sender<Msg>,
+    buffered: Arc<AtomicUsize>,
+}
+
+/// A [`MakeWriter`] that owns (or forwards to) a background worker it can
+/// be asked to flush and shut down on demand.
+///
+/// This is implemented by [`NonBlocking`], and lets [`fmt::Subscriber`]
+/// expose a `shutdown` method that propagates down to its writer without
+/// needing to know about non-blocking writers specifically.
+///
+/// [`fmt::Subscriber`]: ../struct.Subscriber.html
+pub trait ShutdownWriter: MakeWriter {
+    /// Flushes and shuts down the background worker backing this writer,
+    /// blocking for up to `timeout`.
+    ///
+    /// Returns the number of writes still buffered when this method
+    /// returned; this is `0` unless `timeout` elapsed first.
+    fn shutdown(&self, timeout: Duration) -> usize;
+}
+
+impl ShutdownWriter for NonBlocking {
+    fn shutdown(&self, timeout: Duration) -> usize {
+        NonBlocking::shutdown(self, timeout)
+    }
+}
+
+impl io::Write for NonBlockingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len();
+        self.buffered.fetch_add(1, Ordering::SeqCst);
+        self.sender.send(Msg::Write(buf.to_vec())).map_err(|_| {
+            self.buffered.fetch_sub(1, Ordering::SeqCst);
+            io::Error::from(io::ErrorKind::BrokenPipe)
+        })?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let (ack, ack_rx) = mpsc::channel();
+        if self.sender.send(Msg::Flush(ack)).is_ok() {
+            let _ = ack_rx.recv();
+        }
+        Ok(())
+    }
+}
+
+/// Owns the background worker thread spawned by [`non_blocking`], and
+/// ensures it is shut down.
+///
+/// Dropping a `WorkerGuard` makes a best-effort attempt to flush any
+/// buffered writes, bounded by a short timeout, so that it's safe (if not
+/// ideal) to let one fall out of scope. For a deterministic shutdown --
+/// blocking until the worker has actually drained and joined, with an
+/// explicit timeout -- call [`shutdown`](WorkerGuard::shutdown) instead.
+///
+/// [`non_blocking`]: fn.non_blocking.html
+#[derive(Debug)]
+pub struct WorkerGuard {
+    handle: Option<thread::JoinHandle<()>>,
+    sender: mpsc::Sender<Msg>,
+    buffered: Arc<AtomicUsize>,
+    done: Arc<AtomicBool>,
+}
+
+impl WorkerGuard {
+    /// Flushes and shuts down the background worker, blocking for up to
+    /// five seconds.
+    ///
+    /// See [`shutdown_timeout`](WorkerGuard::shutdown_timeout) to configure
+    /// the timeout.
+    pub fn shutdown(self) -> usize {
+        self.shutdown_timeout(Duration::from_secs(5))
+    }
+
+    /// Flushes and shuts down the background worker, blocking until it has
+    /// drained its backlog and exited, or until `timeout` elapses.
+    ///
+    /// Returns the number of writes that were still buffered (neither
+    /// written nor dropped) when this method returned. This is `0` unless
+    /// the worker failed to finish draining within `timeout`.
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> usize {
+        let deadline = Instant::now() + timeout;
+
+        let (ack, ack_rx) = mpsc::channel();
+        if self.sender.send(Msg::Shutdown(ack)).is_ok() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let _ = ack_rx.recv_timeout(remaining);
+        }
+
+        while !self.done.load(Ordering::SeqCst) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+
+        self.buffered.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for WorkerGuard {
+    fn drop(&mut self) {
+        if self.handle.is_none() {
+            // Already shut down via `shutdown`/`shutdown_timeout`.
+            return;
+        }
+
+        let (ack, ack_rx) = mpsc::channel();
+        if self.sender.send(Msg::Shutdown(ack)).is_ok() {
+            let _ = ack_rx.recv_timeout(Duration::from_millis(100));
+        }
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+
+        let buffered = self.buffered.load(Ordering::SeqCst);
+        if buffered > 0 {
+            eprintln!(
+                "WorkerGuard dropped with {} buffered write(s) not flushed in time",
+                buffered
+            );
+        }
+    }
+}
+
+enum BufferedMsg {
+    Write(Vec<u8>),
+    Flush(mpsc::Sender<()>),
+    Shutdown(mpsc::Sender<()>),
+}
+
+impl std::fmt::Debug for BufferedMsg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BufferedMsg::Write(buf) => f.debug_tuple("Write").field(&buf.len()).finish(),
+            BufferedMsg::Flush(_) => f.debug_tuple("Flush").finish(),
+            BufferedMsg::Shutdown(_) => f.debug_tuple("Shutdown").finish(),
+        }
+    }
+}
+
+/// Wraps an [`io::Write`] so that writes to it are coalesced in memory and
+/// flushed in batches, rather than issuing one underlying `write` per
+/// formatted event.
+///
+/// Returns a [`BufferedWriter`] (cloneable, implements [`MakeWriter`]) and a
+/// [`BufferedWriterGuard`] that owns the background thread doing the
+/// coalescing. The buffer is flushed whenever it reaches `threshold` bytes,
+/// whenever `flush_interval` elapses with anything still buffered, and when
+/// the guard is shut down (explicitly or via `Drop`) — so a low event rate
+/// never delays logs past `flush_interval`, even though it doesn't hit
+/// `threshold`.
+///
+/// Unlike [`non_blocking`], which only moves writes off the caller's thread,
+/// `BufferedWriter` also reduces the *number* of underlying writes issued,
+/// which matters most for writers backed by a syscall (files, sockets).
+///
+/// [`io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+/// [`MakeWriter`]: trait.MakeWriter.html
+/// [`non_blocking`]: fn.non_blocking.html
+pub fn buffered_writer<W>(
+    mut writer: W,
+    threshold: usize,
+    flush_interval: Duration,
+) -> (BufferedWriter, BufferedWriterGuard)
+where
+    W: io::Write + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel::<BufferedMsg>();
+    let done = Arc::new(AtomicBool::new(false));
+    let worker_done = done.clone();
+
+    let flush_buf = |writer: &mut W, buf: &mut Vec<u8>| {
+        if !buf.is_empty() {
+            let _ = writer.write_all(buf);
+            buf.clear();
+        }
+    };
+
+    let handle = thread::spawn(move || {
+        let mut buf = Vec::new();
+        loop {
+            match receiver.recv_timeout(flush_interval) {
+                Ok(BufferedMsg::Write(bytes)) => {
+                    buf.extend_from_slice(&bytes);
+                    if buf.len() >= threshold {
+                        flush_buf(&mut writer, &mut buf);
+                    }
+                }
+                Ok(BufferedMsg::Flush(ack)) => {
+                    flush_buf(&mut writer, &mut buf);
+                    let _ = writer.flush();
+                    let _ = ack.send(());
+                }
+                Ok(BufferedMsg::Shutdown(ack)) => {
+                    flush_buf(&mut writer, &mut buf);
+                    let _ = writer.flush();
+                    let _ = ack.send(());
+                    break;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    flush_buf(&mut writer, &mut buf);
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        worker_done.store(true, Ordering::SeqCst);
+    });
+
+    let buffered = BufferedWriter {
+        sender: sender.clone(),
+    };
+    let guard = BufferedWriterGuard {
+        handle: Some(handle),
+        sender,
+        done,
+    };
+    (buffered, guard)
+}
+
+/// A [`MakeWriter`] that coalesces writes in memory before flushing them to
+/// its underlying [`io::Write`] in batches.
+///
+/// Constructed via [`buffered_writer`].
+///
+/// [`MakeWriter`]: trait.MakeWriter.html
+/// [`io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+/// [`buffered_writer`]: fn.buffered_writer.html
+#[derive(Debug, Clone)]
+pub struct BufferedWriter {
+    sender: mpsc::Sender<BufferedMsg>,
+}
+
+impl MakeWriter for BufferedWriter {
+    type Writer = BufferedWriterHandle;
+
+    fn make_writer(&self) -> Self::Writer {
+        BufferedWriterHandle {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+/// The [`io::Write`] implementation handed out by [`BufferedWriter::make_writer`].
+///
+/// [`io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+/// [`BufferedWriter::make_writer`]: struct.BufferedWriter.html#method.make_writer
+#[derive(Debug)]
+pub struct BufferedWriterHandle {
+    sender: mpsc::Sender<BufferedMsg>,
+}
+
+impl io::Write for BufferedWriterHandle {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len();
+        self.sender
+            .send(BufferedMsg::Write(buf.to_vec()))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let (ack, ack_rx) = mpsc::channel();
+        if self.sender.send(BufferedMsg::Flush(ack)).is_ok() {
+            let _ = ack_rx.recv();
+        }
+        Ok(())
+    }
+}
+
+/// Owns the background worker thread spawned by [`buffered_writer`], and
+/// ensures any still-buffered bytes are flushed.
+///
+/// Dropping a `BufferedWriterGuard` makes a best-effort attempt to flush,
+/// bounded by a short timeout. For a deterministic drain, call
+/// [`shutdown`](BufferedWriterGuard::shutdown) instead.
+///
+/// [`buffered_writer`]: fn.buffered_writer.html
+#[derive(Debug)]
+pub struct BufferedWriterGuard {
+    handle: Option<thread::JoinHandle<()>>,
+    sender: mpsc::Sender<BufferedMsg>,
+    done: Arc<AtomicBool>,
+}
+
+impl BufferedWriterGuard {
+    /// Flushes and shuts down the background worker, blocking for up to
+    /// five seconds.
+    pub fn shutdown(self) {
+        self.shutdown_timeout(Duration::from_secs(5))
+    }
+
+    /// Flushes and shuts down the background worker, blocking until it has
+    /// drained its buffer and exited, or until `timeout` elapses.
+    pub fn shutdown_timeout(mut self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+
+        let (ack, ack_rx) = mpsc::channel();
+        if self.sender.send(BufferedMsg::Shutdown(ack)).is_ok() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let _ = ack_rx.recv_timeout(remaining);
+        }
+
+        while !self.done.load(Ordering::SeqCst) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for BufferedWriterGuard {
+    fn drop(&mut self) {
+        if self.handle.is_none() {
+            // Already shut down via `shutdown`/`shutdown_timeout`.
+            return;
+        }
+
+        let (ack, ack_rx) = mpsc::channel();
+        if self.sender.send(BufferedMsg::Shutdown(ack)).is_ok() {
+            let _ = ack_rx.recv_timeout(Duration::from_millis(100));
+        }
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Where a [`socket_writer`] ships trace data.
+#[derive(Debug, Clone, Copy)]
+pub enum SocketTarget {
+    /// Write each record to a TCP stream connected to `addr`. The stream
+    /// carries whatever framing the formatter already produces (a
+    /// newline-delimited JSON formatter needs no further framing here,
+    /// since each write is one already-terminated line).
+    Tcp(std::net::SocketAddr),
+    /// Send each record as a single UDP datagram to `addr`. Unlike TCP,
+    /// there's no stream to frame: one `write` is one datagram, so a
+    /// newline-delimited JSON formatter's trailing `\n` is sent along with
+    /// (and is harmless in) the payload.
+    Udp(std::net::SocketAddr),
+}
+
+const SOCKET_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const SOCKET_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const SOCKET_MAX_BACKOFF: Duration = Duration::from_secs(30);
+const SOCKET_RETRY_INTERVAL: Duration = Duration::from_millis(200);
+
+enum Conn {
+    Tcp(std::net::TcpStream),
+    Udp(std::net::UdpSocket),
+}
+
+impl Conn {
+    fn connect(target: SocketTarget) -> io::Result<Self> {
+        match target {
+            SocketTarget::Tcp(addr) => {
+                std::net::TcpStream::connect_timeout(&addr, SOCKET_CONNECT_TIMEOUT).map(Conn::Tcp)
+            }
+            SocketTarget::Udp(addr) => {
+                let local: std::net::SocketAddr = if addr.is_ipv4() {
+                    ([0, 0, 0, 0], 0).into()
+                } else {
+                    ([0, 0, 0, 0, 0, 0, 0, 0], 0).into()
+                };
+                let socket = std::net::UdpSocket::bind(local)?;
+                socket.connect(addr)?;
+                Ok(Conn::Udp(socket))
+            }
+        }
+    }
+
+    fn send(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            Conn::Tcp(stream) => stream.write_all(buf),
+            Conn::Udp(socket) => socket.send(buf).map(|_| ()),
+        }
+    }
+}
+
+enum SocketMsg {
+    Write(Vec<u8>),
+    Flush(mpsc::Sender<()>),
+    Shutdown(mpsc::Sender<()>),
+}
+
+impl std::fmt::Debug for SocketMsg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SocketMsg::Write(buf) => f.debug_tuple("Write").field(&buf.len()).finish(),
+            SocketMsg::Flush(_) => f.debug_tuple("Flush").finish(),
+            SocketMsg::Shutdown(_) => f.debug_tuple("Shutdown").finish(),
+        }
+    }
+}
+
+/// Tries to drain as much of `backlog` as possible over `conn`, dropping
+/// the connection (so the caller reconnects) on the first write error.
+fn drain_socket_backlog(conn: &mut Option<Conn>, backlog: &mut std::collections::VecDeque<Vec<u8>>) {
+    while let Some(buf) = backlog.front() {
+        let sent = match conn {
+            Some(c) => c.send(buf).is_ok(),
+            None => false,
+        };
+        if sent {
+            backlog.pop_front();
+        } else {
+            *conn = None;
+            break;
+        }
+    }
+}
+
+/// Ships writes to a remote collector over a TCP or UDP socket, reconnecting
+/// with exponential backoff on failure.
+///
+/// Returns a [`SocketWriter`] (cloneable, implements [`MakeWriter`]) and a
+/// [`SocketWriterGuard`] that owns the background thread doing the actual
+/// network I/O. Writes never block on the network: each one hands its bytes
+/// to the background thread, which buffers up to `max_buffered` records in
+/// memory while disconnected (dropping the oldest once that bound is
+/// exceeded — see [`SocketWriter::dropped`]) and drains the backlog as soon
+/// as it reconnects.
+///
+/// Combine with [`format::Json`](super::format::Json) for line-delimited
+/// JSON log shipping.
+///
+/// [`MakeWriter`]: trait.MakeWriter.html
+pub fn socket_writer(
+    target: SocketTarget,
+    max_buffered: usize,
+) -> (SocketWriter, SocketWriterGuard) {
+    let (sender, receiver) = mpsc::channel::<SocketMsg>();
+    let done = Arc::new(AtomicBool::new(false));
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let worker_done = done.clone();
+    let worker_dropped = dropped.clone();
+
+    let handle = thread::spawn(move || {
+        let mut conn: Option<Conn> = None;
+        let mut backlog: std::collections::VecDeque<Vec<u8>> = std::collections::VecDeque::new();
+        let mut backoff = SOCKET_INITIAL_BACKOFF;
+        let mut next_attempt = Instant::now();
+
+        loop {
+            match receiver.recv_timeout(SOCKET_RETRY_INTERVAL) {
+                Ok(SocketMsg::Write(buf)) => {
+                    if backlog.len() >= max_buffered {
+                        backlog.pop_front();
+                        worker_dropped.fetch_add(1, Ordering::SeqCst);
+                    }
+                    backlog.push_back(buf);
+                }
+                Ok(SocketMsg::Flush(ack)) => {
+                    drain_socket_backlog(&mut conn, &mut backlog);
+                    let _ = ack.send(());
+                }
+                Ok(SocketMsg::Shutdown(ack)) => {
+                    drain_socket_backlog(&mut conn, &mut backlog);
+                    let _ = ack.send(());
+                    break;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+
+            if conn.is_none() && Instant::now() >= next_attempt {
+                match Conn::connect(target) {
+                    Ok(c) => {
+                        conn = Some(c);
+                        backoff = SOCKET_INITIAL_BACKOFF;
+                    }
+                    Err(_) => {
+                        next_attempt = Instant::now() + backoff;
+                        backoff = (backoff * 2).min(SOCKET_MAX_BACKOFF);
+                    }
+                }
+            }
+
+            if conn.is_some() {
+                drain_socket_backlog(&mut conn, &mut backlog);
+            }
+        }
+        worker_done.store(true, Ordering::SeqCst);
+    });
+
+    let writer = SocketWriter {
+        sender: sender.clone(),
+        dropped: dropped.clone(),
+    };
+    let guard = SocketWriterGuard {
+        handle: Some(handle),
+        sender,
+        done,
+    };
+    (writer, guard)
+}
+
+/// A [`MakeWriter`] that ships writes to a remote collector over a socket.
+///
+/// Constructed via [`socket_writer`].
+///
+/// [`MakeWriter`]: trait.MakeWriter.html
+/// [`socket_writer`]: fn.socket_writer.html
+#[derive(Debug, Clone)]
+pub struct SocketWriter {
+    sender: mpsc::Sender<SocketMsg>,
+    dropped: Arc<AtomicUsize>,
+}
+
+impl SocketWriter {
+    /// Returns the number of records dropped so far because the buffer
+    /// accumulated while disconnected exceeded `max_buffered`.
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::SeqCst)
+    }
+}
+
+impl MakeWriter for SocketWriter {
+    type Writer = SocketWriterHandle;
+
+    fn make_writer(&self) -> Self::Writer {
+        SocketWriterHandle {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+/// The [`io::Write`] implementation handed out by [`SocketWriter::make_writer`].
+///
+/// [`io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+/// [`SocketWriter::make_writer`]: struct.SocketWriter.html#method.make_writer
+#[derive(Debug)]
+pub struct SocketWriterHandle {
+    sender: mpsc::Sender<SocketMsg>,
+}
+
+impl io::Write for SocketWriterHandle {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len();
+        self.sender
+            .send(SocketMsg::Write(buf.to_vec()))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let (ack, ack_rx) = mpsc::channel();
+        if self.sender.send(SocketMsg::Flush(ack)).is_ok() {
+            let _ = ack_rx.recv();
+        }
+        Ok(())
+    }
+}
+
+/// Owns the background worker thread spawned by [`socket_writer`], and
+/// ensures it is shut down.
+///
+/// Dropping a `SocketWriterGuard` makes a best-effort attempt to flush,
+/// bounded by a short timeout. For a deterministic drain, call
+/// [`shutdown`](SocketWriterGuard::shutdown) instead.
+///
+/// [`socket_writer`]: fn.socket_writer.html
+#[derive(Debug)]
+pub struct SocketWriterGuard {
+    handle: Option<thread::JoinHandle<()>>,
+    sender: mpsc::Sender<SocketMsg>,
+    done: Arc<AtomicBool>,
+}
+
+impl SocketWriterGuard {
+    /// Flushes and shuts down the background worker, blocking for up to
+    /// five seconds.
+    pub fn shutdown(self) {
+        self.shutdown_timeout(Duration::from_secs(5))
+    }
+
+    /// Flushes and shuts down the background worker, blocking until it has
+    /// drained what it can send and exited, or until `timeout` elapses.
+    pub fn shutdown_timeout(mut self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+
+        let (ack, ack_rx) = mpsc::channel();
+        if self.sender.send(SocketMsg::Shutdown(ack)).is_ok() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let _ = ack_rx.recv_timeout(remaining);
+        }
+
+        while !self.done.load(Ordering::SeqCst) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for SocketWriterGuard {
+    fn drop(&mut self) {
+        if self.handle.is_none() {
+            // Already shut down via `shutdown`/`shutdown_timeout`.
+            return;
+        }
+
+        let (ack, ack_rx) = mpsc::channel();
+        if self.sender.send(SocketMsg::Shutdown(ack)).is_ok() {
+            let _ = ack_rx.recv_timeout(Duration::from_millis(100));
+        }
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// How the formatting path handles byte sequences that are not valid
+/// UTF-8 on their way to a [`MakeWriter`]'s sink.
+///
+/// Every formatter in this crate builds its output as a Rust `String`,
+/// which is valid UTF-8 by construction, so in practice the bytes handed
+/// to a writer are always already valid. This policy exists so that
+/// guarantee is explicit and enforced at the writer boundary — via
+/// [`sanitize_utf8`] — rather than assumed, so a `FormatEvent`
+/// implementation that builds its buffer unsafely can never cause invalid
+/// UTF-8 to reach a writer.
+///
+/// Set via [`Builder::with_invalid_utf8`].
+///
+/// [`MakeWriter`]: trait.MakeWriter.html
+/// [`Builder::with_invalid_utf8`]: ../struct.Builder.html#method.with_invalid_utf8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Utf8Policy {
+    /// Replace invalid byte sequences with `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// This is the default, and matches [`String::from_utf8_lossy`].
+    Lossy,
+    /// Hex-escape each invalid byte as `\xNN`, leaving surrounding valid
+    /// UTF-8 untouched.
+    HexEscape,
+}
+
+impl Default for Utf8Policy {
+    fn default() -> Self {
+        Utf8Policy::Lossy
+    }
+}
+
+/// A [`MakeWriter`] that keeps only the most recently written `capacity`
+/// bytes in a circular in-memory buffer, discarding older bytes as new ones
+/// arrive.
+///
+/// This operates below the formatter, on raw bytes already rendered by
+/// whatever [`Format`] is in use — unlike [`FilteredCaptureLayer`], which
+/// captures structured [`Event`]s. It's meant for "attach the last N KB of
+/// logs" crash diagnostics, where the exact formatted text matters and a
+/// fixed memory budget matters more than keeping every event.
+///
+/// [`MakeWriter`]: trait.MakeWriter.html
+/// [`Format`]: ../format/struct.Format.html
+/// [`FilteredCaptureLayer`]: ../../capture/struct.FilteredCaptureLayer.html
+/// [`Event`]: https://docs.rs/tracing-core/latest/tracing_core/event/struct.Event.html
+#[derive(Clone, Debug)]
+pub struct RingWriter {
+    buf: Arc<RwLock<std::collections::VecDeque<u8>>>,
+    capacity: usize,
+}
+
+impl RingWriter {
+    /// Returns a new `RingWriter` that retains at most `capacity` bytes.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buf: Arc::new(RwLock::new(std::collections::VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Returns the buffer's current contents as a `String`, lossily
+    /// converting any invalid UTF-8 (which can only occur if a write was
+    /// truncated mid-codepoint by the ring wrapping around).
+    pub fn contents(&self) -> String {
+        let buf = try_lock!(self.buf.read(), else return String::new());
+        let bytes: Vec<u8> = buf.iter().copied().collect();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+impl MakeWriter for RingWriter {
+    type Writer = RingWriterHandle;
+
+    fn make_writer(&self) -> Self::Writer {
+        RingWriterHandle {
+            buf: self.buf.clone(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+/// The [`io::Write`] implementation returned by [`RingWriter::make_writer`].
+///
+/// [`io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+/// [`RingWriter::make_writer`]: struct.RingWriter.html#method.make_writer
+#[derive(Debug)]
+pub struct RingWriterHandle {
+    buf: Arc<RwLock<std::collections::VecDeque<u8>>>,
+    capacity: usize,
+}
+
+impl io::Write for RingWriterHandle {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let mut buf = try_lock!(self.buf.write(), else return Ok(data.len()));
+        if data.len() >= self.capacity {
+            buf.clear();
+            buf.extend(data[data.len() - self.capacity..].iter().copied());
+        } else {
+            let overflow = (buf.len() + data.len()).saturating_sub(self.capacity);
+            for _ in 0..overflow {
+                buf.pop_front();
+            }
+            buf.extend(data.iter().copied());
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Applies `policy` to `bytes`, returning a `String` that is valid UTF-8
+/// regardless of whether `bytes` was.
+///
+/// Structured formatters (such as [`format::Json`]) rely on this always
+/// returning a string that's safe to splice into their output literally.
+///
+/// [`format::Json`]: ../format/struct.Json.html
+pub(crate) fn sanitize_utf8(bytes: &[u8], policy: Utf8Policy) -> String {
+    if let Ok(s) = std::str::from_utf8(bytes) {
+        return s.to_owned();
+    }
+    match policy {
+        Utf8Policy::Lossy => String::from_utf8_lossy(bytes).into_owned(),
+        Utf8Policy::HexEscape => {
+            let mut out = String::with_capacity(bytes.len());
+            let mut rest = bytes;
+            loop {
+                match std::str::from_utf8(rest) {
+                    Ok(valid) => {
+                        out.push_str(valid);
+                        break;
+                    }
+                    Err(e) => {
+                        let valid_up_to = e.valid_up_to();
+                        out.push_str(std::str::from_utf8(&rest[..valid_up_to]).unwrap());
+                        let bad_len = e.error_len().unwrap_or(rest.len() - valid_up_to);
+                        for b in &rest[valid_up_to..valid_up_to + bad_len] {
+                            out.push_str(&format!("\\x{:02x}", b));
+                        }
+                        rest = &rest[valid_up_to + bad_len..];
+                    }
+                }
+            }
+            out
+        }
+    }
+}
+
+/// A [`MakeWriter`] that forwards each write to the Windows debug console
+/// via `OutputDebugStringW`, for developers running under a debugger who
+/// want logs in its output window instead of (or in addition to) stdout.
+///
+/// Each write is split on `\n` and one `OutputDebugStringW` call is made per
+/// logical line, converting that line's UTF-8 bytes to UTF-16 first, since
+/// that's the encoding the Win32 API expects. A trailing fragment with no
+/// terminating `\n` is still sent as its own line rather than buffered,
+/// since a [`Format`] always terminates a complete record before the next
+/// write arrives. Invalid UTF-8 is replaced lossily, matching every other
+/// writer in this module, which only ever receive bytes built from a `String`.
+///
+/// Requires the `windows-debug-console` feature. The type exists on every
+/// target so that code using it still compiles off Windows, but on any
+/// target other than Windows every write is a silent no-op.
+///
+/// [`Format`]: super::format::Format
+#[cfg(feature = "windows-debug-console")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DebugConsoleWriter;
+
+#[cfg(feature = "windows-debug-console")]
+impl DebugConsoleWriter {
+    /// Returns a new `DebugConsoleWriter`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[cfg(feature = "windows-debug-console")]
+impl MakeWriter for DebugConsoleWriter {
+    type Writer = DebugConsoleWriter;
+
+    fn make_writer(&self) -> Self::Writer {
+        *self
+    }
+}
+
+#[cfg(feature = "windows-debug-console")]
+impl io::Write for DebugConsoleWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        for line in text.split('\n') {
+            if !line.is_empty() {
+                debug_console_sys::output_line(line);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "windows-debug-console", windows))]
+mod debug_console_sys {
+    extern "system" {
+        fn OutputDebugStringW(lp_output_string: *const u16);
+    }
+
+    pub(super) fn output_line(line: &str) {
+        let mut wide: Vec<u16> = line.encode_utf16().collect();
+        wide.push(0);
+        unsafe {
+            OutputDebugStringW(wide.as_ptr());
+        }
+    }
+}
+
+#[cfg(all(feature = "windows-debug-console", not(windows)))]
+mod debug_console_sys {
+    pub(super) fn output_line(_line: &str) {}
+}
+
 #[cfg(test)]
 mod test {
     use super::MakeWriter;
@@ -153,3 +1285,224 @@ mod test {
         test_writer(make_writer, msg, &BUF);
     }
 }
+
+#[cfg(test)]
+mod utf8_policy_tests {
+    use super::{sanitize_utf8, Utf8Policy};
+
+    #[test]
+    fn valid_utf8_is_returned_unchanged() {
+        assert_eq!(sanitize_utf8(b"hello world", Utf8Policy::Lossy), "hello world");
+        assert_eq!(
+            sanitize_utf8(b"hello world", Utf8Policy::HexEscape),
+            "hello world"
+        );
+    }
+
+    #[test]
+    fn lossy_policy_replaces_invalid_bytes() {
+        let bytes = [b'f', b'o', 0xff, b'o'];
+        assert_eq!(sanitize_utf8(&bytes, Utf8Policy::Lossy), "fo\u{fffd}o");
+    }
+
+    #[test]
+    fn hex_escape_policy_escapes_invalid_bytes_without_touching_valid_ones() {
+        let bytes = [b'f', b'o', 0xff, b'o'];
+        assert_eq!(sanitize_utf8(&bytes, Utf8Policy::HexEscape), "fo\\xffo");
+    }
+}
+
+#[cfg(test)]
+mod non_blocking_tests {
+    use super::{non_blocking, MakeWriter};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.0.lock().unwrap().flush()
+        }
+    }
+
+    #[test]
+    fn writes_are_delivered_before_shutdown_returns() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let (make_writer, guard) = non_blocking(SharedBuf(buf.clone()));
+
+        let mut writer = make_writer.make_writer();
+        writer.write_all(b"hello\n").unwrap();
+        writer.write_all(b"world\n").unwrap();
+
+        let remaining = guard.shutdown();
+
+        assert_eq!(remaining, 0);
+        assert_eq!(&buf.lock().unwrap()[..], b"hello\nworld\n");
+    }
+
+    #[test]
+    fn flush_blocks_until_prior_writes_are_applied() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let (make_writer, _guard) = non_blocking(SharedBuf(buf.clone()));
+
+        let mut writer = make_writer.make_writer();
+        writer.write_all(b"buffered").unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(&buf.lock().unwrap()[..], b"buffered");
+    }
+
+    #[test]
+    fn shutdown_timeout_reports_the_time_budget_used() {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let (make_writer, guard) = non_blocking(SharedBuf(buf.clone()));
+
+        let mut writer = make_writer.make_writer();
+        writer.write_all(b"quick\n").unwrap();
+
+        let remaining = guard.shutdown_timeout(Duration::from_secs(1));
+
+        assert_eq!(remaining, 0);
+        assert_eq!(&buf.lock().unwrap()[..], b"quick\n");
+    }
+}
+
+#[cfg(test)]
+mod buffered_writer_tests {
+    use super::{buffered_writer, MakeWriter};
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct CountingWriter {
+        data: Arc<Mutex<Vec<u8>>>,
+        writes: Arc<Mutex<usize>>,
+    }
+
+    impl Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            *self.writes.lock().unwrap() += 1;
+            self.data.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn many_small_writes_coalesce_into_fewer_underlying_writes() {
+        let inner = CountingWriter::default();
+        let (make_writer, guard) =
+            buffered_writer(inner.clone(), 1024, Duration::from_secs(60));
+
+        let mut writer = make_writer.make_writer();
+        for _ in 0..1000 {
+            writer.write_all(b"0123456789").unwrap();
+        }
+
+        guard.shutdown();
+
+        assert_eq!(inner.data.lock().unwrap().len(), 10_000);
+        let writes = *inner.writes.lock().unwrap();
+        assert!(
+            writes < 1000,
+            "expected far fewer than 1000 underlying writes, got {}",
+            writes
+        );
+        assert!(writes > 0);
+    }
+
+    #[test]
+    fn a_sub_threshold_write_is_flushed_by_the_interval_timer() {
+        let inner = CountingWriter::default();
+        let (make_writer, guard) =
+            buffered_writer(inner.clone(), 1024, Duration::from_millis(20));
+
+        let mut writer = make_writer.make_writer();
+        writer.write_all(b"short").unwrap();
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(&inner.data.lock().unwrap()[..], b"short");
+
+        guard.shutdown();
+    }
+
+    #[test]
+    fn dropping_the_guard_flushes_any_remaining_buffered_bytes() {
+        let inner = CountingWriter::default();
+        let (make_writer, guard) =
+            buffered_writer(inner.clone(), 1024, Duration::from_secs(60));
+
+        let mut writer = make_writer.make_writer();
+        writer.write_all(b"never hits the threshold").unwrap();
+
+        drop(guard);
+
+        assert_eq!(&inner.data.lock().unwrap()[..], b"never hits the threshold");
+    }
+}
+
+#[cfg(all(test, feature = "windows-debug-console"))]
+mod debug_console_tests {
+    use super::DebugConsoleWriter;
+    use std::io::Write;
+
+    #[test]
+    fn write_reports_the_full_byte_count_and_never_panics() {
+        let mut writer = DebugConsoleWriter::new();
+        let n = writer.write(b"one\ntwo\nthree").unwrap();
+        assert_eq!(n, b"one\ntwo\nthree".len());
+    }
+
+    #[test]
+    fn empty_trailing_line_is_not_sent() {
+        let mut writer = DebugConsoleWriter::new();
+        // A single trailing newline splits into `["line", ""]`; the empty
+        // tail must not produce a spurious `OutputDebugStringW` call.
+        writer.write(b"line\n").unwrap();
+    }
+}
+
+#[cfg(test)]
+mod ring_writer_tests {
+    use super::{MakeWriter, RingWriter};
+    use std::io::Write;
+
+    #[test]
+    fn writes_within_capacity_are_kept_in_full() {
+        let ring = RingWriter::new(16);
+        let mut writer = ring.make_writer();
+        writer.write_all(b"hello").unwrap();
+
+        assert_eq!(ring.contents(), "hello");
+    }
+
+    #[test]
+    fn writes_past_capacity_keep_only_the_tail() {
+        let ring = RingWriter::new(5);
+        let mut writer = ring.make_writer();
+        writer.write_all(b"hello world").unwrap();
+
+        assert_eq!(ring.contents(), "world");
+    }
+
+    #[test]
+    fn many_small_writes_wrap_around_the_buffer() {
+        let ring = RingWriter::new(5);
+        let mut writer = ring.make_writer();
+        for byte in b"hello world" {
+            writer.write_all(&[*byte]).unwrap();
+        }
+
+        assert_eq!(ring.contents(), "world");
+    }
+}
@@ -0,0 +1,177 @@
+//! A `Layer` that records span durations into per-name histograms, for
+//! computing latency percentiles (p50/p95/p99) without an external metrics
+//! system.
+use crate::layer::{Context, Layer};
+use crate::sync::RwLock;
+use std::{collections::HashMap, sync::Arc, time::Instant};
+use tracing_core::{span, subscriber::Subscriber};
+
+pub(crate) const BUCKETS: usize = 64;
+
+/// A `Layer` that records each span's elapsed wall-clock time into a
+/// histogram keyed by the span's name, on close.
+///
+/// Call [`snapshot`] at any time to get the current p50/p95/p99 latencies
+/// per span name. The backing histogram is a simple power-of-two bucketed
+/// structure; it trades precision for a fixed, small memory footprint.
+///
+/// [`snapshot`]: #method.snapshot
+#[derive(Clone, Debug)]
+pub struct HistogramLayer {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    starts: RwLock<HashMap<span::Id, (Instant, &'static str)>>,
+    histograms: RwLock<HashMap<&'static str, Histogram>>,
+}
+
+impl Default for Inner {
+    fn default() -> Self {
+        Self {
+            starts: RwLock::new(HashMap::new()),
+            histograms: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+/// A snapshot of the recorded latency percentiles for a single span name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Percentiles {
+    /// The number of times a span with this name has been closed.
+    pub count: u64,
+    /// The 50th percentile latency, in nanoseconds.
+    pub p50_nanos: u64,
+    /// The 95th percentile latency, in nanoseconds.
+    pub p95_nanos: u64,
+    /// The 99th percentile latency, in nanoseconds.
+    pub p99_nanos: u64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Histogram {
+    // Bucket `i` counts samples whose nanosecond duration falls in
+    // `(2^(i-1), 2^i]`. This trades precision for a small, fixed footprint.
+    buckets: [u64; BUCKETS],
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; BUCKETS],
+        }
+    }
+}
+
+impl Histogram {
+    pub(crate) fn record(&mut self, nanos: u64) {
+        let bucket = if nanos == 0 {
+            0
+        } else {
+            (64 - nanos.leading_zeros()) as usize
+        };
+        self.buckets[bucket.min(BUCKETS - 1)] += 1;
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    pub(crate) fn percentile(&self, p: f64) -> u64 {
+        let total = self.count();
+        if total == 0 {
+            return 0;
+        }
+        let target = (total as f64 * p).ceil() as u64;
+        let mut seen = 0u64;
+        for (i, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                return 1u64 << i;
+            }
+        }
+        1u64 << (BUCKETS - 1)
+    }
+}
+
+impl HistogramLayer {
+    /// Returns a new, empty `HistogramLayer`.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner::default()),
+        }
+    }
+
+    /// Returns a snapshot of the current percentiles for every span name
+    /// that has been closed at least once.
+    pub fn snapshot(&self) -> HashMap<&'static str, Percentiles> {
+        let histograms = try_lock!(self.inner.histograms.read(), else return HashMap::new());
+        histograms
+            .iter()
+            .map(|(&name, h)| {
+                (
+                    name,
+                    Percentiles {
+                        count: h.count(),
+                        p50_nanos: h.percentile(0.50),
+                        p95_nanos: h.percentile(0.95),
+                        p99_nanos: h.percentile(0.99),
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+impl Default for HistogramLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for HistogramLayer {
+    fn new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, _ctx: Context<'_, S>) {
+        let name = attrs.metadata().name();
+        try_lock!(self.inner.starts.write()).insert(id.clone(), (Instant::now(), name));
+    }
+
+    fn on_close(&self, id: span::Id, _ctx: Context<'_, S>) {
+        let start = try_lock!(self.inner.starts.write()).remove(&id);
+        if let Some((start, name)) = start {
+            let nanos = start.elapsed().as_nanos().min(u128::from(u64::max_value())) as u64;
+            try_lock!(self.inner.histograms.write())
+                .entry(name)
+                .or_insert_with(Histogram::default)
+                .record(nanos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn records_percentiles_per_span_name() {
+        let layer = HistogramLayer::new();
+        let subscriber =
+            tracing_core::dispatcher::Dispatch::new(crate::layer::tests::TrackingSubscriber::default().with(layer.clone()));
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            for _ in 0..5 {
+                let span = tracing::trace_span!("work");
+                let _enter = span.enter();
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+
+        let snapshot = layer.snapshot();
+        let work = snapshot.get("work").expect("should have recorded `work`");
+        assert_eq!(work.count, 5);
+        assert!(work.p50_nanos > 0);
+        assert!(work.p99_nanos >= work.p50_nanos);
+    }
+}
@@ -0,0 +1,240 @@
+//! Bridges `io::Write`-based diagnostics into the `tracing` event stream.
+//!
+//! [`TracingWriter`] is the inverse of [`MakeWriter`]: rather than giving
+//! `tracing` output somewhere to go, it gives non-`tracing` code — legacy
+//! modules or third-party libraries that only know how to write to a
+//! `Box<dyn io::Write>` — a way into the trace stream. Bytes written to it
+//! are buffered and split on newlines; each complete line becomes a
+//! `tracing` event at a fixed level and target. A partial line (no trailing
+//! `\n` yet) stays buffered until the next newline, an explicit [`flush`],
+//! or the writer is dropped.
+//!
+//! Because `Metadata`'s target must be known at compile time, and the
+//! target configured here is only known at runtime, the configured target
+//! is carried as a `target` field on the emitted event rather than as the
+//! event's true `Metadata::target` (which is fixed to this module's path)
+//! — the same trick [`tracing-log`] uses for a `log::Record`'s target.
+//!
+//! [`MakeWriter`]: super::writer::MakeWriter
+//! [`flush`]: std::io::Write::flush
+//! [`tracing-log`]: https://docs.rs/tracing-log
+use std::io;
+use std::io::Write;
+use tracing_core::callsite::Callsite;
+use tracing_core::field;
+use tracing_core::{Event, Kind, Level, Metadata};
+
+macro_rules! io_write_cs {
+    ($level:expr) => {{
+        struct Cs;
+        static CALLSITE: Cs = Cs;
+        static FIELD_NAMES: &[&str] = &["target", "message"];
+        static META: Metadata<'static> = Metadata::new(
+            "io write",
+            "tracing_subscriber::io_writer",
+            $level,
+            None,
+            None,
+            None,
+            field::FieldSet::new(FIELD_NAMES, tracing_core::identify_callsite!(&CALLSITE)),
+            Kind::EVENT,
+        );
+
+        impl Callsite for Cs {
+            fn set_interest(&self, _: tracing_core::subscriber::Interest) {}
+            fn metadata(&self) -> &'static Metadata<'static> {
+                &META
+            }
+        }
+
+        &CALLSITE as &'static dyn Callsite
+    }};
+}
+
+static TRACE_CS: &dyn Callsite = io_write_cs!(Level::TRACE);
+static DEBUG_CS: &dyn Callsite = io_write_cs!(Level::DEBUG);
+static INFO_CS: &dyn Callsite = io_write_cs!(Level::INFO);
+static WARN_CS: &dyn Callsite = io_write_cs!(Level::WARN);
+static ERROR_CS: &dyn Callsite = io_write_cs!(Level::ERROR);
+
+fn cs_for(level: Level) -> &'static dyn Callsite {
+    match level {
+        Level::TRACE => TRACE_CS,
+        Level::DEBUG => DEBUG_CS,
+        Level::INFO => INFO_CS,
+        Level::WARN => WARN_CS,
+        Level::ERROR => ERROR_CS,
+    }
+}
+
+fn emit(level: Level, target: &str, message: &str) {
+    let cs = cs_for(level);
+    let meta = cs.metadata();
+    let fields = meta.fields();
+    let target_field = fields.field("target").expect("target field must exist");
+    let message_field = fields.field("message").expect("message field must exist");
+    let values = [
+        (&target_field, Some(&target as &dyn field::Value)),
+        (&message_field, Some(&message as &dyn field::Value)),
+    ];
+    Event::dispatch(meta, &fields.value_set(&values));
+}
+
+/// An [`io::Write`] adapter that emits each complete line written to it as
+/// a `tracing` event at a fixed level and target.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct TracingWriter {
+    level: Level,
+    target: String,
+    buf: Vec<u8>,
+}
+
+impl TracingWriter {
+    /// Returns a new `TracingWriter` that emits each line written to it as
+    /// an event at `level`, with `target` recorded as the event's `target`
+    /// field.
+    pub fn new(level: Level, target: impl Into<String>) -> Self {
+        Self {
+            level,
+            target: target.into(),
+            buf: Vec::new(),
+        }
+    }
+
+    fn emit_line(&self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches('\r');
+        emit(self.level.clone(), &self.target, line);
+    }
+}
+
+impl io::Write for TracingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        while let Some(pos) = self.buf.iter().position(|&b| b == b'\n') {
+            let rest = self.buf.split_off(pos + 1);
+            let mut line = std::mem::replace(&mut self.buf, rest);
+            line.pop(); // drop the trailing '\n'
+            self.emit_line(&line);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buf.is_empty() {
+            let line = std::mem::take(&mut self.buf);
+            self.emit_line(&line);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TracingWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::field::{Field, Visit};
+    use tracing_core::{
+        dispatcher::Dispatch,
+        span,
+        subscriber::{Interest, Subscriber},
+    };
+
+    #[derive(Default)]
+    struct RecordingSubscriber {
+        lines: Arc<Mutex<Vec<(String, String)>>>,
+    }
+
+    #[derive(Default)]
+    struct LineVisitor {
+        target: Option<String>,
+        message: Option<String>,
+    }
+
+    impl Visit for LineVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            let value = format!("{:?}", value);
+            match field.name() {
+                "target" => self.target = Some(value.trim_matches('"').to_owned()),
+                "message" => self.message = Some(value.trim_matches('"').to_owned()),
+                _ => {}
+            }
+        }
+    }
+
+    impl Subscriber for RecordingSubscriber {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut visitor = LineVisitor::default();
+            event.record(&mut visitor);
+            if let (Some(target), Some(message)) = (visitor.target, visitor.message) {
+                self.lines.lock().unwrap().push((target, message));
+            }
+        }
+
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn complete_lines_are_emitted_as_they_arrive() {
+        let recorder = RecordingSubscriber::default();
+        let lines = recorder.lines.clone();
+        let dispatch = Dispatch::new(recorder);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let mut writer = TracingWriter::new(Level::INFO, "legacy");
+            write!(writer, "first line\nsecond ").unwrap();
+            write!(writer, "line\n").unwrap();
+        });
+
+        let seen = lines.lock().unwrap();
+        assert_eq!(
+            *seen,
+            vec![
+                ("legacy".to_owned(), "first line".to_owned()),
+                ("legacy".to_owned(), "second line".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_trailing_partial_line_is_flushed_on_drop() {
+        let recorder = RecordingSubscriber::default();
+        let lines = recorder.lines.clone();
+        let dispatch = Dispatch::new(recorder);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let mut writer = TracingWriter::new(Level::WARN, "legacy");
+            write!(writer, "no newline yet").unwrap();
+            drop(writer);
+        });
+
+        assert_eq!(
+            *lines.lock().unwrap(),
+            vec![("legacy".to_owned(), "no newline yet".to_owned())]
+        );
+    }
+}
@@ -152,6 +152,29 @@ where
         true
     }
 
+    /// Returns the most verbose [`LevelFilter`] that this layer will enable,
+    /// if that value is known ahead of time and does not change based on the
+    /// current [`Context`].
+    ///
+    /// Layers that filter dynamically (for example, based on the current
+    /// span's fields) should return `None`, since there is no single level
+    /// that describes their behavior. The default implementation returns
+    /// `None`, assuming the most permissive case.
+    ///
+    /// This is advisory only: it allows a composed stack of layers to
+    /// determine the loosest level any individual layer could possibly
+    /// enable, so that a single restrictive layer doesn't cause the
+    /// dispatcher to disable a callsite that a sibling layer still cares
+    /// about. It must never be used to *skip* calling [`enabled`], only to
+    /// decide whether a callsite should be statically disabled.
+    ///
+    /// [`LevelFilter`]: ../filter/struct.LevelFilter.html
+    /// [`Context`]: struct.Context.html
+    /// [`enabled`]: #method.enabled
+    fn max_level_hint(&self) -> Option<crate::filter::LevelFilter> {
+        None
+    }
+
     /// Notifies this layer that a new span was constructed with the given
     /// `Attributes` and `Id`.
     fn new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
@@ -549,6 +572,17 @@ where
         }
     }
 
+    fn max_level_hint(&self) -> Option<crate::filter::LevelFilter> {
+        // The composed hint is the *most verbose* of the two layers' hints:
+        // if either layer has no hint (i.e. it may enable anything), the
+        // composed stack has no hint either, since a quiet layer must never
+        // suppress a more verbose sibling's callsites.
+        match (self.layer.max_level_hint(), self.inner.max_level_hint()) {
+            (Some(a), Some(b)) => Some(std::cmp::max(a, b)),
+            _ => None,
+        }
+    }
+
     #[inline]
     fn new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
         self.inner.new_span(attrs, id, ctx.clone());
@@ -616,6 +650,13 @@ where
     }
 }
 
+impl<L, S> Layered<L, S> {
+    /// Returns a reference to the wrapped inner subscriber.
+    pub(crate) fn inner_ref(&self) -> &S {
+        &self.inner
+    }
+}
+
 // impl<L, S> Layered<L, S> {
 //     // TODO(eliza): is there a compelling use-case for this being public?
 //     pub(crate) fn into_inner(self) -> S {
@@ -782,4 +823,116 @@ pub(crate) mod tests {
         assert!(Subscriber::downcast_ref::<NopLayer>(&s).is_some());
         assert!(Subscriber::downcast_ref::<NopLayer2>(&s).is_some());
     }
+
+    #[test]
+    fn max_level_hint_is_the_most_verbose_of_the_stack() {
+        use crate::filter::LevelFilter;
+
+        let stack = LevelFilter::INFO.and_then(LevelFilter::TRACE);
+        assert_eq!(
+            Layer::<NopSubscriber>::max_level_hint(&stack),
+            Some(LevelFilter::TRACE)
+        );
+    }
+
+    #[test]
+    fn max_level_hint_is_none_if_any_layer_is_unbounded() {
+        let stack = NopLayer.and_then(crate::filter::LevelFilter::INFO);
+        assert_eq!(Layer::<NopSubscriber>::max_level_hint(&stack), None);
+    }
+
+    #[test]
+    fn callsite_disabled_by_all_layers_is_never() {
+        use tracing_core::{field::FieldSet, identify_callsite, Callsite, Kind, Level};
+
+        struct NeverLayer;
+        impl<S: Subscriber> Layer<S> for NeverLayer {
+            fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+                Interest::never()
+            }
+        }
+
+        struct Cs;
+        impl Callsite for Cs {
+            fn set_interest(&self, _interest: Interest) {}
+            fn metadata(&self) -> &Metadata<'_> {
+                unimplemented!()
+            }
+        }
+
+        static META: &Metadata<'static> = &Metadata::new(
+            "a_span",
+            "test",
+            Level::TRACE,
+            None,
+            None,
+            None,
+            FieldSet::new(&[], identify_callsite!(&Cs)),
+            Kind::SPAN,
+        );
+
+        let stack = NeverLayer
+            .and_then(crate::filter::LevelFilter::TRACE)
+            .with_subscriber(NopSubscriber);
+        let interest = Subscriber::register_callsite(&stack, META);
+        assert!(interest.is_never());
+    }
+
+    /// A subscriber that enables everything, gives every span its own ID,
+    /// tracks which span is current on each thread, and reports every span
+    /// as closed when it's dropped, for tests of layers that depend on
+    /// those notifications or on `Context::current_span`.
+    #[derive(Default)]
+    pub(crate) struct TrackingSubscriber {
+        next_id: std::sync::atomic::AtomicU64,
+        spans: crate::Registry,
+        current: crate::CurrentSpan,
+    }
+
+    impl Subscriber for TrackingSubscriber {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+            let next = self
+                .next_id
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            let id = span::Id::from_u64(next + 1);
+            self.spans.insert(id.clone(), attrs.metadata());
+            id
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, id: &span::Id) {
+            self.current.enter(id.clone());
+        }
+
+        fn exit(&self, _: &span::Id) {
+            self.current.exit();
+        }
+
+        fn try_close(&self, id: span::Id) -> bool {
+            self.spans.remove(&id);
+            true
+        }
+
+        fn current_span(&self) -> span::Current {
+            match self
+                .current
+                .id()
+                .and_then(|id| self.spans.get(&id).map(|meta| (id, meta)))
+            {
+                Some((id, meta)) => span::Current::new(id, meta),
+                None => span::Current::none(),
+            }
+        }
+    }
 }
@@ -0,0 +1,297 @@
+//! A `Layer` that lets a span subtree override the global level filter, for
+//! dynamically raising verbosity on a single request or task.
+use crate::filter::LevelFilter;
+use crate::layer::{Context, Layer};
+use crate::sync::RwLock;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing_core::field::{Field, Visit};
+use tracing_core::{span, subscriber::Interest, subscriber::Subscriber, Metadata};
+
+/// A `Layer` that raises the effective level threshold for a span subtree
+/// when that subtree's root (or any ancestor) has recorded a configured
+/// field naming a more verbose level.
+///
+/// Events are enabled at `base_level` and above as usual; an event more
+/// verbose than `base_level` is additionally enabled if the current span, or
+/// any of its ancestors, has recorded the configured field with a level name
+/// (`"trace"`, `"debug"`, `"info"`, `"warn"`, `"error"`, or `"off"`, matched
+/// the same way as [`LevelFilter::from_str`]) permissive enough to admit it.
+/// This is useful for bumping verbosity for one request at a time: tag the
+/// request's root span with `log_level = "debug"` and every event nested
+/// under it, at `debug` or less verbose, is emitted, even if the process's
+/// global filter is set to `info`.
+///
+/// A descendant span may set its own `log_level` field to override its
+/// ancestor's for itself and its own descendants; recording no field (or an
+/// unparseable value) simply inherits the ancestor's override, the same as
+/// an intermediate span that never mentions the field at all.
+///
+/// ## How the override is tracked
+///
+/// This version of `tracing-subscriber` predates [`Registry`] and
+/// [`LookupSpan`]: there is no per-span extensions map, and a `Layer` cannot
+/// walk a span's full ancestor chain — only its immediate parent is visible,
+/// via [`Attributes::parent`] or the contextually current span. This layer
+/// gets the same effect without that machinery, the same way
+/// [`sampling::SamplingFilter`] and [`field_filter::FieldElevationFilter`] do:
+/// because [`new_span`] fires exactly once for every span, resolving one hop
+/// of parent and propagating the stored override at creation time is
+/// transitively equivalent to walking all the way to the root. The override
+/// itself is kept in this layer's own map, keyed by span ID, in place of a
+/// per-span extensions entry.
+///
+/// ## Composing with other layers
+///
+/// A `Layered` stack's `enabled` is the logical AND of every layer in the
+/// stack — a `Layer` can only veto another layer's decision, never override
+/// it. This layer's override therefore only has its intended effect when it
+/// is the layer making the level-based accept/reject decision that would
+/// otherwise apply (which is why it takes its own `base_level` rather than
+/// deferring to a separate level filter below it); stacking this on top of
+/// an independent, stricter filter still leaves that filter free to reject
+/// an overridden event.
+///
+/// [`new_span`]: #method.new_span
+/// [`Registry`]: ../struct.Registry.html
+/// [`LookupSpan`]: ../registry/trait.LookupSpan.html
+/// [`Attributes::parent`]: https://docs.rs/tracing-core/latest/tracing_core/span/struct.Attributes.html#method.parent
+/// [`sampling::SamplingFilter`]: ../sampling/struct.SamplingFilter.html
+/// [`field_filter::FieldElevationFilter`]: ../field_filter/struct.FieldElevationFilter.html
+#[derive(Clone, Debug)]
+pub struct LevelOverrideFilter {
+    field_name: &'static str,
+    base_level: LevelFilter,
+    overrides: Arc<RwLock<HashMap<span::Id, LevelFilter>>>,
+}
+
+impl LevelOverrideFilter {
+    /// Returns a new `LevelOverrideFilter` that enables events at
+    /// `base_level` and above unconditionally, and enables more verbose
+    /// events within any span subtree whose root (or an ancestor) has
+    /// recorded `field_name` with a sufficiently verbose level name.
+    pub fn new(field_name: &'static str, base_level: LevelFilter) -> Self {
+        Self {
+            field_name,
+            base_level,
+            overrides: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the level override in effect for the span with the given
+    /// `id`, if this layer has one recorded for it.
+    pub fn override_for(&self, id: &span::Id) -> Option<LevelFilter> {
+        try_lock!(self.overrides.read(), else return None)
+            .get(id)
+            .cloned()
+    }
+
+    fn parent_of<S: Subscriber>(
+        &self,
+        attrs: &span::Attributes<'_>,
+        ctx: &Context<'_, S>,
+    ) -> Option<span::Id> {
+        if let Some(parent) = attrs.parent() {
+            return Some(parent.clone());
+        }
+        if attrs.is_contextual() {
+            return ctx.current_span().id().cloned();
+        }
+        None
+    }
+}
+
+/// Looks for the configured field among a span's attributes, and records the
+/// [`LevelFilter`] it names, if any.
+///
+/// Any other value (or no matching field at all) leaves [`level`] as `None`,
+/// so the caller falls back to the inherited override.
+///
+/// [`level`]: LevelFieldVisitor::level
+struct LevelFieldVisitor<'a> {
+    field_name: &'a str,
+    level: Option<LevelFilter>,
+}
+
+impl<'a> LevelFieldVisitor<'a> {
+    fn record(&mut self, value: &str) {
+        if let Ok(level) = LevelFilter::from_str(value) {
+            self.level = Some(level);
+        }
+    }
+}
+
+impl<'a> Visit for LevelFieldVisitor<'a> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == self.field_name {
+            self.record(value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == self.field_name {
+            self.record(format!("{:?}", value).trim_matches('"'));
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LevelOverrideFilter {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if metadata.is_event() && self.base_level < *metadata.level() {
+            // Whether this callsite is enabled depends on the span it's
+            // invoked from, so its interest can't be cached as a single
+            // global answer.
+            Interest::sometimes()
+        } else {
+            Interest::always()
+        }
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let inherited = self.parent_of(attrs, &ctx).and_then(|parent| self.override_for(&parent));
+
+        let mut visitor = LevelFieldVisitor {
+            field_name: self.field_name,
+            level: inherited,
+        };
+        attrs.record(&mut visitor);
+
+        if let Some(level) = visitor.level {
+            try_lock!(self.overrides.write()).insert(id.clone(), level);
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = LevelFieldVisitor {
+            field_name: self.field_name,
+            level: self.override_for(id),
+        };
+        values.record(&mut visitor);
+        if let Some(level) = visitor.level {
+            try_lock!(self.overrides.write()).insert(id.clone(), level);
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
+        if self.base_level >= *metadata.level() {
+            return true;
+        }
+        match ctx.current_span().id() {
+            Some(id) => self
+                .override_for(id)
+                .map(|level| level >= *metadata.level())
+                .unwrap_or(false),
+            None => false,
+        }
+    }
+
+    fn on_close(&self, id: span::Id, _ctx: Context<'_, S>) {
+        try_lock!(self.overrides.write()).remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct CountingSubscriber {
+        events: Arc<Mutex<usize>>,
+        spans: Arc<crate::layer::tests::TrackingSubscriber>,
+    }
+
+    impl Subscriber for CountingSubscriber {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+            self.spans.new_span(attrs)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, _: &tracing_core::Event<'_>) {
+            *self.events.lock().unwrap() += 1;
+        }
+
+        fn enter(&self, id: &span::Id) {
+            self.spans.enter(id)
+        }
+
+        fn exit(&self, id: &span::Id) {
+            self.spans.exit(id)
+        }
+
+        fn try_close(&self, id: span::Id) -> bool {
+            self.spans.try_close(id)
+        }
+
+        fn current_span(&self) -> span::Current {
+            self.spans.current_span()
+        }
+    }
+
+    #[test]
+    fn debug_events_are_dropped_outside_an_overridden_subtree() {
+        let counter = CountingSubscriber::default();
+        let filter = LevelOverrideFilter::new("log_level", LevelFilter::INFO);
+        let subscriber = tracing_core::dispatcher::Dispatch::new(counter.clone().with(filter));
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let span = tracing::info_span!("normal_request");
+            let _enter = span.enter();
+            tracing::info!("always shown");
+            tracing::debug!("too verbose, dropped");
+        });
+
+        assert_eq!(*counter.events.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn debug_events_pass_inside_a_subtree_tagged_with_a_debug_override() {
+        let counter = CountingSubscriber::default();
+        let filter = LevelOverrideFilter::new("log_level", LevelFilter::INFO);
+        let subscriber = tracing_core::dispatcher::Dispatch::new(counter.clone().with(filter));
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let root = tracing::info_span!("debug_request", log_level = "debug");
+            let _root_enter = root.enter();
+
+            let child = tracing::info_span!("handler");
+            let _child_enter = child.enter();
+
+            tracing::debug!("now visible because an ancestor overrode log_level");
+        });
+
+        assert_eq!(*counter.events.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn a_child_span_can_narrow_an_inherited_override() {
+        let counter = CountingSubscriber::default();
+        let filter = LevelOverrideFilter::new("log_level", LevelFilter::INFO);
+        let subscriber = tracing_core::dispatcher::Dispatch::new(counter.clone().with(filter));
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let root = tracing::info_span!("debug_request", log_level = "debug");
+            let _root_enter = root.enter();
+
+            let child = tracing::info_span!("quiet_handler", log_level = "warn");
+            let _child_enter = child.enter();
+
+            tracing::debug!("dropped: narrowed back down by the child span");
+        });
+
+        assert_eq!(*counter.events.lock().unwrap(), 0);
+    }
+}
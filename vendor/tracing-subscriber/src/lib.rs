@@ -20,6 +20,9 @@
 //!   implementation for printing formatted representations of trace events.
 //!   Enabled by default.
 //! - `ansi`: Enables `fmt` support for ANSI terminal colors. Enabled by default.
+//! - `task`: Enables the [`task`] module, which provides [`task::spawn_instrumented`]
+//!   and [`task::SpawnExt`] for propagating the current span into tasks
+//!   spawned on a Tokio executor. Not enabled by default.
 //!
 //! ### Optional Dependencies
 //!
@@ -31,6 +34,8 @@
 //!   than `Vec`) as a performance optimization. Enabled by default.
 //! - [`parking_lot`]: Use the `parking_lot` crate's `RwLock` implementation
 //!   rather than the Rust standard library's implementation.
+//! - [`tokio`] and [`tracing-futures`]: Required by the `task` feature, to
+//!   spawn instrumented futures on a Tokio executor.
 //!
 //! [`tracing`]: https://docs.rs/tracing/latest/tracing/
 //! [`Subscriber`]: https://docs.rs/tracing-core/latest/tracing_core/subscriber/trait.Subscriber.html
@@ -41,6 +46,8 @@
 //! [`chrono`]: https://crates.io/crates/chrono
 //! [`env_logger` crate]: https://crates.io/crates/env_logger
 //! [`parking_lot`]: https://crates.io/crates/parking_lot
+//! [`tokio`]: https://crates.io/crates/tokio
+//! [`tracing-futures`]: https://crates.io/crates/tracing-futures
 #![doc(html_root_url = "https://docs.rs/tracing-subscriber/0.1.3")]
 #![warn(
     missing_debug_implementations,
@@ -93,25 +100,84 @@ macro_rules! try_lock {
     };
 }
 
+pub mod callsites;
+pub mod capture;
+pub mod dedup;
+pub mod drop_counters;
+pub mod enabled;
+pub mod field;
+pub mod field_filter;
 pub mod filter;
 #[cfg(feature = "fmt")]
 pub mod fmt;
+pub mod histogram;
+pub mod io_writer;
 pub mod layer;
+pub mod level_override;
+pub mod mirror;
+pub mod otel;
+pub mod panics;
 pub mod prelude;
 pub mod reload;
+pub mod sampling;
+pub mod span_events;
 pub(crate) mod sync;
+#[cfg(feature = "task")]
+pub mod task;
+pub mod tee;
 pub(crate) mod thread;
+pub mod timing;
 
 #[cfg(feature = "env-filter")]
 #[allow(deprecated)]
 pub use filter::{EnvFilter, Filter};
 
+pub use enabled::would_log;
 pub use layer::Layer;
 
 #[cfg(feature = "fmt")]
 pub use fmt::Subscriber as FmtSubscriber;
 
+use std::collections::HashMap;
 use std::default::Default;
+use tracing_core::{dispatcher, Metadata, Subscriber};
+
+/// Sets `subscriber` as the default for the current thread for the lifetime
+/// of the returned guard.
+///
+/// Unlike [`tracing_core::dispatcher::with_default`], which only installs
+/// the subscriber for the duration of a closure, the subscriber set by this
+/// function remains the default until the returned guard is dropped. This is
+/// especially handy in tests, which would otherwise need to wrap every
+/// assertion in a `with_default` closure.
+///
+/// [`tracing_core::dispatcher::with_default`]: https://docs.rs/tracing-core/0.1.5/tracing_core/dispatcher/fn.with_default.html
+///
+/// # Examples
+///
+/// ```
+/// # struct MySubscriber;
+/// # impl tracing_core::Subscriber for MySubscriber {
+/// #   fn register_callsite(&self, _: &'static tracing_core::Metadata<'static>) -> tracing_core::subscriber::Interest { tracing_core::subscriber::Interest::always() }
+/// #   fn enabled(&self, _: &tracing_core::Metadata<'_>) -> bool { true }
+/// #   fn new_span(&self, _: &tracing_core::span::Attributes<'_>) -> tracing_core::span::Id { tracing_core::span::Id::from_u64(1) }
+/// #   fn record(&self, _: &tracing_core::span::Id, _: &tracing_core::span::Record<'_>) {}
+/// #   fn record_follows_from(&self, _: &tracing_core::span::Id, _: &tracing_core::span::Id) {}
+/// #   fn event(&self, _: &tracing_core::Event<'_>) {}
+/// #   fn enter(&self, _: &tracing_core::span::Id) {}
+/// #   fn exit(&self, _: &tracing_core::span::Id) {}
+/// # }
+/// let guard = tracing_subscriber::set_default(MySubscriber);
+/// // ... code that emits spans and events runs against `MySubscriber` ...
+/// drop(guard); // the previous default subscriber is restored here.
+/// ```
+pub fn set_default<S>(subscriber: S) -> dispatcher::DefaultGuard
+where
+    S: Subscriber + Send + Sync + 'static,
+{
+    dispatcher::set_default(&dispatcher::Dispatch::new(subscriber))
+}
+
 /// Tracks the currently executing span on a per-thread basis.
 #[derive(Debug)]
 pub struct CurrentSpan {
@@ -143,6 +209,21 @@ impl CurrentSpan {
             let _ = current.pop();
         });
     }
+
+    /// Empties the current thread's span stack.
+    ///
+    /// This is **not** for general use — exiting spans out of order leaves
+    /// them open forever, since nothing else will ever call their matching
+    /// `exit`. It exists for executors that multiplex unrelated tasks onto
+    /// the same thread: between tasks, a leftover span stack from a task
+    /// that didn't clean up after itself (e.g. it panicked, or was dropped
+    /// without running to completion) would otherwise leak into the next
+    /// task's context. Calling `clear()` at a task boundary is a safety net
+    /// against that, not a substitute for entering and exiting spans
+    /// correctly within a task.
+    pub fn clear(&self) {
+        self.current.with(|current| current.clear());
+    }
 }
 
 impl Default for CurrentSpan {
@@ -151,6 +232,119 @@ impl Default for CurrentSpan {
     }
 }
 
+/// A lookup table mapping span [`Id`](::Id)s to their [`Metadata`].
+///
+/// [`CurrentSpan`] only stores the `Id`s of the spans a thread is currently
+/// inside of; it doesn't know anything about those spans' names, targets, or
+/// other metadata. A `Subscriber` implementation that wants `CurrentSpan`'s
+/// cheap per-thread tracking, but also needs to resolve an `Id` back to the
+/// `Metadata` it was created with (for example, to print a span's name),
+/// can register spans in a `Registry` as they're created, and look them up
+/// again using the `Id`s produced by `CurrentSpan`.
+#[derive(Debug)]
+pub struct Registry {
+    spans: sync::RwLock<HashMap<Id, &'static Metadata<'static>>>,
+}
+
+impl Registry {
+    /// Returns a new, empty `Registry`.
+    pub fn new() -> Self {
+        Self {
+            spans: sync::RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Associates the given `id` with `metadata`, so that it can later be
+    /// resolved by [`Registry::get`].
+    pub fn insert(&self, id: Id, metadata: &'static Metadata<'static>) {
+        try_lock!(self.spans.write()).insert(id, metadata);
+    }
+
+    /// Removes the entry for `id`, if one exists.
+    ///
+    /// This should be called when a span is closed, so the registry does
+    /// not grow unboundedly over the life of the program.
+    pub fn remove(&self, id: &Id) {
+        try_lock!(self.spans.write()).remove(id);
+    }
+
+    /// Returns the `Metadata` associated with `id`, if any is registered.
+    pub fn get(&self, id: &Id) -> Option<&'static Metadata<'static>> {
+        try_lock!(self.spans.read(), else return None)
+            .get(id)
+            .copied()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 mod sealed {
     pub trait Sealed<A = ()> {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer::tests::NopSubscriber;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::{span, subscriber::Interest, Event};
+
+    struct CollectSubscriber(Arc<Mutex<usize>>);
+
+    impl Subscriber for CollectSubscriber {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, _: &Event<'_>) {
+            *self.0.lock().unwrap() += 1;
+        }
+
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn set_default_restores_prior_default_on_drop() {
+        let outer = set_default(NopSubscriber);
+        assert!(dispatcher::get_default(|d| d.is::<NopSubscriber>()));
+
+        let count = Arc::new(Mutex::new(0));
+        let inner = set_default(CollectSubscriber(count.clone()));
+        assert!(dispatcher::get_default(|d| d.is::<CollectSubscriber>()));
+
+        tracing::info!("captured by the inner subscriber");
+        assert_eq!(*count.lock().unwrap(), 1);
+
+        drop(inner);
+        assert!(dispatcher::get_default(|d| d.is::<NopSubscriber>()));
+
+        drop(outer);
+    }
+
+    #[test]
+    fn clear_empties_the_current_thread_span_stack() {
+        let current = CurrentSpan::new();
+        current.enter(Id::from_u64(1));
+        current.enter(Id::from_u64(2));
+        assert_eq!(current.id(), Some(Id::from_u64(2)));
+
+        current.clear();
+        assert_eq!(current.id(), None);
+    }
+}
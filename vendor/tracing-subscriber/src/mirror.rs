@@ -0,0 +1,119 @@
+//! A `Layer` that mirrors events above a threshold to a second, independent
+//! `Subscriber`.
+use crate::filter::LevelFilter;
+use crate::layer::{Context, Layer};
+use tracing_core::{subscriber::Subscriber, Event};
+
+/// A `Layer` that forwards events at or above `threshold` to a secondary
+/// `Subscriber`, in addition to letting the primary stack handle every
+/// event as usual.
+///
+/// This is for routing high-severity events to a second destination — an
+/// alerting pipeline, a paging integration — without otherwise disturbing
+/// the primary subscriber's own filtering or formatting. `threshold` only
+/// gates what reaches the secondary subscriber; the primary stack still
+/// decides, independently, whether an event reaches it at all.
+///
+/// The secondary subscriber only ever sees [`event`] calls: `MirrorLayer`
+/// does not forward spans, so a mirrored event's [`current_span`] lookups on
+/// the secondary subscriber's side will not resolve to anything meaningful.
+/// This matches the common case of an alerting destination that only cares
+/// about the event's own fields and message, not its span context.
+///
+/// [`event`]: tracing_core::subscriber::Subscriber::event
+/// [`current_span`]: tracing_core::subscriber::Subscriber::current_span
+#[derive(Debug)]
+pub struct MirrorLayer<A> {
+    alert: A,
+    threshold: LevelFilter,
+}
+
+impl<A> MirrorLayer<A> {
+    /// Returns a new `MirrorLayer` that forwards events at or above
+    /// `threshold` to `alert`.
+    pub fn new(alert: A, threshold: impl Into<LevelFilter>) -> Self {
+        Self {
+            alert,
+            threshold: threshold.into(),
+        }
+    }
+}
+
+impl<S, A> Layer<S> for MirrorLayer<A>
+where
+    S: Subscriber,
+    A: Subscriber,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if self.threshold >= *event.metadata().level() {
+            self.alert.event(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::{span, subscriber::Interest, Metadata};
+
+    #[derive(Clone, Default)]
+    struct CountingSubscriber {
+        events: Arc<Mutex<usize>>,
+    }
+
+    impl Subscriber for CountingSubscriber {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, _: &Event<'_>) {
+            *self.events.lock().unwrap() += 1;
+        }
+
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn an_error_event_reaches_both_the_primary_and_the_alert_subscriber() {
+        let primary = CountingSubscriber::default();
+        let alert = CountingSubscriber::default();
+        let mirror = MirrorLayer::new(alert.clone(), LevelFilter::ERROR);
+        let dispatch = tracing_core::dispatcher::Dispatch::new(primary.clone().with(mirror));
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::error!("disk is on fire");
+        });
+
+        assert_eq!(*primary.events.lock().unwrap(), 1);
+        assert_eq!(*alert.events.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn an_info_event_reaches_only_the_primary() {
+        let primary = CountingSubscriber::default();
+        let alert = CountingSubscriber::default();
+        let mirror = MirrorLayer::new(alert.clone(), LevelFilter::ERROR);
+        let dispatch = tracing_core::dispatcher::Dispatch::new(primary.clone().with(mirror));
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("just FYI");
+        });
+
+        assert_eq!(*primary.events.lock().unwrap(), 1);
+        assert_eq!(*alert.events.lock().unwrap(), 0);
+    }
+}
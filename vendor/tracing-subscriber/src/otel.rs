@@ -0,0 +1,172 @@
+//! A `Layer` that enriches spans with OpenTelemetry-style trace and span
+//! IDs, for correlating `tracing` data with distributed traces.
+use crate::layer::{Context, Layer};
+use crate::sync::RwLock;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing_core::{span, subscriber::Subscriber};
+
+/// A `Layer` that assigns a 128-bit trace ID and a 64-bit span ID to every
+/// span.
+///
+/// IDs are generated when a span is created (in [`new_span`]) and are stable
+/// for the span's lifetime. A span inherits its parent's trace ID (following
+/// the explicit parent given to the span, or the contextually current span if
+/// none was given); a root span (one with no parent) is assigned a fresh
+/// trace ID. Every span, root or not, gets its own span ID.
+///
+/// ## A note on events
+///
+/// In this version of `tracing-core`, an [`Event`]'s fields are fixed by its
+/// callsite's static [`Metadata`] — a `Layer` has no way to splice a
+/// `trace_id`/`span_id` field into an event that didn't already declare one.
+/// So rather than rewriting events, this layer exposes the current span's IDs
+/// via [`trace_id`] and [`span_id`], which a downstream `Layer`, formatter, or
+/// exporter can call (using [`Context::current_span`] to find the span to
+/// look up) when it wants to attach correlation IDs to whatever it emits.
+/// This mirrors how [`Registry`] and [`CurrentSpan`] are paired elsewhere in
+/// this crate: one type tracks the state, another queries it.
+///
+/// This layer does not perform OTLP export itself; it only tracks the
+/// correlation IDs that an exporter would need.
+///
+/// [`new_span`]: #method.new_span
+/// [`Event`]: https://docs.rs/tracing-core/latest/tracing_core/event/struct.Event.html
+/// [`Metadata`]: https://docs.rs/tracing-core/latest/tracing_core/metadata/struct.Metadata.html
+/// [`trace_id`]: #method.trace_id
+/// [`span_id`]: #method.span_id
+/// [`Context::current_span`]: ../layer/struct.Context.html#method.current_span
+/// [`Registry`]: ../struct.Registry.html
+/// [`CurrentSpan`]: ../struct.CurrentSpan.html
+#[derive(Clone, Debug)]
+pub struct OtelContextLayer {
+    ids: Arc<RwLock<HashMap<span::Id, SpanIds>>>,
+}
+
+impl Default for OtelContextLayer {
+    fn default() -> Self {
+        Self {
+            ids: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SpanIds {
+    trace_id: u128,
+    span_id: u64,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+fn next_u64() -> u64 {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+fn new_trace_id() -> u128 {
+    (u128::from(next_u64()) << 64) | u128::from(next_u64())
+}
+
+impl OtelContextLayer {
+    /// Returns a new `OtelContextLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the trace ID assigned to the span with the given `id`, if it
+    /// is currently tracked by this layer.
+    pub fn trace_id(&self, id: &span::Id) -> Option<u128> {
+        try_lock!(self.ids.read(), else return None)
+            .get(id)
+            .map(|ids| ids.trace_id)
+    }
+
+    /// Returns the span ID assigned to the span with the given `id`, if it
+    /// is currently tracked by this layer.
+    pub fn span_id(&self, id: &span::Id) -> Option<u64> {
+        try_lock!(self.ids.read(), else return None)
+            .get(id)
+            .map(|ids| ids.span_id)
+    }
+
+    fn parent_of<S: Subscriber>(
+        &self,
+        attrs: &span::Attributes<'_>,
+        ctx: &Context<'_, S>,
+    ) -> Option<span::Id> {
+        if let Some(parent) = attrs.parent() {
+            return Some(parent.clone());
+        }
+        if attrs.is_contextual() {
+            return ctx.current_span().id().cloned();
+        }
+        None
+    }
+}
+
+impl<S: Subscriber> Layer<S> for OtelContextLayer {
+    fn new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let parent_trace_id = self
+            .parent_of(attrs, &ctx)
+            .and_then(|parent| self.trace_id(&parent));
+
+        let ids = SpanIds {
+            trace_id: parent_trace_id.unwrap_or_else(new_trace_id),
+            span_id: next_u64(),
+        };
+        try_lock!(self.ids.write()).insert(id.clone(), ids);
+    }
+
+    fn on_close(&self, id: span::Id, _ctx: Context<'_, S>) {
+        try_lock!(self.ids.write()).remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn child_span_shares_trace_id_but_not_span_id() {
+        let layer = OtelContextLayer::new();
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer.clone()),
+        );
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let span = tracing::info_span!("parent");
+            let _enter = span.enter();
+            let parent_id = span.id().expect("span should have an id");
+            let parent_trace_id = layer.trace_id(&parent_id).expect("parent should have a trace id");
+
+            let child = tracing::info_span!("child");
+            let child_id = child.id().expect("child span should have an id");
+
+            assert_eq!(layer.trace_id(&child_id), Some(parent_trace_id));
+            assert_ne!(layer.span_id(&child_id), layer.span_id(&parent_id));
+        });
+    }
+
+    #[test]
+    fn two_events_in_same_span_observe_the_same_trace_id() {
+        let layer = OtelContextLayer::new();
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer.clone()),
+        );
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let span = tracing::info_span!("work");
+            let _enter = span.enter();
+            let id = span.id().expect("span should have an id");
+
+            let first = layer.trace_id(&id);
+            tracing::info!("first event");
+            let second = layer.trace_id(&id);
+            tracing::info!("second event");
+
+            assert_eq!(first, second);
+        });
+    }
+}
@@ -0,0 +1,200 @@
+//! Reports Rust panics as `tracing` `ERROR` events.
+//!
+//! Panics normally go straight to the default panic handler (or whatever
+//! hook a previous call to [`std::panic::set_hook`] installed) and print to
+//! stderr, bypassing `tracing` entirely. A panic that happens deep inside a
+//! span tree is then missing from structured logs, with no way to tell which
+//! span it occurred in.
+//!
+//! [`install_panic_hook`] installs a hook that emits an `ERROR` event
+//! carrying the panic's message and source location before chaining to
+//! whatever hook was previously installed, so it composes with panic hooks
+//! set up for other purposes (e.g. `color-backtrace`).
+//!
+//! This module depends only on `tracing-core`, so it works with any
+//! `Subscriber`, not just the ones provided by the [`fmt`](crate::fmt)
+//! module.
+use tracing_core::callsite::Callsite;
+use tracing_core::field;
+use tracing_core::{subscriber::Interest, Event, Kind, Level, Metadata};
+
+use std::panic;
+
+struct PanicCallsite;
+static PANIC_CALLSITE: PanicCallsite = PanicCallsite;
+static PANIC_FIELDS: &[&str] = &["message", "panic.file", "panic.line", "backtrace"];
+static PANIC_META: Metadata<'static> = Metadata::new(
+    "panic",
+    "panic",
+    Level::ERROR,
+    None,
+    None,
+    None,
+    field::FieldSet::new(PANIC_FIELDS, tracing_core::identify_callsite!(&PANIC_CALLSITE)),
+    Kind::EVENT,
+);
+
+impl Callsite for PanicCallsite {
+    fn set_interest(&self, _: Interest) {}
+    fn metadata(&self) -> &'static Metadata<'static> {
+        &PANIC_META
+    }
+}
+
+/// Installs a panic hook that emits an `ERROR`-level tracing event for every
+/// panic on the thread it occurs on, then chains to the hook that was
+/// previously installed (the default hook, if none was).
+///
+/// The emitted event has target and name `"panic"`, and carries:
+///
+/// - `message`: the panic payload, as passed to `panic!`.
+/// - `panic.file` / `panic.line`: the panic's source location, if known.
+/// - `backtrace`: a captured backtrace, if the `RUST_BACKTRACE` environment
+///   variable is set (see [`std::backtrace::Backtrace`]); otherwise absent.
+///
+/// Because panic hooks run before the stack unwinds, the event is recorded
+/// with whatever span was current on the panicking thread at the moment of
+/// the panic — the same span context a `tracing::error!` call made from
+/// that point would have had.
+///
+/// Call this once, early in `main`, before anything else installs a panic
+/// hook of its own.
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        report_panic(info);
+        previous(info);
+    }));
+}
+
+fn report_panic(info: &panic::PanicInfo<'_>) {
+    let message = panic_message(info);
+    let file = info.location().map(|loc| loc.file());
+    let line = info.location().map(|loc| loc.line());
+    let backtrace = if std::env::var_os("RUST_BACKTRACE").is_some() {
+        Some(std::backtrace::Backtrace::force_capture().to_string())
+    } else {
+        None
+    };
+    let backtrace = backtrace.as_deref();
+
+    let fields = PANIC_META.fields();
+    let message_field = fields.field("message").expect("message field must exist");
+    let file_field = fields
+        .field("panic.file")
+        .expect("panic.file field must exist");
+    let line_field = fields
+        .field("panic.line")
+        .expect("panic.line field must exist");
+    let backtrace_field = fields
+        .field("backtrace")
+        .expect("backtrace field must exist");
+
+    let values: [(&field::Field, Option<&dyn field::Value>); 4] = [
+        (
+            &message_field,
+            Some(&message.as_str() as &dyn field::Value),
+        ),
+        (&file_field, file.as_ref().map(|f| f as &dyn field::Value)),
+        (&line_field, line.as_ref().map(|l| l as &dyn field::Value)),
+        (
+            &backtrace_field,
+            backtrace.as_ref().map(|b| b as &dyn field::Value),
+        ),
+    ];
+    Event::dispatch(&PANIC_META, &fields.value_set(&values));
+}
+
+fn panic_message(info: &panic::PanicInfo<'_>) -> String {
+    if let Some(s) = info.payload().downcast_ref::<&str>() {
+        (*s).to_owned()
+    } else if let Some(s) = info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Box<dyn Any>".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::field::Visit;
+    use tracing_core::{dispatcher::Dispatch, span};
+
+    #[derive(Default)]
+    struct RecordingSubscriber {
+        events: Arc<Mutex<Vec<(&'static str, String)>>>,
+    }
+
+    #[derive(Default)]
+    struct MessageVisitor(Option<String>);
+    impl Visit for MessageVisitor {
+        fn record_str(&mut self, field: &field::Field, value: &str) {
+            if field.name() == "message" {
+                self.0 = Some(value.to_owned());
+            }
+        }
+        fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.0 = Some(format!("{:?}", value));
+            }
+        }
+    }
+
+    impl tracing_core::Subscriber for RecordingSubscriber {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, event: &Event<'_>) {
+            let mut visitor = MessageVisitor::default();
+            event.record(&mut visitor);
+            if let Some(message) = visitor.0 {
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push((event.metadata().name(), message));
+            }
+        }
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn a_panic_is_reported_as_an_error_event() {
+        let recorder = RecordingSubscriber::default();
+        let events = recorder.events.clone();
+        let dispatch = Dispatch::new(recorder);
+
+        // Run the hook installation and the panic itself on a child thread so
+        // that the panic is contained there rather than aborting the test
+        // process. The panic hook is process-wide, so the default hook is
+        // restored before installing (so that a hook left behind by another
+        // test doesn't also report this panic) and again afterwards.
+        let handle = std::thread::spawn(move || {
+            tracing_core::dispatcher::with_default(&dispatch, || {
+                let _ = std::panic::take_hook();
+                install_panic_hook();
+                let result = std::panic::catch_unwind(|| {
+                    panic!("something went wrong");
+                });
+                assert!(result.is_err());
+            });
+        });
+        handle.join().unwrap();
+        let _ = std::panic::take_hook();
+
+        let seen = events.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].0, "panic");
+        assert!(seen[0].1.contains("something went wrong"));
+    }
+}
@@ -25,24 +25,43 @@ use tracing_core::{
     Event, Metadata,
 };
 
+/// A callback invoked after a `Handle`'s wrapped layer is reloaded, with a
+/// reference to the layer's new value.
+type ReloadCallback<L> = Box<dyn Fn(&L) + Send + Sync>;
+
 /// Wraps a `Layer`, allowing it to be reloaded dynamically at runtime.
-#[derive(Debug)]
 pub struct Layer<L, S> {
     // TODO(eliza): this once used a `crossbeam_util::ShardedRwLock`. We may
     // eventually wish to replace it with a sharded lock implementation on top
     // of our internal `RwLock` wrapper type. If possible, we should profile
     // this first to determine if it's necessary.
     inner: Arc<RwLock<L>>,
+    on_reload: Arc<RwLock<Vec<ReloadCallback<L>>>>,
     _s: PhantomData<fn(S)>,
 }
 
 /// Allows reloading the state of an associated `Layer`.
-#[derive(Debug)]
 pub struct Handle<L, S> {
     inner: Weak<RwLock<L>>,
+    on_reload: Weak<RwLock<Vec<ReloadCallback<L>>>>,
     _s: PhantomData<fn(S)>,
 }
 
+impl<L, S> fmt::Debug for Layer<L, S>
+where
+    L: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Layer").field("inner", &self.inner).finish()
+    }
+}
+
+impl<L, S> fmt::Debug for Handle<L, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle").field("inner", &self.inner).finish()
+    }
+}
+
 /// Indicates that an error occurred when reloading a layer.
 #[derive(Debug)]
 pub struct Error {
@@ -123,6 +142,7 @@ where
     pub fn new(inner: L) -> (Self, Handle<L, S>) {
         let this = Self {
             inner: Arc::new(RwLock::new(inner)),
+            on_reload: Arc::new(RwLock::new(Vec::new())),
             _s: PhantomData,
         };
         let handle = this.handle();
@@ -133,6 +153,7 @@ where
     pub fn handle(&self) -> Handle<L, S> {
         Handle {
             inner: Arc::downgrade(&self.inner),
+            on_reload: Arc::downgrade(&self.on_reload),
             _s: PhantomData,
         }
     }
@@ -166,6 +187,41 @@ where
         drop(lock);
 
         callsite::rebuild_interest_cache();
+
+        // Run any registered `on_reload` callbacks with the new value, after
+        // the write lock above has already been released, so a callback that
+        // itself calls back into this `Handle` (to read the current layer,
+        // say) can't deadlock against it.
+        if let Some(on_reload) = self.on_reload.upgrade() {
+            let current = try_lock!(inner.read(), else return Ok(()));
+            for callback in try_lock!(on_reload.read(), else return Ok(())).iter() {
+                callback(&*current);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Registers `callback` to be invoked with a reference to this handle's
+    /// layer every time it is successfully reloaded via [`reload`] or
+    /// [`modify`]. Callbacks run after the write lock guarding the layer has
+    /// been released, so they may safely call back into this `Handle` (for
+    /// example, via [`with_current`]) without deadlocking.
+    ///
+    /// Returns an error if the subscriber owning the wrapped layer has
+    /// already been dropped.
+    ///
+    /// [`reload`]: #method.reload
+    /// [`modify`]: #method.modify
+    /// [`with_current`]: #method.with_current
+    pub fn on_reload<F>(&self, callback: F) -> Result<(), Error>
+    where
+        F: Fn(&L) + Send + Sync + 'static,
+    {
+        let on_reload = self.on_reload.upgrade().ok_or(Error {
+            kind: ErrorKind::SubscriberGone,
+        })?;
+        try_lock!(on_reload.write(), else return Err(Error::poisoned())).push(Box::new(callback));
         Ok(())
     }
 
@@ -193,6 +249,7 @@ impl<L, S> Clone for Handle<L, S> {
     fn clone(&self) -> Self {
         Handle {
             inner: self.inner.clone(),
+            on_reload: self.on_reload.clone(),
             _s: PhantomData,
         }
     }
@@ -295,4 +352,32 @@ mod test {
             assert_eq!(FILTER2_CALLS.load(Ordering::Relaxed), 1);
         })
     }
+
+    #[test]
+    fn on_reload_runs_after_the_write_lock_is_released() {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        enum Mode {
+            Quiet,
+            Loud,
+        }
+        impl<S: Subscriber> crate::Layer<S> for Mode {}
+
+        let (layer, handle) = Layer::new(Mode::Quiet);
+        let _subscriber =
+            tracing_core::dispatcher::Dispatch::new(crate::layer::tests::NopSubscriber.with(layer));
+
+        let seen = Arc::new(RwLock::new(Vec::new()));
+        let seen2 = seen.clone();
+        handle
+            .on_reload(move |mode: &Mode| {
+                // This would deadlock if called while `modify`'s write lock
+                // were still held.
+                seen2.write().unwrap().push(*mode);
+            })
+            .expect("handle should still be live");
+
+        handle.reload(Mode::Loud).expect("should reload");
+
+        assert_eq!(*seen.read().unwrap(), vec![Mode::Loud]);
+    }
 }
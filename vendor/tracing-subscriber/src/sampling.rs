@@ -0,0 +1,329 @@
+//! A `Layer` that makes a single keep/drop decision per trace and filters
+//! events consistently with it, for probabilistic trace sampling.
+use crate::drop_counters::{DropCounters, DropReason};
+use crate::layer::{Context, Layer};
+use crate::sync::RwLock;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing_core::{
+    field, span,
+    subscriber::{Interest, Subscriber},
+    Metadata,
+};
+
+/// A `Layer` that samples whole traces rather than individual events.
+///
+/// When a root span (one with no parent) is created, this layer rolls a
+/// random number and compares it against `sample_rate` to decide whether the
+/// trace is "kept". Every span in the trace inherits its root's decision by
+/// following its parent's entry, so the decision only needs to be rolled
+/// once per trace. In `enabled`, events are filtered against the decision
+/// recorded for whichever span is currently in scope, so a non-sampled trace
+/// produces no events at all. Spans themselves are always enabled.
+///
+/// ## A note on "extensions" and ancestor lookup
+///
+/// This version of `tracing-subscriber` predates [`Registry`] and
+/// [`LookupSpan`]: there is no per-span extensions map, and a `Layer` cannot
+/// walk a span's full ancestor chain — only its immediate parent is visible,
+/// via [`Attributes::parent`] or the contextually current span. This layer
+/// gets the same effect without that machinery: because [`new_span`] fires
+/// exactly once for every span, resolving one hop of parent and propagating
+/// the stored decision at creation time is transitively equivalent to
+/// walking all the way to the root. The decision itself is kept in this
+/// layer's own map, keyed by span ID, in place of a per-span extensions
+/// entry.
+///
+/// ## Forcing a decision with the `sample` field
+///
+/// A span created with a `sample = "always"` or `sample = "never"` field
+/// overrides the probabilistic decision for that span (and, by the
+/// inheritance described above, for everything nested inside it), so that
+/// high-value call paths can opt out of global sampling regardless of the
+/// configured `sample_rate`. Any other value for `sample` is ignored and
+/// falls back to the inherited or rolled decision.
+///
+/// [`new_span`]: #method.new_span
+/// [`Attributes::parent`]: https://docs.rs/tracing-core/latest/tracing_core/span/struct.Attributes.html#method.parent
+/// [`Registry`]: ../struct.Registry.html
+/// [`LookupSpan`]: ../registry/trait.LookupSpan.html
+#[derive(Clone)]
+pub struct SamplingFilter<R = fn() -> f64> {
+    sample_rate: f64,
+    sample: R,
+    decisions: Arc<RwLock<HashMap<span::Id, bool>>>,
+    drop_counters: Option<DropCounters>,
+}
+
+impl SamplingFilter {
+    /// Returns a new `SamplingFilter` that keeps approximately `sample_rate`
+    /// of traces.
+    ///
+    /// `sample_rate` is clamped to `[0.0, 1.0]`.
+    pub fn new(sample_rate: f64) -> Self {
+        Self::with_sampler(sample_rate, random)
+    }
+}
+
+impl<R> SamplingFilter<R>
+where
+    R: Fn() -> f64,
+{
+    /// Returns a new `SamplingFilter` that draws its random numbers from
+    /// `sample` instead of the default RNG, for deterministic testing.
+    ///
+    /// `sample` should return a value uniformly distributed in `[0.0, 1.0)`.
+    pub fn with_sampler(sample_rate: f64, sample: R) -> Self {
+        Self {
+            sample_rate: sample_rate.max(0.0).min(1.0),
+            sample,
+            decisions: Arc::new(RwLock::new(HashMap::new())),
+            drop_counters: None,
+        }
+    }
+
+    /// Returns this filter with `counters` recording a
+    /// [`DropReason::Sampled`] every time it filters out an event belonging
+    /// to a trace it decided not to sample.
+    ///
+    /// See the [`drop_counters`](crate::drop_counters) module for emitting a
+    /// shutdown summary across this and other filtering features.
+    pub fn with_drop_counters(mut self, counters: DropCounters) -> Self {
+        self.drop_counters = Some(counters);
+        self
+    }
+
+    /// Returns whether the trace that the span with the given `id` belongs
+    /// to was sampled, if this layer has a decision recorded for it.
+    pub fn is_sampled(&self, id: &span::Id) -> Option<bool> {
+        try_lock!(self.decisions.read(), else return None)
+            .get(id)
+            .copied()
+    }
+
+    fn parent_of<S: Subscriber>(
+        &self,
+        attrs: &span::Attributes<'_>,
+        ctx: &Context<'_, S>,
+    ) -> Option<span::Id> {
+        if let Some(parent) = attrs.parent() {
+            return Some(parent.clone());
+        }
+        if attrs.is_contextual() {
+            return ctx.current_span().id().cloned();
+        }
+        None
+    }
+}
+
+impl<S, R> Layer<S> for SamplingFilter<R>
+where
+    S: Subscriber,
+    R: Fn() -> f64 + 'static,
+{
+    fn new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut overrider = SampleOverride::default();
+        attrs.record(&mut overrider);
+
+        let decision = overrider.decision().unwrap_or_else(|| {
+            let inherited = self
+                .parent_of(attrs, &ctx)
+                .and_then(|parent| self.is_sampled(&parent));
+            inherited.unwrap_or_else(|| (self.sample)() < self.sample_rate)
+        });
+        try_lock!(self.decisions.write()).insert(id.clone(), decision);
+    }
+
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if metadata.is_event() {
+            // Whether an event is enabled depends on the span it's recorded
+            // in, so its interest can't be cached as a single global answer.
+            Interest::sometimes()
+        } else {
+            Interest::always()
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
+        if !metadata.is_event() {
+            return true;
+        }
+        let sampled = match ctx.current_span().id().and_then(|id| self.is_sampled(id)) {
+            Some(sampled) => sampled,
+            None => true,
+        };
+        if !sampled {
+            if let Some(counters) = &self.drop_counters {
+                counters.record(DropReason::Sampled);
+            }
+        }
+        sampled
+    }
+
+    fn on_close(&self, id: span::Id, _ctx: Context<'_, S>) {
+        try_lock!(self.decisions.write()).remove(&id);
+    }
+}
+
+/// Looks for a `sample` field among a span's attributes at creation, and
+/// records whether it forces a `"always"` or `"never"` sampling decision.
+///
+/// Any other value (or no `sample` field at all) leaves [`decision`] as
+/// `None`, so the caller falls back to its normal inherit-or-roll logic.
+///
+/// [`decision`]: SampleOverride::decision
+#[derive(Default)]
+struct SampleOverride {
+    decision: Option<bool>,
+}
+
+impl SampleOverride {
+    fn decision(&self) -> Option<bool> {
+        self.decision
+    }
+
+    fn record(&mut self, value: &str) {
+        match value {
+            "always" => self.decision = Some(true),
+            "never" => self.decision = Some(false),
+            _ => {}
+        }
+    }
+}
+
+impl field::Visit for SampleOverride {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        if field.name() == "sample" {
+            self.record(value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn fmt::Debug) {
+        if field.name() == "sample" {
+            self.record(format!("{:?}", value).trim_matches('"'));
+        }
+    }
+}
+
+static NEXT_SEED: AtomicU64 = AtomicU64::new(0x2545_f491_4f6c_dd1d);
+
+/// A small xorshift-based generator, used so this layer doesn't need to pull
+/// in an external RNG crate just to roll a sampling decision.
+fn random() -> f64 {
+    let mut x = NEXT_SEED.fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed);
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    NEXT_SEED.store(x, Ordering::Relaxed);
+    (x >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn sampler(value: f64) -> impl Fn() -> f64 + Clone {
+        move || value
+    }
+
+    #[test]
+    fn root_span_decision_follows_sample_rate() {
+        let kept = SamplingFilter::with_sampler(0.5, sampler(0.1));
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(kept.clone()),
+        );
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let span = tracing::info_span!("root");
+            let id = span.id().expect("span should have an id");
+            assert_eq!(kept.is_sampled(&id), Some(true));
+        });
+
+        let dropped = SamplingFilter::with_sampler(0.5, sampler(0.9));
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(dropped.clone()),
+        );
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let span = tracing::info_span!("root");
+            let id = span.id().expect("span should have an id");
+            assert_eq!(dropped.is_sampled(&id), Some(false));
+        });
+    }
+
+    #[test]
+    fn decision_is_cleared_on_close() {
+        let layer = SamplingFilter::with_sampler(1.0, sampler(0.0));
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer.clone()),
+        );
+
+        let id = tracing_core::dispatcher::with_default(&subscriber, || {
+            let span = tracing::info_span!("root");
+            span.id().expect("span should have an id")
+        });
+
+        assert_eq!(layer.is_sampled(&id), None);
+    }
+
+    #[test]
+    fn sample_always_field_overrides_a_dropped_decision() {
+        let layer = SamplingFilter::with_sampler(0.0, sampler(0.9));
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer.clone()),
+        );
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let span = tracing::info_span!("root", sample = "always");
+            let id = span.id().expect("span should have an id");
+            assert_eq!(layer.is_sampled(&id), Some(true));
+        });
+    }
+
+    #[test]
+    fn sample_never_field_overrides_a_kept_decision() {
+        let layer = SamplingFilter::with_sampler(1.0, sampler(0.0));
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer.clone()),
+        );
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let span = tracing::info_span!("root", sample = "never");
+            let id = span.id().expect("span should have an id");
+            assert_eq!(layer.is_sampled(&id), Some(false));
+        });
+    }
+
+    #[test]
+    fn drop_counters_record_events_filtered_out_by_sampling() {
+        use crate::drop_counters::{DropCounters, DropReason};
+
+        let counters = DropCounters::new();
+        let layer =
+            SamplingFilter::with_sampler(0.5, sampler(0.9)).with_drop_counters(counters.clone());
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer),
+        );
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let span = tracing::info_span!("root");
+            let _enter = span.enter();
+            tracing::info!("this event belongs to an unsampled trace");
+        });
+
+        assert_eq!(counters.count(DropReason::Sampled), 1);
+    }
+
+    #[test]
+    fn sample_override_propagates_to_children() {
+        let layer = SamplingFilter::with_sampler(0.0, sampler(0.9));
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer.clone()),
+        );
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let root = tracing::info_span!("root", sample = "always");
+            let _enter = root.enter();
+            let child = tracing::info_span!("child");
+            let id = child.id().expect("span should have an id");
+            assert_eq!(layer.is_sampled(&id), Some(true));
+        });
+    }
+}
@@ -0,0 +1,413 @@
+//! A `Layer` that emits each span's open, enter, exit, and close as synthetic
+//! events sharing a stable per-span correlation id.
+//!
+//! Some downstream tooling consumes a flat stream of events and has no
+//! notion of a `tracing` span at all. Without span awareness, there's no way
+//! for such tooling to reconstruct how long a span was open, which events
+//! happened inside it, or even what the span was named. This layer bridges
+//! the gap: it emits an `open` event once, when the span is created,
+//! carrying the span's name and its recorded fields (joined as
+//! `key=value`-space-separated text); then an `enter` event when the span is
+//! entered, an `exit` event when it's exited, and a `close` event when it's
+//! closed for good. Every one of these carries a `span.id` field set to the
+//! same value for a given span, so downstream tooling can join `open`'s
+//! id-to-name-and-fields mapping against the `enter`/`exit`/`close` events to
+//! reconstruct the interval without understanding spans at all.
+//!
+//! This crate predates `tracing`'s `#[instrument]`-level span-lifecycle
+//! event support (the `with_span_events`/`FmtSpan` options added to later
+//! `tracing-subscriber` releases); this layer provides the same
+//! observability independently of the `fmt` module, using only
+//! `tracing-core`, so it composes with any `Subscriber`.
+use crate::layer::{Context, Layer};
+use std::fmt::Write as _;
+use tracing_core::callsite::Callsite;
+use tracing_core::field::{self, Field};
+use tracing_core::{span, subscriber::Subscriber, Event, Kind, Level, Metadata};
+
+macro_rules! lifecycle_cs {
+    ($name:expr, $level:expr) => {{
+        struct Cs;
+        static CALLSITE: Cs = Cs;
+        static FIELD_NAMES: &[&str] = &["span.id", "marker", "span.name", "fields"];
+        static META: Metadata<'static> = Metadata::new(
+            $name,
+            "tracing_subscriber::span_events",
+            $level,
+            None,
+            None,
+            None,
+            field::FieldSet::new(FIELD_NAMES, tracing_core::identify_callsite!(&CALLSITE)),
+            Kind::EVENT,
+        );
+
+        impl Callsite for Cs {
+            fn set_interest(&self, _: tracing_core::subscriber::Interest) {}
+            fn metadata(&self) -> &'static Metadata<'static> {
+                &META
+            }
+        }
+
+        &CALLSITE as &'static dyn Callsite
+    }};
+}
+
+static ENTER_TRACE: &dyn Callsite = lifecycle_cs!("enter", Level::TRACE);
+static ENTER_DEBUG: &dyn Callsite = lifecycle_cs!("enter", Level::DEBUG);
+static ENTER_INFO: &dyn Callsite = lifecycle_cs!("enter", Level::INFO);
+static ENTER_WARN: &dyn Callsite = lifecycle_cs!("enter", Level::WARN);
+static ENTER_ERROR: &dyn Callsite = lifecycle_cs!("enter", Level::ERROR);
+
+static EXIT_TRACE: &dyn Callsite = lifecycle_cs!("exit", Level::TRACE);
+static EXIT_DEBUG: &dyn Callsite = lifecycle_cs!("exit", Level::DEBUG);
+static EXIT_INFO: &dyn Callsite = lifecycle_cs!("exit", Level::INFO);
+static EXIT_WARN: &dyn Callsite = lifecycle_cs!("exit", Level::WARN);
+static EXIT_ERROR: &dyn Callsite = lifecycle_cs!("exit", Level::ERROR);
+
+static CLOSE_TRACE: &dyn Callsite = lifecycle_cs!("close", Level::TRACE);
+static CLOSE_DEBUG: &dyn Callsite = lifecycle_cs!("close", Level::DEBUG);
+static CLOSE_INFO: &dyn Callsite = lifecycle_cs!("close", Level::INFO);
+static CLOSE_WARN: &dyn Callsite = lifecycle_cs!("close", Level::WARN);
+static CLOSE_ERROR: &dyn Callsite = lifecycle_cs!("close", Level::ERROR);
+
+static OPEN_TRACE: &dyn Callsite = lifecycle_cs!("open", Level::TRACE);
+static OPEN_DEBUG: &dyn Callsite = lifecycle_cs!("open", Level::DEBUG);
+static OPEN_INFO: &dyn Callsite = lifecycle_cs!("open", Level::INFO);
+static OPEN_WARN: &dyn Callsite = lifecycle_cs!("open", Level::WARN);
+static OPEN_ERROR: &dyn Callsite = lifecycle_cs!("open", Level::ERROR);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Lifecycle {
+    Open,
+    Enter,
+    Exit,
+    Close,
+}
+
+fn cs_for(kind: Lifecycle, level: Level) -> &'static dyn Callsite {
+    match (kind, level) {
+        (Lifecycle::Open, Level::TRACE) => OPEN_TRACE,
+        (Lifecycle::Open, Level::DEBUG) => OPEN_DEBUG,
+        (Lifecycle::Open, Level::INFO) => OPEN_INFO,
+        (Lifecycle::Open, Level::WARN) => OPEN_WARN,
+        (Lifecycle::Open, Level::ERROR) => OPEN_ERROR,
+        (Lifecycle::Enter, Level::TRACE) => ENTER_TRACE,
+        (Lifecycle::Enter, Level::DEBUG) => ENTER_DEBUG,
+        (Lifecycle::Enter, Level::INFO) => ENTER_INFO,
+        (Lifecycle::Enter, Level::WARN) => ENTER_WARN,
+        (Lifecycle::Enter, Level::ERROR) => ENTER_ERROR,
+        (Lifecycle::Exit, Level::TRACE) => EXIT_TRACE,
+        (Lifecycle::Exit, Level::DEBUG) => EXIT_DEBUG,
+        (Lifecycle::Exit, Level::INFO) => EXIT_INFO,
+        (Lifecycle::Exit, Level::WARN) => EXIT_WARN,
+        (Lifecycle::Exit, Level::ERROR) => EXIT_ERROR,
+        (Lifecycle::Close, Level::TRACE) => CLOSE_TRACE,
+        (Lifecycle::Close, Level::DEBUG) => CLOSE_DEBUG,
+        (Lifecycle::Close, Level::INFO) => CLOSE_INFO,
+        (Lifecycle::Close, Level::WARN) => CLOSE_WARN,
+        (Lifecycle::Close, Level::ERROR) => CLOSE_ERROR,
+    }
+}
+
+/// Returns the terse, single-token marker used for a lifecycle event's
+/// `marker` field when [`SpanEventsLayer::with_compact_markers`] is enabled,
+/// consistent with the compact formatter's own terse, inline style.
+///
+/// `open` also gets its own marker (`=span`), distinct from the enter/exit
+/// transition markers, since it's not a transition but the one-time
+/// id-to-name-and-fields mapping record. `exit` is rendered the same way as
+/// `enter`'s counterpart closing bracket would read out of context, so it
+/// reuses `close`'s marker rather than introducing a third transition token.
+fn compact_marker(kind: Lifecycle) -> &'static str {
+    match kind {
+        Lifecycle::Open => "=span",
+        Lifecycle::Enter => "+span",
+        Lifecycle::Exit | Lifecycle::Close => "-span",
+    }
+}
+
+/// A [`field::Visit`] that joins every recorded field into a single
+/// `key=value`-space-joined string, used to capture a span's fields at
+/// [`Lifecycle::Open`] without depending on the `fmt` module's own field
+/// formatting (this layer is meant to compose with any `Subscriber`, not
+/// just `fmt::Subscriber`).
+#[derive(Default)]
+struct FieldJoiner {
+    out: String,
+}
+
+impl field::Visit for FieldJoiner {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if !self.out.is_empty() {
+            self.out.push(' ');
+        }
+        let _ = write!(self.out, "{}={:?}", field.name(), value);
+    }
+}
+
+fn emit(
+    kind: Lifecycle,
+    level: Level,
+    span_id: u64,
+    compact: bool,
+    name_and_fields: Option<(&str, &str)>,
+    record: impl FnOnce(&Event<'_>),
+) {
+    let cs = cs_for(kind, level);
+    let meta = cs.metadata();
+    let fields = meta.fields();
+    let id_field = fields.field("span.id").expect("span.id field must exist");
+    let marker_field = fields.field("marker").expect("marker field must exist");
+    let name_field = fields.field("span.name").expect("span.name field must exist");
+    let fields_field = fields.field("fields").expect("fields field must exist");
+    let marker: &str = if compact {
+        compact_marker(kind)
+    } else {
+        meta.name()
+    };
+
+    let name_value: Option<&dyn field::Value> =
+        name_and_fields.as_ref().map(|(name, _)| name as &dyn field::Value);
+    let fields_value: Option<&dyn field::Value> =
+        name_and_fields.as_ref().map(|(_, joined)| joined as &dyn field::Value);
+    let values = [
+        (&id_field, Some(&span_id as &dyn field::Value)),
+        (&marker_field, Some(&marker as &dyn field::Value)),
+        (&name_field, name_value),
+        (&fields_field, fields_value),
+    ];
+    record(&Event::new(meta, &fields.value_set(&values)));
+}
+
+fn dispatch(event: &Event<'_>) {
+    tracing_core::dispatcher::get_default(|current| current.event(event));
+}
+
+/// A `Layer` that emits an `open`/`enter`/`exit`/`close` event for every
+/// span, each carrying a `span.id` field with the same value for a given
+/// span, and a `marker` field naming which lifecycle transition it is.
+/// `open` additionally carries `span.name` and `fields`, mapping the id to
+/// the span's name and recorded fields.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Clone, Debug)]
+pub struct SpanEventsLayer {
+    level: Level,
+    compact: bool,
+}
+
+impl SpanEventsLayer {
+    /// Returns a new `SpanEventsLayer` that emits its lifecycle events at
+    /// `level`.
+    pub fn new(level: Level) -> Self {
+        Self {
+            level,
+            compact: false,
+        }
+    }
+
+    /// Renders the `marker` field as a short single token (`+span` on enter,
+    /// `-span` on exit and close) instead of the full lifecycle name, so the
+    /// events read tersely when formatted with [`fmt::format::Compact`].
+    ///
+    /// This doesn't give the compact formatter its own dedicated rendering
+    /// path for span lifecycles — this crate predates that distinction (see
+    /// the [module-level documentation](self)) — it just picks terser field
+    /// values that compose with the compact formatter's existing
+    /// field-rendering, which already prints every field as `key=value`.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`fmt::format::Compact`]: crate::fmt::format::Compact
+    pub fn with_compact_markers(self, compact: bool) -> Self {
+        Self { compact, ..self }
+    }
+}
+
+impl Default for SpanEventsLayer {
+    /// Returns a `SpanEventsLayer` that emits its lifecycle events at
+    /// `Level::TRACE`.
+    fn default() -> Self {
+        Self::new(Level::TRACE)
+    }
+}
+
+impl<S: Subscriber> Layer<S> for SpanEventsLayer {
+    fn new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut joiner = FieldJoiner::default();
+        attrs.record(&mut joiner);
+        // A span created through the default dispatcher is created while that
+        // dispatcher is in use, so an event dispatched from here would reach
+        // no subscriber. The `open` event is therefore handed straight to the
+        // wrapped subscriber; layers stacked outside this one don't see it.
+        emit(
+            Lifecycle::Open,
+            self.level.clone(),
+            id.into_u64(),
+            self.compact,
+            Some((attrs.metadata().name(), &joiner.out)),
+            |event| ctx.event(event),
+        );
+    }
+
+    fn on_enter(&self, id: &span::Id, _ctx: Context<'_, S>) {
+        emit(Lifecycle::Enter, self.level.clone(), id.into_u64(), self.compact, None, dispatch);
+    }
+
+    fn on_exit(&self, id: &span::Id, _ctx: Context<'_, S>) {
+        emit(Lifecycle::Exit, self.level.clone(), id.into_u64(), self.compact, None, dispatch);
+    }
+
+    fn on_close(&self, id: span::Id, _ctx: Context<'_, S>) {
+        emit(Lifecycle::Close, self.level.clone(), id.into_u64(), self.compact, None, dispatch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_core::field::{Field, Visit};
+    use tracing_core::{dispatcher::Dispatch, subscriber::Interest, Metadata};
+
+    #[derive(Default)]
+    struct RecordingSubscriber {
+        span_ids: Arc<Mutex<Vec<(&'static str, u64)>>>,
+    }
+
+    struct SpanIdVisitor(Option<u64>);
+    impl Visit for SpanIdVisitor {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            if field.name() == "span.id" {
+                self.0 = Some(value);
+            }
+        }
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    impl Subscriber for RecordingSubscriber {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut visitor = SpanIdVisitor(None);
+            event.record(&mut visitor);
+            if let Some(id) = visitor.0 {
+                self.span_ids
+                    .lock()
+                    .unwrap()
+                    .push((event.metadata().name(), id));
+            }
+        }
+
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+
+        fn try_close(&self, _: span::Id) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn enter_exit_and_close_share_the_same_correlation_id() {
+        let recorder = RecordingSubscriber::default();
+        let span_ids = recorder.span_ids.clone();
+        let dispatch = Dispatch::new(recorder.with(SpanEventsLayer::new(Level::INFO)));
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("work");
+            let _enter = span.enter();
+            drop(_enter);
+            drop(span);
+        });
+
+        let seen = span_ids.lock().unwrap();
+        assert_eq!(seen.len(), 4);
+        let id = seen[0].1;
+        assert_eq!(seen[0].0, "open");
+        assert_eq!(seen[1], ("enter", id));
+        assert_eq!(seen[2], ("exit", id));
+        assert_eq!(seen[3], ("close", id));
+    }
+
+    struct NameAndFieldsVisitor {
+        name: String,
+        fields: String,
+    }
+
+    impl Visit for NameAndFieldsVisitor {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            match field.name() {
+                "span.name" => self.name = value.to_string(),
+                "fields" => self.fields = value.to_string(),
+                _ => {}
+            }
+        }
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    #[test]
+    fn open_carries_the_span_name_and_recorded_fields() {
+        #[derive(Default)]
+        struct NameFieldsSubscriber {
+            recorded: Arc<Mutex<Option<(String, String)>>>,
+        }
+
+        impl Subscriber for NameFieldsSubscriber {
+            fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+                Interest::always()
+            }
+
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                if event.metadata().name() != "open" {
+                    return;
+                }
+                let mut visitor = NameAndFieldsVisitor {
+                    name: String::new(),
+                    fields: String::new(),
+                };
+                event.record(&mut visitor);
+                *self.recorded.lock().unwrap() = Some((visitor.name, visitor.fields));
+            }
+
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let subscriber = NameFieldsSubscriber::default();
+        let recorded = subscriber.recorded.clone();
+        let dispatch = Dispatch::new(subscriber.with(SpanEventsLayer::new(Level::INFO)));
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let _span = tracing::info_span!("checkout", user_id = 42);
+        });
+
+        let (name, fields) = recorded.lock().unwrap().clone().expect("open event not seen");
+        assert_eq!(name, "checkout");
+        assert_eq!(fields, "user_id=42");
+    }
+}
@@ -0,0 +1,76 @@
+//! Helpers for carrying span context across `tokio::spawn` boundaries.
+//!
+//! A future spawned with `tokio::spawn` typically runs on a different
+//! worker thread than the task that spawned it, so it starts out with no
+//! span context at all — whatever span was current at the call site of
+//! `spawn` doesn't carry over, since span context is tracked per-thread.
+//! [`spawn_instrumented`] captures that span and uses
+//! [`tracing_futures::Instrument`] to re-enter it every time the spawned
+//! future is polled, so events and spans produced while it runs are still
+//! attributed to the span that was active when it was spawned.
+use tokio::executor::{spawn, Spawn};
+use tracing::Span;
+use tracing_futures::Instrument;
+
+/// Spawns `future` on the default Tokio executor, instrumented with the
+/// [current span](Span::current).
+///
+/// This is equivalent to `tokio::spawn(future.instrument(Span::current()))`,
+/// but makes the span-propagating intent explicit at the call site.
+///
+/// # Panics
+///
+/// Like [`tokio::spawn`], this panics if called outside the context of a
+/// Tokio executor.
+pub fn spawn_instrumented<F>(future: F) -> Spawn
+where
+    F: futures::Future<Item = (), Error = ()> + Send + 'static,
+{
+    spawn(future.instrument(Span::current()))
+}
+
+/// Extension trait for spawning a future on a Tokio executor with the
+/// current span automatically propagated into it.
+///
+/// This is provided as a trait, in addition to the free function
+/// [`spawn_instrumented`], so the instrumented spawn can be chained
+/// fluently at the end of a future-building expression.
+pub trait SpawnExt: futures::Future<Item = (), Error = ()> + Send + Sized + 'static {
+    /// Instruments this future with the [current span](Span::current) and
+    /// spawns it on the default Tokio executor.
+    fn spawn_instrumented(self) -> Spawn {
+        spawn_instrumented(self)
+    }
+}
+
+impl<F> SpawnExt for F where F: futures::Future<Item = (), Error = ()> + Send + 'static {}
+
+#[cfg(test)]
+mod tests {
+    use super::spawn_instrumented;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn spawned_future_runs_inside_the_captured_span() {
+        let observed_name = Arc::new(Mutex::new(None));
+        let observed_name2 = observed_name.clone();
+
+        tokio::run(futures::lazy(move || {
+            let span = tracing::info_span!("outer");
+            let _guard = span.enter();
+
+            // Run on the threadpool, likely a different worker thread than
+            // this one: without `spawn_instrumented`, the span above would
+            // not be current when this runs.
+            spawn_instrumented(futures::lazy(move || {
+                let name = tracing::Span::current().metadata().map(|m| m.name());
+                *observed_name2.lock().unwrap() = name;
+                Ok(())
+            }));
+
+            Ok(())
+        }));
+
+        assert_eq!(*observed_name.lock().unwrap(), Some("outer"));
+    }
+}
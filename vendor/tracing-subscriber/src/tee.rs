@@ -0,0 +1,233 @@
+//! A `Subscriber` that forwards every call to two other `Subscriber`s, for
+//! running two complete, independent subscribers side by side.
+use crate::sync::RwLock;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing_core::{
+    span, subscriber::Interest, subscriber::Subscriber, Event, Metadata,
+};
+
+/// A `Subscriber` that owns two other `Subscriber`s and forwards every call
+/// to both.
+///
+/// This predates the `Layer` model: before a single subscriber could be
+/// composed out of layers, running two independent, complete subscribers
+/// (for example, a real one and a test-capturing one) required standing up
+/// two entirely separate dispatch stacks. `Tee` lets both run under a single
+/// `Dispatch` instead.
+///
+/// ## Id reconciliation
+///
+/// `a` and `b` each mint their own [`Id`]s for a span, which are not in
+/// general equal to each other, so `Tee` cannot simply reuse either child's
+/// `Id`. Instead, when [`new_span`] is called, `Tee` asks both children to
+/// create the span, mints an `Id` of its own, and records the mapping from
+/// its `Id` to the pair of child `Id`s. Every later call that takes an `Id`
+/// (`record`, `record_follows_from`, `enter`, `exit`, `clone_span`,
+/// `try_close`) looks up that pair and forwards to each child with its own
+/// `Id`. The mapping is dropped once [`try_close`] reports the span has no
+/// more handles on `Tee`'s side.
+///
+/// [`enabled`] is the logical OR of both children (a span or event enabled
+/// by either child is enabled); [`register_callsite`]'s [`Interest`] is
+/// combined the same way (never only if both children say never, always if
+/// either says always, sometimes otherwise).
+///
+/// `Tee` does not track a current span of its own: [`current_span`] keeps
+/// the default "unknown" behavior, since resolving a current span correctly
+/// would mean picking one child's view over the other's. This version of
+/// `tracing-core`'s `Subscriber` trait also has no `max_level_hint` to
+/// combine.
+///
+/// [`Id`]: https://docs.rs/tracing-core/latest/tracing_core/span/struct.Id.html
+/// [`new_span`]: #method.new_span
+/// [`try_close`]: #method.try_close
+/// [`enabled`]: #method.enabled
+/// [`register_callsite`]: #method.register_callsite
+/// [`Interest`]: https://docs.rs/tracing-core/latest/tracing_core/subscriber/struct.Interest.html
+/// [`current_span`]: #method.current_span
+#[derive(Debug)]
+pub struct Tee<A, B> {
+    a: A,
+    b: B,
+    spans: RwLock<HashMap<span::Id, (span::Id, span::Id)>>,
+    next_id: AtomicU64,
+}
+
+impl<A, B> Tee<A, B> {
+    /// Returns a new `Tee` that forwards every call to both `a` and `b`.
+    pub fn new(a: A, b: B) -> Self {
+        Self {
+            a,
+            b,
+            spans: RwLock::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    fn children_of(&self, id: &span::Id) -> Option<(span::Id, span::Id)> {
+        try_lock!(self.spans.read(), else return None).get(id).cloned()
+    }
+}
+
+impl<A, B> Subscriber for Tee<A, B>
+where
+    A: Subscriber,
+    B: Subscriber,
+{
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        let a = self.a.register_callsite(metadata);
+        let b = self.b.register_callsite(metadata);
+        if a.is_never() && b.is_never() {
+            Interest::never()
+        } else if a.is_always() || b.is_always() {
+            Interest::always()
+        } else {
+            Interest::sometimes()
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.a.enabled(metadata) || self.b.enabled(metadata)
+    }
+
+    fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+        let id_a = self.a.new_span(span);
+        let id_b = self.b.new_span(span);
+        let id = span::Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed));
+        try_lock!(self.spans.write(), else return id.clone()).insert(id.clone(), (id_a, id_b));
+        id
+    }
+
+    fn record(&self, span: &span::Id, values: &span::Record<'_>) {
+        if let Some((id_a, id_b)) = self.children_of(span) {
+            self.a.record(&id_a, values);
+            self.b.record(&id_b, values);
+        }
+    }
+
+    fn record_follows_from(&self, span: &span::Id, follows: &span::Id) {
+        if let (Some((span_a, span_b)), Some((follows_a, follows_b))) =
+            (self.children_of(span), self.children_of(follows))
+        {
+            self.a.record_follows_from(&span_a, &follows_a);
+            self.b.record_follows_from(&span_b, &follows_b);
+        }
+    }
+
+    fn event(&self, event: &Event<'_>) {
+        self.a.event(event);
+        self.b.event(event);
+    }
+
+    fn enter(&self, span: &span::Id) {
+        if let Some((id_a, id_b)) = self.children_of(span) {
+            self.a.enter(&id_a);
+            self.b.enter(&id_b);
+        }
+    }
+
+    fn exit(&self, span: &span::Id) {
+        if let Some((id_a, id_b)) = self.children_of(span) {
+            self.a.exit(&id_a);
+            self.b.exit(&id_b);
+        }
+    }
+
+    fn clone_span(&self, id: &span::Id) -> span::Id {
+        if let Some((id_a, id_b)) = self.children_of(id) {
+            self.a.clone_span(&id_a);
+            self.b.clone_span(&id_b);
+        }
+        id.clone()
+    }
+
+    fn try_close(&self, id: span::Id) -> bool {
+        let children = self.children_of(&id);
+        let (id_a, id_b) = match children {
+            Some(children) => children,
+            None => return false,
+        };
+        let a_closed = self.a.try_close(id_a);
+        let b_closed = self.b.try_close(id_b);
+        let closed = a_closed && b_closed;
+        if closed {
+            try_lock!(self.spans.write(), else return false).remove(&id);
+        }
+        closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct CountingSubscriber {
+        events: Arc<Mutex<usize>>,
+        entered: Arc<Mutex<Vec<u64>>>,
+    }
+
+    impl Subscriber for CountingSubscriber {
+        fn register_callsite(&self, _: &'static Metadata<'static>) -> Interest {
+            Interest::always()
+        }
+
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, _: &Event<'_>) {
+            *self.events.lock().unwrap() += 1;
+        }
+
+        fn enter(&self, id: &span::Id) {
+            self.entered.lock().unwrap().push(id.into_u64());
+        }
+
+        fn exit(&self, _: &span::Id) {}
+
+        fn try_close(&self, _: span::Id) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn events_reach_both_children() {
+        let a = CountingSubscriber::default();
+        let b = CountingSubscriber::default();
+        let tee = Tee::new(a.clone(), b.clone());
+        let dispatch = tracing_core::dispatcher::Dispatch::new(tee);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            tracing::info!("hello");
+        });
+
+        assert_eq!(*a.events.lock().unwrap(), 1);
+        assert_eq!(*b.events.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn spans_are_entered_on_both_children_with_their_own_ids() {
+        let a = CountingSubscriber::default();
+        let b = CountingSubscriber::default();
+        let tee = Tee::new(a.clone(), b.clone());
+        let dispatch = tracing_core::dispatcher::Dispatch::new(tee);
+
+        tracing_core::dispatcher::with_default(&dispatch, || {
+            let span = tracing::info_span!("my_span");
+            let _enter = span.enter();
+        });
+
+        assert_eq!(*a.entered.lock().unwrap(), vec![1]);
+        assert_eq!(*b.entered.lock().unwrap(), vec![1]);
+    }
+}
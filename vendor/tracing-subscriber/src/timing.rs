@@ -0,0 +1,186 @@
+//! A `Layer` that measures the elapsed time between two paired events within
+//! a span, similar to [`tracing-timing`](https://docs.rs/tracing-timing).
+use crate::histogram::Histogram;
+use crate::layer::{Context, Layer};
+use crate::sync::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing_core::{span, subscriber::Subscriber, Event};
+
+/// A `Layer` that records the elapsed time between a pair of events within
+/// the same span, such as `request.start` and `request.end`.
+///
+/// Pairs are identified by their events' [`target`]s, since this version of
+/// `tracing-core` has no notion of a event-level "name" distinct from its
+/// target or message. Configure the layer with the `(start_target,
+/// end_target)` pairs to watch for; when the start event of a pair is seen,
+/// its time is recorded for the current span, and when the matching end
+/// event is seen in the same span, the elapsed time is recorded into a
+/// histogram keyed by the end target. Call [`snapshot`] to get the current
+/// p50/p95/p99 latencies for each pair.
+///
+/// Events outside of a span, or whose start event was never observed in the
+/// current span, are ignored.
+///
+/// [`target`]: https://docs.rs/tracing-core/latest/tracing_core/metadata/struct.Metadata.html#method.target
+/// [`snapshot`]: #method.snapshot
+#[derive(Clone, Debug)]
+pub struct PairedTimingLayer {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    pairs: Vec<(&'static str, &'static str)>,
+    starts: RwLock<HashMap<(span::Id, &'static str), Instant>>,
+    histograms: RwLock<HashMap<&'static str, Histogram>>,
+}
+
+/// A snapshot of the recorded latency percentiles for a single event pair,
+/// keyed by the pair's end target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Percentiles {
+    /// The number of times this pair's end event has been observed following
+    /// a matching start event.
+    pub count: u64,
+    /// The 50th percentile latency, in nanoseconds.
+    pub p50_nanos: u64,
+    /// The 95th percentile latency, in nanoseconds.
+    pub p95_nanos: u64,
+    /// The 99th percentile latency, in nanoseconds.
+    pub p99_nanos: u64,
+}
+
+impl PairedTimingLayer {
+    /// Returns a new `PairedTimingLayer` watching for the given
+    /// `(start_target, end_target)` pairs.
+    pub fn new(pairs: impl Into<Vec<(&'static str, &'static str)>>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                pairs: pairs.into(),
+                starts: RwLock::new(HashMap::new()),
+                histograms: RwLock::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Returns a snapshot of the current percentiles for every pair whose
+    /// end event has been observed at least once, keyed by the pair's end
+    /// target.
+    pub fn snapshot(&self) -> HashMap<&'static str, Percentiles> {
+        let histograms = try_lock!(self.inner.histograms.read(), else return HashMap::new());
+        histograms
+            .iter()
+            .map(|(&end, h)| {
+                (
+                    end,
+                    Percentiles {
+                        count: h.count(),
+                        p50_nanos: h.percentile(0.50),
+                        p95_nanos: h.percentile(0.95),
+                        p99_nanos: h.percentile(0.99),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn start_target_for(&self, end_target: &str) -> Option<&'static str> {
+        self.inner
+            .pairs
+            .iter()
+            .find(|(_, end)| *end == end_target)
+            .map(|(start, _)| *start)
+    }
+
+    fn is_start_target(&self, target: &str) -> bool {
+        self.inner.pairs.iter().any(|(start, _)| *start == target)
+    }
+}
+
+impl<S: Subscriber> Layer<S> for PairedTimingLayer {
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let span_id = match ctx.current_span().id() {
+            Some(id) => id.clone(),
+            None => return,
+        };
+        let target = event.metadata().target();
+
+        if self.is_start_target(target) {
+            try_lock!(self.inner.starts.write()).insert((span_id, target), Instant::now());
+            return;
+        }
+
+        let start_target = match self.start_target_for(target) {
+            Some(start_target) => start_target,
+            None => return,
+        };
+        let start = try_lock!(self.inner.starts.write()).remove(&(span_id, start_target));
+        if let Some(start) = start {
+            let nanos = start.elapsed().as_nanos().min(u128::from(u64::max_value())) as u64;
+            try_lock!(self.inner.histograms.write())
+                .entry(target)
+                .or_insert_with(Histogram::default)
+                .record(nanos);
+        }
+    }
+
+    fn on_close(&self, id: span::Id, _ctx: Context<'_, S>) {
+        let mut starts = try_lock!(self.inner.starts.write());
+        let stale: Vec<_> = starts
+            .keys()
+            .filter(|(span_id, _)| *span_id == id)
+            .cloned()
+            .collect();
+        for key in stale {
+            starts.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn records_elapsed_time_between_paired_events() {
+        let layer = PairedTimingLayer::new(vec![("request.start", "request.end")]);
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer.clone()),
+        );
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let span = tracing::info_span!("request");
+            let _enter = span.enter();
+            tracing::event!(target: "request.start", tracing::Level::TRACE, "begin");
+            thread::sleep(Duration::from_millis(1));
+            tracing::event!(target: "request.end", tracing::Level::TRACE, "end");
+        });
+
+        let snapshot = layer.snapshot();
+        let pair = snapshot
+            .get("request.end")
+            .expect("should have recorded the pair");
+        assert_eq!(pair.count, 1);
+        assert!(pair.p50_nanos > 0);
+    }
+
+    #[test]
+    fn end_without_start_is_ignored() {
+        let layer = PairedTimingLayer::new(vec![("request.start", "request.end")]);
+        let subscriber = tracing_core::dispatcher::Dispatch::new(
+            crate::layer::tests::TrackingSubscriber::default().with(layer.clone()),
+        );
+
+        tracing_core::dispatcher::with_default(&subscriber, || {
+            let span = tracing::info_span!("request");
+            let _enter = span.enter();
+            tracing::event!(target: "request.end", tracing::Level::TRACE, "end");
+        });
+
+        assert!(layer.snapshot().is_empty());
+    }
+}
@@ -741,6 +741,43 @@ macro_rules! event {
     );
 }
 
+/// Constructs a new `Event`, like [`event!`], but also records the literal
+/// format string as a `message.template` field alongside the rendered
+/// `message`.
+///
+/// This is opt-in: ordinary [`event!`] (and the `trace!`/`debug!`/`info!`/
+/// `warn!`/`error!` macros built on it) never emit `message.template`.
+/// Pipelines that want to group events by their template, independently of
+/// the values interpolated into it on a given call, should use this macro
+/// instead for the calls they care about.
+///
+/// Only the plain and `target:` forms of the [`event!`] syntax are
+/// supported; a literal format string and its arguments (if any) must
+/// follow any other fields, exactly as with `event!`.
+///
+/// [`event!`]: macro.event.html
+///
+/// # Examples
+///
+/// ```rust
+/// use tracing::{event_with_template, Level};
+///
+/// # fn main() {
+/// let id = 42;
+/// event_with_template!(Level::INFO, "user {} logged in", id);
+/// event_with_template!(target: "app_events", Level::INFO, "user {} logged in", id);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! event_with_template {
+    (target: $target:expr, $lvl:expr, $fmt:literal $(, $arg:expr)* $(,)?) => (
+        $crate::event!(target: $target, $lvl, { message.template = $fmt }, $fmt $(, $arg)*)
+    );
+    ($lvl:expr, $fmt:literal $(, $arg:expr)* $(,)?) => (
+        $crate::event!(target: module_path!(), $lvl, { message.template = $fmt }, $fmt $(, $arg)*)
+    );
+}
+
 /// Constructs an event at the trace level.
 ///
 /// This functions similarly to the [`event!`] macro. See [the top-level
@@ -1831,6 +1868,10 @@ macro_rules! callsite {
             fn metadata(&self) -> &Metadata {
                 &META
             }
+
+            fn interest(&self) -> Interest {
+                self.interest()
+            }
         }
         REGISTRATION.call_once(|| {
             callsite::register(&MyCallsite);
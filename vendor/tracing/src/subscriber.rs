@@ -19,6 +19,25 @@ where
     crate::dispatcher::with_default(&crate::Dispatch::new(subscriber), f)
 }
 
+/// Sets this subscriber as the default for the current thread, returning a
+/// guard that resets the default subscriber to the prior default when it is
+/// dropped.
+///
+/// Unlike [`with_default`], which only sets the default for the duration of
+/// a closure, this allows the default to be set for an arbitrarily-scoped
+/// section of code, as determined by the returned guard's lifetime. This is
+/// especially useful in tests, where `with_default` would otherwise force
+/// every assertion into a single closure.
+///
+/// [`with_default`]: fn.with_default.html
+#[cfg(feature = "std")]
+pub fn set_default<S>(subscriber: S) -> crate::dispatcher::DefaultGuard
+where
+    S: Subscriber + Send + Sync + 'static,
+{
+    crate::dispatcher::set_default(&crate::Dispatch::new(subscriber))
+}
+
 /// Sets this subscriber as the global default for the duration of the entire program.
 /// Will be used as a fallback if no thread-local subscriber has been set in a thread (using `with_default`.)
 ///
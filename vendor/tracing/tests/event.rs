@@ -349,3 +349,51 @@ fn explicit_child_at_levels() {
 
     handle.assert_finished();
 }
+
+#[test]
+fn event_with_template_records_both_message_and_template() {
+    let (subscriber, handle) = subscriber::mock()
+        .event(
+            event::mock().with_fields(
+                field::mock("message")
+                    .with_value(&tracing::field::debug(format_args!(
+                        "user {} logged in",
+                        42
+                    )))
+                    .and(field::mock("message.template").with_value(&"user {} logged in"))
+                    .only(),
+            ),
+        )
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        let id = 42;
+        event_with_template!(Level::INFO, "user {} logged in", id);
+    });
+
+    handle.assert_finished();
+}
+
+#[test]
+fn event_with_template_and_explicit_target() {
+    let (subscriber, handle) = subscriber::mock()
+        .event(
+            event::mock()
+                .with_target("app_events")
+                .with_fields(
+                    field::mock("message")
+                        .with_value(&tracing::field::debug(format_args!("no args here")))
+                        .and(field::mock("message.template").with_value(&"no args here"))
+                        .only(),
+                ),
+        )
+        .done()
+        .run_with_handle();
+
+    with_default(subscriber, || {
+        event_with_template!(target: "app_events", Level::INFO, "no args here");
+    });
+
+    handle.assert_finished();
+}